@@ -1,3 +1,4 @@
+use duckdb::Connection;
 use tauri::State;
 use uuid::Uuid;
 
@@ -5,6 +6,35 @@ use crate::error::Result;
 use crate::models::{ChatMessage, Conversation, ConversationWithMessages};
 use crate::state::AppState;
 
+/// Conversations and their messages. `model` was added later, hence the ALTER.
+const CONVERSATIONS_DDL: &str = r#"
+    CREATE TABLE IF NOT EXISTS _duckbake_conversations (
+        id VARCHAR PRIMARY KEY,
+        project_id VARCHAR NOT NULL,
+        title VARCHAR NOT NULL,
+        created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE IF NOT EXISTS _duckbake_messages (
+        id VARCHAR PRIMARY KEY,
+        conversation_id VARCHAR NOT NULL,
+        role VARCHAR NOT NULL,
+        content TEXT NOT NULL,
+        created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        FOREIGN KEY (conversation_id) REFERENCES _duckbake_conversations(id)
+    );
+    ALTER TABLE _duckbake_conversations ADD COLUMN IF NOT EXISTS model VARCHAR;
+"#;
+
+/// Unsent message text, one per conversation
+const DRAFTS_DDL: &str = r#"
+    CREATE TABLE IF NOT EXISTS _duckbake_drafts (
+        conversation_id VARCHAR PRIMARY KEY,
+        content TEXT NOT NULL,
+        updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    );
+"#;
+
 #[tauri::command]
 pub async fn list_conversations(
     state: State<'_, AppState>,
@@ -20,31 +50,14 @@ pub async fn list_conversations(
     let conn = conn.lock();
 
     // Ensure conversations table exists
-    conn.execute_batch(
-        r#"
-        CREATE TABLE IF NOT EXISTS _duckbake_conversations (
-            id VARCHAR PRIMARY KEY,
-            project_id VARCHAR NOT NULL,
-            title VARCHAR NOT NULL,
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        );
-        CREATE TABLE IF NOT EXISTS _duckbake_messages (
-            id VARCHAR PRIMARY KEY,
-            conversation_id VARCHAR NOT NULL,
-            role VARCHAR NOT NULL,
-            content TEXT NOT NULL,
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (conversation_id) REFERENCES _duckbake_conversations(id)
-        );
-        "#,
-    )?;
+    conn.execute_batch(CONVERSATIONS_DDL)?;
 
     let mut stmt = conn.prepare(
         r#"
         SELECT id, project_id, title,
                CAST(created_at AS VARCHAR) as created_at,
-               CAST(updated_at AS VARCHAR) as updated_at
+               CAST(updated_at AS VARCHAR) as updated_at,
+               model
         FROM _duckbake_conversations
         WHERE project_id = ?
         ORDER BY updated_at DESC
@@ -59,6 +72,7 @@ pub async fn list_conversations(
                 title: row.get(2)?,
                 created_at: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
                 updated_at: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                model: row.get(5)?,
             })
         })?
         .filter_map(|r| r.ok())
@@ -73,35 +87,17 @@ pub async fn create_conversation(
     project_id: String,
     title: Option<String>,
 ) -> Result<Conversation> {
-    let db_path = {
+    let (db_path, model) = {
         let storage = state.storage.lock();
         let project = storage.get_project(&project_id)?;
-        storage.get_database_path(&project)
+        (storage.get_database_path(&project), project.chat_model)
     };
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
 
     // Ensure table exists
-    conn.execute_batch(
-        r#"
-        CREATE TABLE IF NOT EXISTS _duckbake_conversations (
-            id VARCHAR PRIMARY KEY,
-            project_id VARCHAR NOT NULL,
-            title VARCHAR NOT NULL,
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        );
-        CREATE TABLE IF NOT EXISTS _duckbake_messages (
-            id VARCHAR PRIMARY KEY,
-            conversation_id VARCHAR NOT NULL,
-            role VARCHAR NOT NULL,
-            content TEXT NOT NULL,
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (conversation_id) REFERENCES _duckbake_conversations(id)
-        );
-        "#,
-    )?;
+    conn.execute_batch(CONVERSATIONS_DDL)?;
 
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
@@ -109,10 +105,10 @@ pub async fn create_conversation(
 
     conn.execute(
         r#"
-        INSERT INTO _duckbake_conversations (id, project_id, title, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?)
+        INSERT INTO _duckbake_conversations (id, project_id, title, created_at, updated_at, model)
+        VALUES (?, ?, ?, ?, ?, ?)
         "#,
-        duckdb::params![&id, &project_id, &title, &now, &now],
+        duckdb::params![&id, &project_id, &title, &now, &now, &model],
     )?;
 
     Ok(Conversation {
@@ -121,6 +117,7 @@ pub async fn create_conversation(
         title,
         created_at: now.clone(),
         updated_at: now,
+        model,
     })
 }
 
@@ -138,13 +135,15 @@ pub async fn get_conversation(
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
+    conn.execute_batch(CONVERSATIONS_DDL)?;
 
     // Get conversation
     let conversation: Conversation = conn.query_row(
         r#"
         SELECT id, project_id, title,
                CAST(created_at AS VARCHAR) as created_at,
-               CAST(updated_at AS VARCHAR) as updated_at
+               CAST(updated_at AS VARCHAR) as updated_at,
+               model
         FROM _duckbake_conversations
         WHERE id = ?
         "#,
@@ -156,6 +155,7 @@ pub async fn get_conversation(
                 title: row.get(2)?,
                 created_at: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
                 updated_at: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                model: row.get(5)?,
             })
         },
     )?;
@@ -189,6 +189,7 @@ pub async fn get_conversation(
         title: conversation.title,
         created_at: conversation.created_at,
         updated_at: conversation.updated_at,
+        model: conversation.model,
         messages,
     })
 }
@@ -208,6 +209,7 @@ pub async fn update_conversation(
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
+    conn.execute_batch(CONVERSATIONS_DDL)?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -224,7 +226,8 @@ pub async fn update_conversation(
         r#"
         SELECT id, project_id, title,
                CAST(created_at AS VARCHAR) as created_at,
-               CAST(updated_at AS VARCHAR) as updated_at
+               CAST(updated_at AS VARCHAR) as updated_at,
+               model
         FROM _duckbake_conversations
         WHERE id = ?
         "#,
@@ -236,6 +239,7 @@ pub async fn update_conversation(
                 title: row.get(2)?,
                 created_at: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
                 updated_at: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                model: row.get(5)?,
             })
         },
     )?;
@@ -263,6 +267,7 @@ pub async fn delete_conversation(
         "DELETE FROM _duckbake_messages WHERE conversation_id = ?",
         [&conversation_id],
     )?;
+    clear_draft(&conn, &conversation_id)?;
 
     // Delete conversation
     conn.execute(
@@ -307,6 +312,11 @@ pub async fn add_message(
         duckdb::params![&now, &conversation_id],
     )?;
 
+    // The draft was this message, so it's no longer needed
+    if role == "user" {
+        clear_draft(&conn, &conversation_id)?;
+    }
+
     Ok(ChatMessage {
         id,
         role,
@@ -315,3 +325,72 @@ pub async fn add_message(
         context_tables: None,
     })
 }
+
+/// Store the unsent text of a conversation's message box. Empty text removes the draft.
+#[tauri::command]
+pub async fn save_draft(
+    state: State<'_, AppState>,
+    project_id: String,
+    conversation_id: String,
+    content: String,
+) -> Result<()> {
+    let db_path = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        storage.get_database_path(&project)
+    };
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+
+    if content.trim().is_empty() {
+        return clear_draft(&conn, &conversation_id);
+    }
+
+    conn.execute_batch(DRAFTS_DDL)?;
+    conn.execute(
+        r#"
+        INSERT OR REPLACE INTO _duckbake_drafts (conversation_id, content, updated_at)
+        VALUES (?, ?, ?)
+        "#,
+        duckdb::params![&conversation_id, &content, chrono::Utc::now().to_rfc3339()],
+    )?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_draft(
+    state: State<'_, AppState>,
+    project_id: String,
+    conversation_id: String,
+) -> Result<Option<String>> {
+    let db_path = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        storage.get_database_path(&project)
+    };
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+
+    conn.execute_batch(DRAFTS_DDL)?;
+    let content = conn
+        .query_row(
+            "SELECT content FROM _duckbake_drafts WHERE conversation_id = ?",
+            [&conversation_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    Ok(content)
+}
+
+fn clear_draft(conn: &Connection, conversation_id: &str) -> Result<()> {
+    conn.execute_batch(DRAFTS_DDL)?;
+    conn.execute(
+        "DELETE FROM _duckbake_drafts WHERE conversation_id = ?",
+        [conversation_id],
+    )?;
+    Ok(())
+}
@@ -1,11 +1,45 @@
-use tauri::State;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tracing::{info, warn};
 
-use crate::error::Result;
-use crate::models::{ProjectContext, QueryResult, TableContext, TableInfo, TableSchema};
+use crate::error::{AppError, Result};
+use crate::models::{
+    AggregateFn, AggregateResult, AttachedDatabase, ChatQueryResult, ColumnTypeChange,
+    ConnectionCapabilities, DedupeResult, DistinctValues, DuckDbSettings, GeneratedColumn,
+    OpenConnection, ProjectContext, QueryResult, QueryStreamEvent, QueryValidation, RowCount,
+    SeekPage, StorageBreakdown, TableContext, TableDiff, TableInfo, TableSchema, TableSummary,
+    TimeBucket, VizSuggestion,
+};
+use crate::services::{quote_ident, DuckDbService, ImportMode, ImportResult};
 use crate::state::AppState;
 
 #[tauri::command]
-pub async fn get_tables(state: State<'_, AppState>, project_id: String) -> Result<Vec<TableInfo>> {
+pub async fn get_tables(
+    state: State<'_, AppState>,
+    project_id: String,
+    include_attached: Option<bool>,
+) -> Result<Vec<TableInfo>> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    let mut tables = state.duckdb.get_tables(&conn)?;
+    if include_attached.unwrap_or(false) {
+        tables.extend(state.duckdb.get_attached_tables(&conn, &project_id)?);
+    }
+    Ok(tables)
+}
+
+/// Tables with estimated row counts and sizes, cheap enough for the initial sidebar render
+#[tauri::command]
+pub async fn list_tables_fast(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<Vec<TableSummary>> {
     let storage = state.storage.lock();
     let project = storage.get_project(&project_id)?;
     let db_path = storage.get_database_path(&project);
@@ -13,7 +47,7 @@ pub async fn get_tables(state: State<'_, AppState>, project_id: String) -> Resul
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
-    state.duckdb.get_tables(&conn)
+    state.duckdb.list_tables_fast(&conn)
 }
 
 #[tauri::command]
@@ -48,6 +82,158 @@ pub async fn execute_query(
     state.duckdb.execute_query(&conn, &sql)
 }
 
+/// Run a query block from a chat reply, suggesting (and with `auto_correct`, trying)
+/// the closest real name when it references a table or column that doesn't exist
+#[tauri::command]
+pub async fn execute_chat_query(
+    state: State<'_, AppState>,
+    project_id: String,
+    sql: String,
+    auto_correct: Option<bool>,
+) -> Result<ChatQueryResult> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    Ok(state.duckdb.execute_chat_query(&conn, &sql, auto_correct.unwrap_or(false)))
+}
+
+/// Chart type and axes that suit a result, for when a chat reply's `viz` is
+/// missing or doesn't fit the data
+#[tauri::command]
+pub async fn suggest_visualization(query_result: QueryResult) -> VizSuggestion {
+    DuckDbService::suggest_visualization(&query_result)
+}
+
+/// Grouped aggregate of a table for charting without writing SQL, with a matching
+/// chart spec. `measure` may be left out for `count`. A `bucket` groups a date or
+/// timestamp column by period, and `fill_gaps` adds the periods with no rows.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn aggregate(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    group_by: String,
+    measure: Option<String>,
+    agg: AggregateFn,
+    bucket: Option<TimeBucket>,
+    fill_gaps: Option<bool>,
+) -> Result<AggregateResult> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.aggregate(
+        &conn,
+        &table_name,
+        &group_by,
+        measure.as_deref(),
+        agg,
+        bucket,
+        fill_gaps.unwrap_or(false),
+    )
+}
+
+/// Rows per message sent by `execute_query_streamed`
+const STREAM_BATCH_ROWS: usize = 1000;
+
+/// Run a query and push its rows over `channel` in batches as they are read, so the
+/// UI can render progressively. `stream_id` identifies the stream for
+/// `cancel_query_stream`.
+#[tauri::command]
+pub async fn execute_query_streamed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    sql: String,
+    stream_id: String,
+    channel: Channel<QueryStreamEvent>,
+) -> Result<()> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    state.clear_query_stream_cancellation(&stream_id);
+
+    // Reading a large result takes a while, keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let conn = conn.lock();
+        let result = state.duckdb.execute_query_streamed(
+            &conn,
+            &sql,
+            STREAM_BATCH_ROWS,
+            &mut |event| {
+                let complete = matches!(event, QueryStreamEvent::Complete { .. });
+                if !complete && state.should_cancel_query_stream(&stream_id) {
+                    return false;
+                }
+                channel.send(event).is_ok()
+            },
+        );
+        state.clear_query_stream_cancellation(&stream_id);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Query stream task failed: {}", e)))?
+}
+
+#[tauri::command]
+pub async fn cancel_query_stream(state: State<'_, AppState>, stream_id: String) -> Result<()> {
+    state.cancel_query_stream(&stream_id);
+    Ok(())
+}
+
+/// Check a query's syntax and references without running it, for live editor feedback
+#[tauri::command]
+pub async fn validate_query(
+    state: State<'_, AppState>,
+    project_id: String,
+    sql: String,
+) -> Result<QueryValidation> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    Ok(state.duckdb.validate_query(&conn, &sql))
+}
+
+/// Save a query's results as a new table, or replace/append to an existing one
+#[tauri::command]
+pub async fn materialize_query(
+    state: State<'_, AppState>,
+    project_id: String,
+    sql: String,
+    table_name: String,
+    mode: ImportMode,
+) -> Result<ImportResult> {
+    let _job = state.start_project_job(
+        &project_id,
+        &format!("writing query results to {}", table_name),
+    )?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.materialize_query(&conn, &sql, &table_name, mode)
+}
+
 #[tauri::command]
 pub async fn query_table(
     state: State<'_, AppState>,
@@ -57,6 +243,7 @@ pub async fn query_table(
     page_size: u32,
     order_by: Option<String>,
     order_desc: Option<bool>,
+    include_rowid: Option<bool>,
 ) -> Result<QueryResult> {
     let storage = state.storage.lock();
     let project = storage.get_project(&project_id)?;
@@ -65,7 +252,286 @@ pub async fn query_table(
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
-    state.duckdb.query_table(&conn, &table_name, page, page_size, order_by.as_deref(), order_desc.unwrap_or(false))
+    state.duckdb.query_table(
+        &conn,
+        &table_name,
+        page,
+        page_size,
+        order_by.as_deref(),
+        order_desc.unwrap_or(false),
+        include_rowid.unwrap_or(false),
+    )
+}
+
+/// Page through a table by key instead of offset, for fast forward scrolling.
+/// Pass the previous page's `last_key` as `after_key`, or None for the first page.
+#[tauri::command]
+pub async fn query_table_seek(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    after_key: Option<serde_json::Value>,
+    order_by: String,
+    page_size: u32,
+) -> Result<SeekPage> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state
+        .duckdb
+        .query_table_seek(&conn, &table_name, after_key.as_ref(), &order_by, page_size)
+}
+
+#[tauri::command]
+pub async fn get_random_sample(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    n: u32,
+) -> Result<QueryResult> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.get_random_sample(&conn, &table_name, n)
+}
+
+#[tauri::command]
+pub async fn count_rows(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    where_clause: Option<String>,
+    exact: Option<bool>,
+) -> Result<RowCount> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.count_rows(
+        &conn,
+        &table_name,
+        where_clause.as_deref(),
+        exact.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub async fn get_distinct_values(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    column: String,
+    limit: Option<u32>,
+) -> Result<DistinctValues> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state
+        .duckdb
+        .get_distinct_values(&conn, &table_name, &column, limit.unwrap_or(100))
+}
+
+/// CREATE TABLE statement for a user table
+#[tauri::command]
+pub async fn get_table_ddl(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+) -> Result<String> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.get_table_ddl(&conn, &table_name)
+}
+
+/// CREATE TABLE statements for all of a project's user tables, one after another
+#[tauri::command]
+pub async fn get_project_schema_sql(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<String> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.get_schema_sql(&conn)
+}
+
+/// Compare table B against table A. Rows are only compared when `compare_rows` is set,
+/// returning up to `row_limit` (default 100) differing rows each way.
+#[tauri::command]
+pub async fn diff_tables(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_a: String,
+    table_b: String,
+    compare_rows: Option<bool>,
+    row_limit: Option<u32>,
+) -> Result<TableDiff> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    let row_limit = compare_rows.unwrap_or(false).then(|| row_limit.unwrap_or(100));
+    state.duckdb.diff_tables(&conn, &table_a, &table_b, row_limit)
+}
+
+#[tauri::command]
+pub async fn rename_column(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    old_name: String,
+    new_name: String,
+) -> Result<TableSchema> {
+    let _job = state.start_project_job(
+        &project_id,
+        &format!("renaming a column of {}", table_name),
+    )?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.rename_column(&conn, &table_name, &old_name, &new_name)
+}
+
+#[tauri::command]
+pub async fn add_column(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    name: String,
+    data_type: String,
+    default: Option<String>,
+) -> Result<TableSchema> {
+    let _job = state.start_project_job(&project_id, &format!("adding a column to {}", table_name))?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.add_column(&conn, &table_name, &name, &data_type, default.as_deref())
+}
+
+/// Add a column computed from `expression`, e.g. `price * quantity`
+#[tauri::command]
+pub async fn add_generated_column(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    name: String,
+    expression: String,
+) -> Result<GeneratedColumn> {
+    let _job = state.start_project_job(&project_id, &format!("adding a column to {}", table_name))?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.add_generated_column(&conn, &table_name, &name, &expression)
+}
+
+#[tauri::command]
+pub async fn drop_column(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    name: String,
+) -> Result<TableSchema> {
+    let _job = state.start_project_job(
+        &project_id,
+        &format!("dropping a column of {}", table_name),
+    )?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.drop_column(&conn, &table_name, &name)
+}
+
+/// Remove duplicate rows, keeping one per distinct `key_columns` combination (or
+/// per distinct row when none are given)
+#[tauri::command]
+pub async fn deduplicate_table(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    key_columns: Option<Vec<String>>,
+) -> Result<DedupeResult> {
+    let _job = state.start_project_job(&project_id, &format!("deduplicating {}", table_name))?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.deduplicate_table(&conn, &table_name, &key_columns.unwrap_or_default())
+}
+
+/// Convert a column to `new_type`, e.g. numbers imported as text to `DOUBLE`. Values
+/// that don't convert become NULL and are counted in the result.
+#[tauri::command]
+pub async fn change_column_type(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    column: String,
+    new_type: String,
+) -> Result<ColumnTypeChange> {
+    let _job = state.start_project_job(
+        &project_id,
+        &format!("changing a column type of {}", table_name),
+    )?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.change_column_type(&conn, &table_name, &column, new_type.trim())
 }
 
 #[tauri::command]
@@ -74,6 +540,8 @@ pub async fn delete_table(
     project_id: String,
     table_name: String,
 ) -> Result<()> {
+    let _job = state.start_project_job(&project_id, &format!("deleting {}", table_name))?;
+
     let storage = state.storage.lock();
     let project = storage.get_project(&project_id)?;
     let db_path = storage.get_database_path(&project);
@@ -83,18 +551,82 @@ pub async fn delete_table(
     let conn = conn.lock();
 
     // Drop the table
-    let sql = format!("DROP TABLE IF EXISTS \"{}\"", table_name.replace("\"", "\"\""));
+    let sql = format!("DROP TABLE IF EXISTS {}", quote_ident(&table_name));
     conn.execute(&sql, [])?;
 
+    // Computed columns kept on a view over the table would be left dangling
+    state.duckdb.drop_computed_view(&conn, &table_name)?;
+
+    // The sequence behind a generated id column would outlive the table
+    conn.execute(
+        &format!(
+            "DROP SEQUENCE IF EXISTS {}",
+            quote_ident(&format!("_duckbake_seq_{}", table_name))
+        ),
+        [],
+    )?;
+
     // Also remove any vectorization data for this table
     let _ = conn.execute(
-        &format!("DROP TABLE IF EXISTS \"_duckbake_embeddings_{}\"", table_name.replace("\"", "\"\"")),
+        &format!(
+            "DROP TABLE IF EXISTS {}",
+            quote_ident(&format!("_duckbake_embeddings_{}", table_name))
+        ),
         [],
     );
 
     Ok(())
 }
 
+/// How the project's database file is split between tables, embeddings, documents
+/// and conversations
+#[tauri::command]
+pub async fn get_storage_breakdown(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<StorageBreakdown> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.get_storage_breakdown(&conn, &db_path)
+}
+
+#[tauri::command]
+pub async fn checkpoint(state: State<'_, AppState>, project_id: String) -> Result<()> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.checkpoint(&conn)
+}
+
+#[tauri::command]
+pub async fn list_open_connections(state: State<'_, AppState>) -> Result<Vec<OpenConnection>> {
+    Ok(state.duckdb.list_open_connections())
+}
+
+#[tauri::command]
+pub async fn close_connection(state: State<'_, AppState>, project_id: String) -> Result<()> {
+    state.duckdb.close_connection(&project_id);
+    Ok(())
+}
+
+/// Close every DuckDB connection so all data is flushed to disk. Project
+/// metadata in projects.json is written synchronously, so nothing else is pending.
+#[tauri::command]
+pub async fn shutdown(state: State<'_, AppState>) -> Result<()> {
+    let closed = state.duckdb.close_all_connections();
+    info!(target: "duckdb", "Shutdown closed {} connection(s)", closed);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_project_context(
     state: State<'_, AppState>,
@@ -117,10 +649,7 @@ pub async fn get_project_context(
         let schema = state.duckdb.get_table_schema(&conn, &table.name)?;
 
         // Get sample rows (first 3)
-        let sample_query = format!(
-            "SELECT * FROM \"{}\" LIMIT 3",
-            table.name.replace("\"", "\"\"")
-        );
+        let sample_query = format!("SELECT * FROM {} LIMIT 3", quote_ident(&table.name));
         let sample = state.duckdb.execute_query(&conn, &sample_query).ok();
 
         table_contexts.push(TableContext {
@@ -131,7 +660,165 @@ pub async fn get_project_context(
         });
     }
 
+    // Documents are queryable through the docs.* views, so the assistant should know about them
+    match state.duckdb.document_view_contexts(&conn) {
+        Ok(contexts) => table_contexts.extend(contexts),
+        Err(e) => warn!(target: "duckdb", "Failed to describe document views: {}", e),
+    }
+
     Ok(ProjectContext {
         tables: table_contexts,
     })
 }
+
+#[tauri::command]
+pub async fn get_capabilities(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<ConnectionCapabilities> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    // Opening the connection runs the probe if it hasn't happened yet
+    state.duckdb.get_connection(&project_id, &db_path)?;
+    Ok(state.duckdb.get_capabilities(&project_id))
+}
+
+#[tauri::command]
+pub async fn attach_database(
+    state: State<'_, AppState>,
+    project_id: String,
+    file_path: String,
+    alias: String,
+) -> Result<AttachedDatabase> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.attach_database(&conn, &project_id, &file_path, &alias)
+}
+
+#[tauri::command]
+pub async fn detach_database(
+    state: State<'_, AppState>,
+    project_id: String,
+    alias: String,
+) -> Result<()> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.detach_database(&conn, &project_id, &alias)
+}
+
+#[tauri::command]
+pub async fn list_attached_databases(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<Vec<AttachedDatabase>> {
+    Ok(state.duckdb.list_attachments(&project_id))
+}
+
+#[tauri::command]
+pub async fn copy_result_as_tsv(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    sql: String,
+) -> Result<usize> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let result = {
+        let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+        let conn = conn.lock();
+        state.duckdb.execute_query(&conn, &sql)?
+    };
+
+    let tsv = state.duckdb.result_to_tsv(&result);
+    app.clipboard()
+        .write_text(tsv)
+        .map_err(|e| AppError::Custom(format!("Failed to write to clipboard: {}", e)))?;
+
+    Ok(result.row_count)
+}
+
+#[tauri::command]
+pub async fn get_cell_value(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    column: String,
+    primary_key_value: Option<serde_json::Value>,
+    row_id: Option<i64>,
+) -> Result<serde_json::Value> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.get_cell_value(
+        &conn,
+        &table_name,
+        &column,
+        primary_key_value.as_ref(),
+        row_id,
+    )
+}
+
+#[tauri::command]
+pub async fn set_duckdb_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+    threads: Option<i64>,
+    memory_limit: Option<String>,
+) -> Result<DuckDbSettings> {
+    let settings = DuckDbSettings {
+        threads,
+        memory_limit,
+    };
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    // Apply first so invalid values are rejected before being persisted
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    DuckDbService::apply_settings(&conn, &settings)?;
+
+    let storage = state.storage.lock();
+    storage.update_duckdb_settings(&project_id, settings.clone())?;
+    drop(storage);
+
+    state.duckdb.register_settings(&project_id, settings);
+    state.duckdb.get_settings(&conn)
+}
+
+#[tauri::command]
+pub async fn get_duckdb_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<DuckDbSettings> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.get_settings(&conn)
+}
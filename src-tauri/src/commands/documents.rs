@@ -1,36 +1,86 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use duckdb::Connection;
+use parking_lot::Mutex;
 use tauri::{Emitter, State, Window};
+use tracing::warn;
 use uuid::Uuid;
 
-use crate::error::Result;
-use crate::models::{Document, DocumentInfo, DocumentVectorizationProgress};
-use crate::services::DocumentParser;
+use crate::error::{AppError, Result};
+use crate::models::{
+    estimate_eta, progress_percent, BulkDocumentResult, ChunkConfig, Document, DocumentChunk,
+    DocumentChunkPage, DocumentInfo, DocumentMetadata, DocumentPage, DocumentRevectorization,
+    DocumentVectorizationProgress, Project, SimilarityMetric,
+};
+use crate::services::{DocumentParser, DuckDbService};
 use crate::state::AppState;
 
 const BATCH_SIZE: usize = 20;
-const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
 
 #[tauri::command]
 pub async fn upload_document(
     state: State<'_, AppState>,
     project_id: String,
     file_path: String,
+    chunk_config: Option<ChunkConfig>,
+) -> Result<DocumentInfo> {
+    // Images have no text to extract, so a vision model describes them instead
+    let (content, metadata) = if DocumentParser::is_image(&file_path) {
+        let vision_model = state.storage.lock().get_project(&project_id)?.vision_model;
+        let model = vision_model.ok_or_else(|| {
+            AppError::Custom(
+                "Image documents need a multimodal model (e.g. llava). Set a vision model for this project first.".into(),
+            )
+        })?;
+        let image = std::fs::read(&file_path).map_err(|e| {
+            AppError::Custom(format!("Cannot read image '{}': {}", file_path, e))
+        })?;
+        let description = state.ollama.describe_image(&model, &image).await?;
+        DocumentParser::image_document(&file_path, description)?
+    } else {
+        DocumentParser::parse_document(&file_path)?
+    };
+
+    store_document(&state, &project_id, content, metadata, &chunk_config.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn upload_document_text(
+    state: State<'_, AppState>,
+    project_id: String,
+    title: String,
+    content: String,
+    file_type: Option<String>,
+    chunk_config: Option<ChunkConfig>,
+) -> Result<DocumentInfo> {
+    let file_type = file_type.unwrap_or_else(|| "txt".to_string());
+    let (content, metadata) = DocumentParser::parse_text_content(&title, content, &file_type)?;
+
+    store_document(&state, &project_id, content, metadata, &chunk_config.unwrap_or_default())
+}
+
+/// Insert a parsed document and its chunks into the project database
+fn store_document(
+    state: &AppState,
+    project_id: &str,
+    content: String,
+    metadata: DocumentMetadata,
+    chunk_config: &ChunkConfig,
 ) -> Result<DocumentInfo> {
     let db_path = {
         let storage = state.storage.lock();
-        let project = storage.get_project(&project_id)?;
+        let project = storage.get_project(project_id)?;
         storage.get_database_path(&project)
     };
 
-    // Parse document
-    let (content, metadata) = DocumentParser::parse_document(&file_path)?;
-
     // Create document record
     let doc_id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
     let document = Document {
         id: doc_id.clone(),
-        project_id: project_id.clone(),
+        project_id: project_id.to_string(),
         filename: metadata.filename.clone(),
         file_type: metadata.file_type.clone(),
         file_size: metadata.file_size,
@@ -43,16 +93,17 @@ pub async fn upload_document(
         content: content.clone(),
         uploaded_at: now.clone(),
         is_vectorized: false,
+        source_path: metadata.source_path,
     };
 
     // Insert into database
-    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = state.duckdb.get_connection(project_id, &db_path)?;
     let conn = conn.lock();
     state.duckdb.init_document_tables(&conn)?;
     state.duckdb.insert_document(&conn, &document)?;
 
     // Create chunks for the document
-    let chunks = DocumentParser::chunk_document(&doc_id, &content, &metadata.file_type);
+    let chunks = DocumentParser::chunk_document(&doc_id, &content, &metadata.file_type, chunk_config);
     state.duckdb.insert_document_chunks(&conn, &chunks)?;
 
     Ok(DocumentInfo {
@@ -71,7 +122,9 @@ pub async fn upload_document(
 pub async fn get_documents(
     state: State<'_, AppState>,
     project_id: String,
-) -> Result<Vec<DocumentInfo>> {
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> Result<DocumentPage> {
     let db_path = {
         let storage = state.storage.lock();
         let project = storage.get_project(&project_id)?;
@@ -80,7 +133,9 @@ pub async fn get_documents(
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
-    state.duckdb.get_documents(&conn, &project_id)
+    state
+        .duckdb
+        .get_documents(&conn, &project_id, page.unwrap_or(0), page_size)
 }
 
 #[tauri::command]
@@ -106,6 +161,8 @@ pub async fn delete_document(
     project_id: String,
     document_id: String,
 ) -> Result<()> {
+    let _job = state.start_project_job(&project_id, "deleting a document")?;
+
     let db_path = {
         let storage = state.storage.lock();
         let project = storage.get_project(&project_id)?;
@@ -120,6 +177,73 @@ pub async fn delete_document(
     result
 }
 
+#[tauri::command]
+pub async fn delete_documents(
+    state: State<'_, AppState>,
+    project_id: String,
+    document_ids: Vec<String>,
+) -> Result<Vec<BulkDocumentResult>> {
+    let _job = state.start_project_job(&project_id, "deleting documents")?;
+
+    let db_path = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        storage.get_database_path(&project)
+    };
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.delete_documents(&conn, &document_ids)
+}
+
+/// Embed every chunk of a document and store the vectors, calling `on_batch`
+/// with the number of chunks finished after each batch. Returns false when
+/// cancelled between batches, leaving the document not marked as vectorized.
+async fn embed_document_chunks(
+    state: &AppState,
+    conn: &Arc<Mutex<Connection>>,
+    model: &str,
+    document_id: &str,
+    chunks: &[DocumentChunk],
+    mut on_batch: impl FnMut(i64),
+) -> Result<bool> {
+    for chunk_batch in chunks.chunks(BATCH_SIZE) {
+        if state.should_cancel_document_vectorization(document_id) {
+            state.clear_document_vectorization_cancellation(document_id);
+            return Ok(false);
+        }
+
+        let texts: Vec<String> = chunk_batch.iter().map(|c| c.content.clone()).collect();
+        let chunk_ids: Vec<String> = chunk_batch.iter().map(|c| c.id.clone()).collect();
+
+        // Generate embeddings
+        let embeddings = state
+            .ollama
+            .generate_embeddings(texts, Some(model))
+            .await?;
+
+        // Store embeddings
+        let chunk_embeddings: Vec<(String, Vec<f32>)> = chunk_ids
+            .into_iter()
+            .zip(embeddings.into_iter())
+            .collect();
+
+        {
+            let conn = conn.lock();
+            state
+                .duckdb
+                .store_document_chunk_embeddings(&conn, chunk_embeddings, model)?;
+        }
+
+        on_batch(chunk_batch.len() as i64);
+    }
+
+    // Mark document as vectorized
+    let conn = conn.lock();
+    state.duckdb.mark_document_vectorized(&conn, document_id)?;
+    Ok(true)
+}
+
 #[tauri::command]
 pub async fn vectorize_document(
     window: Window,
@@ -127,10 +251,15 @@ pub async fn vectorize_document(
     project_id: String,
     document_id: String,
 ) -> Result<()> {
-    let db_path = {
+    let _job = state.start_project_job(&project_id, "vectorizing a document")?;
+
+    let (db_path, model) = {
         let storage = state.storage.lock();
         let project = storage.get_project(&project_id)?;
-        storage.get_database_path(&project)
+        (
+            storage.get_database_path(&project),
+            project.embedding_model_name().to_string(),
+        )
     };
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
@@ -145,6 +274,9 @@ pub async fn vectorize_document(
 
     let total_chunks = chunks.len() as i64;
 
+    // Clear any previous cancellation flag for this document
+    state.clear_document_vectorization_cancellation(&document_id);
+
     // Emit initial progress
     let _ = window.emit(
         "document-vectorization-progress",
@@ -155,15 +287,13 @@ pub async fn vectorize_document(
             processed_chunks: 0,
             status: "loading_model".to_string(),
             error: None,
+            progress_percent: 0.0,
+            eta_seconds: None,
         },
     );
 
     // Warm up embedding model
-    if let Err(e) = state
-        .ollama
-        .warmup_embedding_model(Some(DEFAULT_EMBEDDING_MODEL))
-        .await
-    {
+    if let Err(e) = state.ollama.warmup_embedding_model(Some(&model)).await {
         let _ = window.emit(
             "document-vectorization-progress",
             DocumentVectorizationProgress {
@@ -173,6 +303,8 @@ pub async fn vectorize_document(
                 processed_chunks: 0,
                 status: "error".to_string(),
                 error: Some(e.to_string()),
+                progress_percent: 0.0,
+                eta_seconds: None,
             },
         );
         return Err(e);
@@ -188,40 +320,16 @@ pub async fn vectorize_document(
             processed_chunks: 0,
             status: "processing".to_string(),
             error: None,
+            progress_percent: 0.0,
+            eta_seconds: None,
         },
     );
 
     // Process chunks in batches
     let mut processed = 0i64;
-
-    for chunk_batch in chunks.chunks(BATCH_SIZE) {
-        let texts: Vec<String> = chunk_batch.iter().map(|c| c.content.clone()).collect();
-        let chunk_ids: Vec<String> = chunk_batch.iter().map(|c| c.id.clone()).collect();
-
-        // Generate embeddings
-        let embeddings = state
-            .ollama
-            .generate_embeddings(texts, Some(DEFAULT_EMBEDDING_MODEL))
-            .await?;
-
-        // Store embeddings
-        let chunk_embeddings: Vec<(String, Vec<f32>)> = chunk_ids
-            .into_iter()
-            .zip(embeddings.into_iter())
-            .collect();
-
-        {
-            let conn = conn.lock();
-            state.duckdb.store_document_chunk_embeddings(
-                &conn,
-                chunk_embeddings,
-                DEFAULT_EMBEDDING_MODEL,
-            )?;
-        }
-
-        processed += chunk_batch.len() as i64;
-
-        // Emit progress
+    let started = Instant::now();
+    let completed = embed_document_chunks(&state, &conn, &model, &document_id, &chunks, |done| {
+        processed += done;
         let _ = window.emit(
             "document-vectorization-progress",
             DocumentVectorizationProgress {
@@ -231,15 +339,12 @@ pub async fn vectorize_document(
                 processed_chunks: processed,
                 status: "processing".to_string(),
                 error: None,
+                progress_percent: progress_percent(processed, total_chunks),
+                eta_seconds: estimate_eta(processed, total_chunks, started.elapsed()),
             },
         );
-    }
-
-    // Mark document as vectorized
-    {
-        let conn = conn.lock();
-        state.duckdb.mark_document_vectorized(&conn, &document_id)?;
-    }
+    })
+    .await?;
 
     // Emit completion
     let _ = window.emit(
@@ -249,19 +354,294 @@ pub async fn vectorize_document(
             document_name: document.filename,
             total_chunks,
             processed_chunks: processed,
-            status: "completed".to_string(),
+            status: if completed { "completed" } else { "cancelled" }.to_string(),
             error: None,
+            progress_percent: progress_percent(processed, total_chunks),
+            eta_seconds: None,
         },
     );
 
     Ok(())
 }
 
+/// Re-embed only the chunks of a document that changed since it was last vectorized,
+/// or that were embedded with another model. Embeddings are stored on the chunk rows,
+/// so chunks removed from the document took theirs with them; unchanged chunks keep
+/// their vectors. Progress counts only the chunks being embedded.
+#[tauri::command]
+pub async fn revectorize_document(
+    window: Window,
+    state: State<'_, AppState>,
+    project_id: String,
+    document_id: String,
+) -> Result<DocumentRevectorization> {
+    let _job = state.start_project_job(&project_id, "re-vectorizing a document")?;
+
+    let (db_path, model) = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        (
+            storage.get_database_path(&project),
+            project.embedding_model_name().to_string(),
+        )
+    };
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+
+    let (document, stale, total) = {
+        let conn = conn.lock();
+        state.duckdb.init_document_tables(&conn)?;
+        let doc = state.duckdb.get_document(&conn, &document_id)?;
+        let (stale, total) = state.duckdb.get_stale_document_chunks(&conn, &document_id, &model)?;
+        (doc, stale, total)
+    };
+
+    let stale_chunks = stale.len() as i64;
+    let started = Instant::now();
+    let progress = |processed: i64, status: &str, error: Option<String>| {
+        DocumentVectorizationProgress {
+            document_id: document.id.clone(),
+            document_name: document.filename.clone(),
+            total_chunks: stale_chunks,
+            processed_chunks: processed,
+            status: status.to_string(),
+            error,
+            progress_percent: progress_percent(processed, stale_chunks),
+            eta_seconds: if status == "processing" {
+                estimate_eta(processed, stale_chunks, started.elapsed())
+            } else {
+                None
+            },
+        }
+    };
+
+    // Clear any previous cancellation flag for this document
+    state.clear_document_vectorization_cancellation(&document_id);
+
+    let mut processed = 0i64;
+    let mut completed = true;
+    if !stale.is_empty() {
+        let _ = window.emit(
+            "document-vectorization-progress",
+            progress(0, "loading_model", None),
+        );
+
+        if let Err(e) = state.ollama.warmup_embedding_model(Some(&model)).await {
+            let _ = window.emit(
+                "document-vectorization-progress",
+                progress(0, "error", Some(e.to_string())),
+            );
+            return Err(e);
+        }
+
+        let _ = window.emit(
+            "document-vectorization-progress",
+            progress(0, "processing", None),
+        );
+        completed = embed_document_chunks(&state, &conn, &model, &document_id, &stale, |done| {
+            processed += done;
+            let _ = window.emit(
+                "document-vectorization-progress",
+                progress(processed, "processing", None),
+            );
+        })
+        .await?;
+    } else {
+        // Nothing to embed, but every chunk already has a current vector
+        let conn = conn.lock();
+        state.duckdb.mark_document_vectorized(&conn, &document_id)?;
+    }
+
+    let status = if completed { "completed" } else { "cancelled" };
+    let _ = window.emit(
+        "document-vectorization-progress",
+        progress(processed, status, None),
+    );
+
+    Ok(DocumentRevectorization {
+        document_id,
+        embedded_chunks: processed,
+        unchanged_chunks: total - stale_chunks,
+    })
+}
+
+/// Vectorize several documents in one run. Progress events carry the document
+/// currently being embedded, with chunk totals summed across the whole selection.
+#[tauri::command]
+pub async fn vectorize_documents(
+    window: Window,
+    state: State<'_, AppState>,
+    project_id: String,
+    document_ids: Vec<String>,
+) -> Result<Vec<BulkDocumentResult>> {
+    let _job = state.start_project_job(&project_id, "vectorizing documents")?;
+
+    let (db_path, model) = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        (
+            storage.get_database_path(&project),
+            project.embedding_model_name().to_string(),
+        )
+    };
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+
+    // Load everything up front so the overall total is known before embedding starts
+    let mut results = Vec::with_capacity(document_ids.len());
+    let mut pending = Vec::new();
+    {
+        let conn = conn.lock();
+        for document_id in &document_ids {
+            let loaded = state.duckdb.get_document(&conn, document_id).and_then(|doc| {
+                let chunks = state.duckdb.get_document_chunks(&conn, document_id)?;
+                Ok((doc, chunks))
+            });
+            match loaded {
+                Ok((doc, chunks)) => pending.push((doc, chunks)),
+                Err(e) => results.push(BulkDocumentResult {
+                    document_id: document_id.clone(),
+                    success: false,
+                    error: Some(e.to_string()),
+                }),
+            }
+        }
+    }
+
+    let total_chunks: i64 = pending.iter().map(|(_, chunks)| chunks.len() as i64).sum();
+    let started = Instant::now();
+    let progress = |document: &Document, processed: i64, status: &str, error: Option<String>| {
+        DocumentVectorizationProgress {
+            document_id: document.id.clone(),
+            document_name: document.filename.clone(),
+            total_chunks,
+            processed_chunks: processed,
+            status: status.to_string(),
+            error,
+            progress_percent: progress_percent(processed, total_chunks),
+            eta_seconds: if status == "processing" {
+                estimate_eta(processed, total_chunks, started.elapsed())
+            } else {
+                None
+            },
+        }
+    };
+
+    if let Some((first, _)) = pending.first() {
+        let _ = window.emit(
+            "document-vectorization-progress",
+            progress(first, 0, "loading_model", None),
+        );
+
+        if let Err(e) = state.ollama.warmup_embedding_model(Some(&model)).await {
+            let _ = window.emit(
+                "document-vectorization-progress",
+                progress(first, 0, "error", Some(e.to_string())),
+            );
+            return Err(e);
+        }
+    }
+
+    for (document, _) in &pending {
+        state.clear_document_vectorization_cancellation(&document.id);
+    }
+
+    let mut processed = 0i64;
+    for (document, chunks) in &pending {
+        let _ = window.emit(
+            "document-vectorization-progress",
+            progress(document, processed, "processing", None),
+        );
+
+        let outcome = embed_document_chunks(&state, &conn, &model, &document.id, chunks, |done| {
+            processed += done;
+            let _ = window.emit(
+                "document-vectorization-progress",
+                progress(document, processed, "processing", None),
+            );
+        })
+        .await;
+
+        // A cancelled document is reported as failed and the rest carry on
+        let outcome = match outcome {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                let _ = window.emit(
+                    "document-vectorization-progress",
+                    progress(document, processed, "cancelled", None),
+                );
+                Err(AppError::Custom("Vectorization was cancelled".into()))
+            }
+            Err(e) => {
+                warn!(target: "documents", "Failed to vectorize {}: {}", document.filename, e);
+                Err(e)
+            }
+        };
+        results.push(BulkDocumentResult {
+            document_id: document.id.clone(),
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+
+    if let Some((last, _)) = pending.last() {
+        let _ = window.emit(
+            "document-vectorization-progress",
+            progress(last, processed, "completed", None),
+        );
+    }
+
+    Ok(results)
+}
+
+/// Stop a running vectorization of a document after its current batch of chunks
+#[tauri::command]
+pub async fn cancel_document_vectorization(
+    state: State<'_, AppState>,
+    document_id: String,
+) -> Result<()> {
+    state.cancel_document_vectorization(&document_id);
+    Ok(())
+}
+
+/// Set (or clear, with None) the multimodal model that describes image documents
+#[tauri::command]
+pub async fn set_project_vision_model(
+    state: State<'_, AppState>,
+    project_id: String,
+    model: Option<String>,
+) -> Result<Project> {
+    let model = model.map(|m| m.trim().to_string()).filter(|m| !m.is_empty());
+    state.storage.lock().update_vision_model(&project_id, model)
+}
+
 #[tauri::command]
 pub async fn get_supported_document_extensions() -> Vec<String> {
     DocumentParser::get_supported_extensions()
 }
 
+/// A page of a document's chunks with their real boundaries, for the document viewer
+#[tauri::command]
+pub async fn get_document_chunks_paginated(
+    state: State<'_, AppState>,
+    project_id: String,
+    document_id: String,
+    page: u32,
+    page_size: u32,
+) -> Result<DocumentChunkPage> {
+    let db_path = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        storage.get_database_path(&project)
+    };
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.get_document_chunks_page(&conn, &document_id, page, page_size)
+}
+
+/// A document's first chunks shaped like search results, for attaching a whole
+/// document to a chat where there's no query to rank by
 #[tauri::command]
 pub async fn get_document_chunks_by_id(
     state: State<'_, AppState>,
@@ -291,6 +671,7 @@ pub async fn get_document_chunks_by_id(
                 "documentId": document_id,
                 "documentName": doc.filename,
                 "content": chunk.content,
+                "headingPath": chunk.heading_path,
                 "similarity": 1.0  // Direct match, max similarity
             })
         })
@@ -305,39 +686,52 @@ pub async fn semantic_search_documents(
     project_id: String,
     query: String,
     limit: Option<usize>,
+    offset: Option<usize>,
+    metric: Option<SimilarityMetric>,
 ) -> Result<Vec<serde_json::Value>> {
-    let db_path = {
+    let query = DuckDbService::validate_search_query(&query)?;
+
+    let (db_path, model) = {
         let storage = state.storage.lock();
         let project = storage.get_project(&project_id)?;
-        storage.get_database_path(&project)
+        (
+            storage.get_database_path(&project),
+            project.embedding_model_name().to_string(),
+        )
     };
 
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    state.duckdb.require_vector_search(&project_id)?;
+
     // Generate embedding for query
     let embeddings = state
         .ollama
-        .generate_embeddings(vec![query], Some(DEFAULT_EMBEDDING_MODEL))
+        .generate_embeddings(vec![query], Some(&model))
         .await?;
 
     let query_embedding = embeddings.into_iter().next().unwrap_or_default();
 
-    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
 
     let results = state.duckdb.semantic_search_documents(
         &conn,
         &project_id,
+        &model,
         &query_embedding,
         limit.unwrap_or(10),
+        offset.unwrap_or(0),
+        metric.unwrap_or_default(),
     )?;
 
     // Convert to JSON
     let json_results: Vec<serde_json::Value> = results
         .into_iter()
-        .map(|(doc_id, doc_name, content, similarity)| {
+        .map(|(doc_id, doc_name, content, heading_path, similarity)| {
             serde_json::json!({
                 "documentId": doc_id,
                 "documentName": doc_name,
                 "content": content,
+                "headingPath": heading_path,
                 "similarity": similarity
             })
         })
@@ -0,0 +1,130 @@
+use std::fs;
+
+use duckdb::Connection;
+use tauri::State;
+
+use crate::error::{AppError, Result};
+use crate::logging;
+use crate::models::{HealthCheck, DEFAULT_EMBEDDING_MODEL};
+use crate::services::DuckDbService;
+use crate::state::AppState;
+
+fn check(name: &str, ok: bool, detail: impl Into<String>) -> HealthCheck {
+    HealthCheck {
+        name: name.to_string(),
+        ok,
+        detail: detail.into(),
+    }
+}
+
+/// Directory containing the rolling log files
+#[tauri::command]
+pub async fn get_log_path() -> Result<String> {
+    logging::log_dir()
+        .map(|dir| dir.display().to_string())
+        .ok_or_else(|| AppError::Custom("Logging is not initialized".into()))
+}
+
+/// Change log verbosity; "debug" and "trace" include SQL text
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<()> {
+    logging::set_level(&level)
+}
+
+#[tauri::command]
+pub async fn self_test(state: State<'_, AppState>) -> Result<Vec<HealthCheck>> {
+    let (data_dir, databases_dir) = {
+        let storage = state.storage.lock();
+        (storage.data_dir(), storage.databases_dir())
+    };
+
+    let mut checks = Vec::new();
+
+    // Data directory writable
+    let probe_file = data_dir.join(".duckbake_write_test");
+    checks.push(match fs::write(&probe_file, b"ok") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe_file);
+            check("data_directory", true, data_dir.display().to_string())
+        }
+        Err(e) => check(
+            "data_directory",
+            false,
+            format!("{} is not writable: {}", data_dir.display(), e),
+        ),
+    });
+
+    // Scratch DuckDB database can be created and queried
+    let scratch_path = databases_dir.join(".duckbake_self_test.duckdb");
+    let scratch = Connection::open(&scratch_path).and_then(|conn| {
+        let answer: i32 = conn.query_row("SELECT 42", [], |row| row.get(0))?;
+        Ok((conn, answer))
+    });
+    let capabilities = match scratch {
+        Ok((conn, 42)) => {
+            checks.push(check("duckdb", true, "Scratch database created and queried"));
+            Some(DuckDbService::probe_capabilities(&conn))
+        }
+        Ok((_, other)) => {
+            checks.push(check(
+                "duckdb",
+                false,
+                format!("Scratch query returned {} instead of 42", other),
+            ));
+            None
+        }
+        Err(e) => {
+            checks.push(check("duckdb", false, format!("Failed to create scratch database: {}", e)));
+            None
+        }
+    };
+    let _ = fs::remove_file(&scratch_path);
+    let _ = fs::remove_file(format!("{}.wal", scratch_path.display()));
+
+    // Vector search functions available in this build
+    checks.push(match capabilities {
+        Some(caps) if caps.list_cosine_similarity => {
+            check("vector_functions", true, "list_cosine_similarity available")
+        }
+        Some(_) => check(
+            "vector_functions",
+            false,
+            "list_cosine_similarity is not available in this DuckDB build",
+        ),
+        None => check("vector_functions", false, "Skipped: DuckDB check failed"),
+    });
+
+    // Ollama reachable
+    let status = state.ollama.check_status().await?;
+    checks.push(if status.connected {
+        check(
+            "ollama",
+            true,
+            format!("Connected (version {})", status.version.unwrap_or_default()),
+        )
+    } else {
+        check("ollama", false, "Ollama is not reachable at http://localhost:11434")
+    });
+
+    // Default embedding model installed
+    checks.push(if status.connected {
+        match state.ollama.list_models().await {
+            Ok(models) if models.iter().any(|m| m.name.starts_with(DEFAULT_EMBEDDING_MODEL)) => {
+                check("embedding_model", true, format!("{} installed", DEFAULT_EMBEDDING_MODEL))
+            }
+            Ok(_) => check(
+                "embedding_model",
+                false,
+                format!(
+                    "{} is not installed (ollama pull {})",
+                    DEFAULT_EMBEDDING_MODEL, DEFAULT_EMBEDDING_MODEL
+                ),
+            ),
+            Err(e) => check("embedding_model", false, format!("Failed to list models: {}", e)),
+        }
+    } else {
+        check("embedding_model", false, "Skipped: Ollama is not reachable")
+    });
+
+    Ok(checks)
+}
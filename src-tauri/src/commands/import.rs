@@ -1,9 +1,23 @@
+use std::path::Path;
+
+use duckdb::Connection;
 use tauri::State;
+use tracing::warn;
 
-use crate::error::Result;
-use crate::services::{FileParser, ImportMode, ImportPreview, ImportResult};
+use crate::error::{AppError, Result};
+use crate::models::{ImportDefaults, ImportTarget, Project, RecentImport};
+use crate::services::{
+    DuckDbService, FileParser, FilePeek, ImportMode, ImportOptions, ImportPreview, ImportResult,
+    ImportValidation,
+};
 use crate::state::AppState;
 
+/// Lines `peek_file` returns when the caller doesn't say
+const DEFAULT_PEEK_LINES: u32 = 50;
+
+/// Most lines `peek_file` will return
+const MAX_PEEK_LINES: u32 = 1000;
+
 #[tauri::command]
 pub async fn preview_import(
     state: State<'_, AppState>,
@@ -16,19 +30,227 @@ pub async fn preview_import(
     drop(storage);
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
-    let conn = conn.lock();
 
-    FileParser::preview_file(&conn, &file_path)
+    // Sniffing and sampling a large file can take a while, keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = conn.lock();
+        FileParser::preview_file(&conn, &file_path)
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Preview task failed: {}", e)))?
+}
+
+/// Preview a file without a project, using a throwaway in-memory database
+#[tauri::command]
+pub async fn describe_file(file_path: String) -> Result<ImportPreview> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = Connection::open_in_memory()?;
+        FileParser::preview_file(&conn, &file_path)
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Preview task failed: {}", e)))?
+}
+
+/// Raw first lines of a file, for working out its delimiter or encoding by eye. Works
+/// for files `preview_import` can't read.
+#[tauri::command]
+pub async fn peek_file(file_path: String, max_lines: Option<u32>) -> Result<FilePeek> {
+    let max_lines = max_lines.unwrap_or(DEFAULT_PEEK_LINES).clamp(1, MAX_PEEK_LINES);
+    FileParser::peek_file(&file_path, max_lines as usize)
+}
+
+/// Full row count for a file, meant to be called in the background after `preview_import`
+#[tauri::command]
+pub async fn estimate_row_count(
+    state: State<'_, AppState>,
+    project_id: String,
+    file_path: String,
+) -> Result<Option<i64>> {
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = conn.lock();
+        FileParser::estimate_row_count(&conn, &file_path)
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Row count task failed: {}", e)))?
 }
 
+/// Import a file. Without a table name or mode, the project's import defaults are used.
 #[tauri::command]
 pub async fn import_file(
     state: State<'_, AppState>,
     project_id: String,
     file_path: String,
-    table_name: String,
+    table_name: Option<String>,
+    mode: Option<ImportMode>,
+    options: Option<ImportOptions>,
+) -> Result<ImportResult> {
+    let target = resolve_import_target(&state, &project_id, &file_path, table_name, mode)?;
+    run_import(
+        &state,
+        &project_id,
+        &file_path,
+        &target.table_name,
+        target.mode,
+        options.unwrap_or_default(),
+    )
+}
+
+/// Table name and mode an import of this file would use by default, for pre-filling forms
+#[tauri::command]
+pub async fn get_import_target(
+    state: State<'_, AppState>,
+    project_id: String,
+    file_path: String,
+) -> Result<ImportTarget> {
+    resolve_import_target(&state, &project_id, &file_path, None, None)
+}
+
+/// Store the mode and table name template imports use when the caller gives none.
+/// The template may use `{filename}` and `{date}`.
+#[tauri::command]
+pub async fn set_project_import_defaults(
+    state: State<'_, AppState>,
+    project_id: String,
+    defaults: ImportDefaults,
+) -> Result<Project> {
+    let template = defaults
+        .table_name_template
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string);
+    if let Some(template) = template.as_deref() {
+        let sample = ImportDefaults {
+            mode: None,
+            table_name_template: Some(template.to_string()),
+        }
+        .table_name_for("file.csv");
+        if sample.contains(['{', '}']) {
+            return Err(AppError::Custom(format!(
+                "Unknown placeholder in '{}': only {{filename}} and {{date}} are supported",
+                template
+            )));
+        }
+        DuckDbService::validate_user_table_name(&sample)?;
+    }
+
+    let defaults = ImportDefaults {
+        table_name_template: template,
+        ..defaults
+    };
+    state.storage.lock().update_import_defaults(&project_id, defaults)
+}
+
+/// Fill in whatever the caller left out from the project's import defaults
+fn resolve_import_target(
+    state: &AppState,
+    project_id: &str,
+    file_path: &str,
+    table_name: Option<String>,
+    mode: Option<ImportMode>,
+) -> Result<ImportTarget> {
+    let defaults = state.storage.lock().get_project(project_id)?.import_defaults;
+    Ok(ImportTarget {
+        table_name: table_name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| defaults.table_name_for(file_path)),
+        mode: mode.or(defaults.mode).unwrap_or(ImportMode::Create),
+    })
+}
+
+#[tauri::command]
+pub async fn get_recent_imports(state: State<'_, AppState>) -> Result<Vec<RecentImport>> {
+    state.storage.lock().list_recent_imports()
+}
+
+/// Re-run a recent import against the same file, replacing the table's contents
+#[tauri::command]
+pub async fn reimport(
+    state: State<'_, AppState>,
+    project_id: String,
+    recent_id: String,
+) -> Result<ImportResult> {
+    let recent = state.storage.lock().get_recent_import(&recent_id)?;
+    if recent.project_id != project_id {
+        return Err(AppError::Custom("That recent import belongs to a different project".into()));
+    }
+
+    if !Path::new(&recent.file_path).exists() {
+        return Err(AppError::Custom(format!(
+            "File no longer exists: {}",
+            recent.file_path
+        )));
+    }
+
+    run_import(
+        &state,
+        &project_id,
+        &recent.file_path,
+        &recent.table_name,
+        ImportMode::Replace,
+        recent.options,
+    )
+}
+
+/// Import a file and remember it in the recent imports list
+fn run_import(
+    state: &AppState,
+    project_id: &str,
+    file_path: &str,
+    table_name: &str,
     mode: ImportMode,
+    options: ImportOptions,
 ) -> Result<ImportResult> {
+    let _job = state.start_project_job(project_id, &format!("importing into {}", table_name))?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(project_id, &db_path)?;
+    if FileParser::detect_file_type(file_path)? == "excel"
+        && !state.duckdb.get_capabilities(project_id).excel_reader
+    {
+        return Err(AppError::Custom(
+            "Excel import requires the DuckDB spatial extension (st_read) which isn't available in this build".into(),
+        ));
+    }
+    let conn = conn.lock();
+
+    let result = FileParser::import_file(&conn, file_path, table_name, mode.clone(), &options)?;
+    drop(conn);
+
+    // The import already succeeded, so a settings write failure shouldn't undo it
+    if let Err(e) = state
+        .storage
+        .lock()
+        .record_recent_import(project_id, file_path, table_name, mode, options)
+    {
+        warn!(target: "import", "Failed to record recent import: {}", e);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn validate_import(
+    state: State<'_, AppState>,
+    project_id: String,
+    file_path: String,
+    table_name: Option<String>,
+    mode: Option<ImportMode>,
+    options: Option<ImportOptions>,
+) -> Result<ImportValidation> {
+    let target = resolve_import_target(&state, &project_id, &file_path, table_name, mode)?;
+
     let storage = state.storage.lock();
     let project = storage.get_project(&project_id)?;
     let db_path = storage.get_database_path(&project);
@@ -37,7 +259,13 @@ pub async fn import_file(
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
 
-    FileParser::import_file(&conn, &file_path, &table_name, mode)
+    FileParser::validate_import(
+        &conn,
+        &file_path,
+        &target.table_name,
+        target.mode,
+        &options.unwrap_or_default(),
+    )
 }
 
 #[tauri::command]
@@ -52,5 +280,6 @@ pub async fn get_supported_extensions() -> Vec<String> {
         "pq".into(),
         "xlsx".into(),
         "xls".into(),
+        "sql".into(),
     ]
 }
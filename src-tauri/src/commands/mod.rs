@@ -6,6 +6,7 @@ mod vectorization;
 mod conversations;
 mod saved_queries;
 mod documents;
+mod health;
 
 pub use project::*;
 pub use database::*;
@@ -15,3 +16,4 @@ pub use vectorization::*;
 pub use conversations::*;
 pub use saved_queries::*;
 pub use documents::*;
+pub use health::*;
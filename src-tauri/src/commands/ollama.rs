@@ -1,9 +1,29 @@
-use tauri::{State, Window};
+use std::time::Instant;
 
-use crate::error::Result;
-use crate::models::{OllamaModel, OllamaStatus};
+use tauri::{Emitter, State, Window};
+use tracing::{info, warn};
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    estimate_eta, progress_percent, DuckbakeBlockResult, ExtractionResult, GenerateOptions,
+    OllamaModel, OllamaStatus, Project, ResponseMode, TableNote, VectorizationProgress, VizSpec,
+};
+use crate::services::{quote_ident, DuckDbService, OllamaService};
 use crate::state::AppState;
 
+/// Most rows returned for a chat block run server-side
+const MAX_BLOCK_ROWS: usize = 1000;
+/// Longest conversation title kept from the model's reply
+const MAX_TITLE_CHARS: usize = 60;
+/// Sample rows shown to the model when summarizing a table
+const SUMMARY_SAMPLE_ROWS: usize = 5;
+/// Sample rows are cut to this many characters so wide text columns don't fill the prompt
+const SUMMARY_MAX_ROW_CHARS: usize = 500;
+/// Rows read, extracted and written back per `extract_structured` batch
+const EXTRACTION_BATCH_SIZE: usize = 20;
+/// Row text is cut to this many characters before being sent for extraction
+const EXTRACTION_MAX_TEXT_CHARS: usize = 4000;
+
 #[tauri::command]
 pub async fn check_ollama_status(state: State<'_, AppState>) -> Result<OllamaStatus> {
     state.ollama.check_status().await
@@ -14,6 +34,28 @@ pub async fn list_ollama_models(state: State<'_, AppState>) -> Result<Vec<Ollama
     state.ollama.list_models().await
 }
 
+/// Set (or clear, with None) the chat model a project defaults to. New conversations
+/// are stamped with it.
+#[tauri::command]
+pub async fn set_project_chat_model(
+    state: State<'_, AppState>,
+    project_id: String,
+    model: Option<String>,
+) -> Result<Project> {
+    let model = model.map(|m| m.trim().to_string()).filter(|m| !m.is_empty());
+    state.storage.lock().update_chat_model(&project_id, model)
+}
+
+#[tauri::command]
+pub async fn get_project_chat_model(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<Option<String>> {
+    Ok(state.storage.lock().get_project(&project_id)?.chat_model)
+}
+
+/// Streams the reply as `chat-chunk` events, or with `json_schema` set (`"json"` or a
+/// JSON schema) waits for the whole reply and returns it parsed instead
 #[tauri::command]
 pub async fn send_chat_message(
     state: State<'_, AppState>,
@@ -21,11 +63,403 @@ pub async fn send_chat_message(
     model: String,
     messages: Vec<(String, String)>,
     context: Option<String>,
-) -> Result<()> {
+    response_mode: Option<ResponseMode>,
+    json_schema: Option<serde_json::Value>,
+) -> Result<Option<serde_json::Value>> {
+    if let Some(format) = json_schema {
+        let value = state.ollama.chat_json(&model, messages, context, format).await?;
+        return Ok(Some(value));
+    }
+
     state
         .ollama
-        .chat_stream(&window, &model, messages, context)
-        .await
+        .chat_stream(
+            &window,
+            &model,
+            messages,
+            context,
+            response_mode.unwrap_or_default(),
+        )
+        .await?;
+    Ok(None)
+}
+
+/// Like `send_chat_message`, but once the response is complete each duckbake
+/// block's SQL is run read-only against the project and its result emitted as a
+/// `chat-block-result` event, so charts can render without another round-trip
+#[tauri::command]
+pub async fn send_chat_message_and_run(
+    state: State<'_, AppState>,
+    window: Window,
+    project_id: String,
+    model: String,
+    messages: Vec<(String, String)>,
+    context: Option<String>,
+    response_mode: Option<ResponseMode>,
+    max_rows: Option<usize>,
+) -> Result<()> {
+    let db_path = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        storage.get_database_path(&project)
+    };
+
+    let blocks = state
+        .ollama
+        .chat_stream(
+            &window,
+            &model,
+            messages,
+            context,
+            response_mode.unwrap_or_default(),
+        )
+        .await?;
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let max_rows = max_rows.unwrap_or(MAX_BLOCK_ROWS).min(MAX_BLOCK_ROWS);
+
+    for block in blocks {
+        let outcome = {
+            let conn = conn.lock();
+            state.duckdb.execute_read_only(&conn, &block.sql, max_rows)
+        };
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let _ = window.emit(
+            "chat-block-result",
+            DuckbakeBlockResult {
+                block,
+                result,
+                error,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// The valid ```duckbake visualization blocks in an assistant message
+#[tauri::command]
+pub async fn parse_viz_spec(text: String) -> Vec<VizSpec> {
+    OllamaService::parse_viz_specs(&text)
+}
+
+/// Ask the model for a short title for a conversation from its first message
+#[tauri::command]
+pub async fn generate_conversation_title(
+    state: State<'_, AppState>,
+    model: String,
+    message: String,
+) -> Result<String> {
+    let prompt = format!(
+        "Write a short title (at most 6 words) for a conversation that starts with the message below. Reply with the title only.\n\nMessage:\n{}",
+        message
+    );
+    let options = GenerateOptions {
+        temperature: Some(0.2),
+        num_predict: Some(24),
+        num_ctx: None,
+    };
+    let reply = state.ollama.generate(&model, &prompt, Some(options)).await?;
+
+    // Models sometimes add a "Title:" preamble or wrap the title in quotes
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    let line = match line.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("title:") => &line[6..],
+        _ => line,
+    };
+    let title: String = line
+        .trim()
+        .trim_matches(['"', '\'', '*', '#'])
+        .trim_end_matches(['.', '!', '?', ':'])
+        .trim()
+        .chars()
+        .take(MAX_TITLE_CHARS)
+        .collect();
+
+    if title.is_empty() {
+        return Err(AppError::Custom("Model returned an empty title".into()));
+    }
+    Ok(title)
+}
+
+/// Ask the model to describe what a table holds from its name and columns
+#[tauri::command]
+pub async fn explain_table(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    model: String,
+) -> Result<String> {
+    let db_path = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        storage.get_database_path(&project)
+    };
+
+    let schema = {
+        let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+        let conn = conn.lock();
+        state.duckdb.get_table_schema(&conn, &table_name)?
+    };
+
+    let columns = schema
+        .columns
+        .iter()
+        .map(|c| {
+            let key = if c.is_primary_key { ", primary key" } else { "" };
+            format!("- {} ({}{})", c.name, c.data_type, key)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let prompt = format!(
+        "A DuckDB table named \"{}\" has these columns:\n{}\n\nIn two or three sentences of plain English, describe what this table most likely contains and what each row represents. Don't write SQL.",
+        table_name, columns
+    );
+
+    state.ollama.generate(&model, &prompt, None).await
+}
+
+/// Describe what a table contains from its schema and a few sample rows. The
+/// summary is cached per table; pass `refresh` to generate a new one.
+#[tauri::command]
+pub async fn summarize_table(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    model: String,
+    refresh: Option<bool>,
+) -> Result<TableNote> {
+    let db_path = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        storage.get_database_path(&project)
+    };
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let (schema, sample) = {
+        let conn = conn.lock();
+        if !refresh.unwrap_or(false) {
+            if let Some(note) = state.duckdb.get_table_note(&conn, &table_name)? {
+                return Ok(note);
+            }
+        }
+        let schema = state.duckdb.get_table_schema(&conn, &table_name)?;
+        let sample = state.duckdb.execute_query(
+            &conn,
+            &format!(
+                "SELECT * FROM {} LIMIT {}",
+                quote_ident(&table_name),
+                SUMMARY_SAMPLE_ROWS
+            ),
+        )?;
+        (schema, sample)
+    };
+
+    let columns = schema
+        .columns
+        .iter()
+        .map(|c| format!("- {} ({})", c.name, c.data_type))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let rows = sample
+        .rows
+        .iter()
+        .map(|row| row.to_string().chars().take(SUMMARY_MAX_ROW_CHARS).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let prompt = format!(
+        "A DuckDB table named \"{}\" has these columns:\n{}\n\nSample rows:\n{}\n\nIn a short paragraph of plain English, describe what data this table contains, what each row represents and anything notable about the columns. Don't write SQL.",
+        table_name, columns, rows
+    );
+
+    let summary = state.ollama.generate(&model, &prompt, None).await?;
+    if summary.is_empty() {
+        return Err(AppError::Custom("Model returned an empty summary".into()));
+    }
+
+    let conn = conn.lock();
+    state.duckdb.save_table_note(&conn, &table_name, &summary, &model)
+}
+
+/// Pull the fields of a JSON schema out of each row's text columns with the LLM,
+/// writing them into columns of the same names (added if missing). Runs in batches
+/// like `vectorize_table`, reporting `extraction-progress` events, and stops early
+/// when `cancel_vectorization` is called for the table.
+#[tauri::command]
+pub async fn extract_structured(
+    window: Window,
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    columns: Vec<String>,
+    schema: serde_json::Value,
+    model: Option<String>,
+) -> Result<ExtractionResult> {
+    let _job = state.start_project_job(
+        &project_id,
+        &format!("extracting fields from {}", table_name),
+    )?;
+
+    let (db_path, project_model) = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        (storage.get_database_path(&project), project.chat_model)
+    };
+    let model = model
+        .or(project_model)
+        .ok_or_else(|| AppError::Custom("No chat model selected for this project".into()))?;
+    if columns.is_empty() {
+        return Err(AppError::Custom("Select at least one column to extract from".into()));
+    }
+    let targets = DuckDbService::extraction_columns(&schema)?;
+    // ADD COLUMN IF NOT EXISTS would keep the source column and the extracted values
+    // would overwrite the text they came from. DuckDB names ignore case.
+    if let Some((name, _)) = targets
+        .iter()
+        .find(|(name, _)| columns.iter().any(|c| c.to_lowercase() == name.to_lowercase()))
+    {
+        return Err(AppError::Custom(format!(
+            "Field '{}' has the same name as a source column; rename it in the schema",
+            name
+        )));
+    }
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let total_rows: i64 = {
+        let conn = conn.lock();
+        let table = state.duckdb.get_table_schema(&conn, &table_name)?;
+        if let Some(missing) = columns
+            .iter()
+            .find(|c| !table.columns.iter().any(|t| &t.name == *c))
+        {
+            return Err(AppError::Custom(format!(
+                "Column '{}' not found in table '{}'",
+                missing, table_name
+            )));
+        }
+        state.duckdb.add_extraction_columns(&conn, &table_name, &targets)?;
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM {}", quote_ident(&table_name)),
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(0)
+    };
+
+    let emit = |processed: i64, status: &str, eta_seconds: Option<f64>| {
+        let _ = window.emit(
+            "extraction-progress",
+            VectorizationProgress {
+                table_name: table_name.clone(),
+                total_rows,
+                processed_rows: processed,
+                status: status.to_string(),
+                error: None,
+                cache_hit_rate: None,
+                batch_index: None,
+                progress_percent: progress_percent(processed, total_rows),
+                eta_seconds,
+            },
+        );
+    };
+
+    state.clear_vectorization_cancellation(&table_name);
+    emit(0, "processing", None);
+
+    let started = Instant::now();
+    let mut last_row_id = -1;
+    let mut processed = 0i64;
+    let mut failed = 0i64;
+    let mut cancelled = false;
+
+    loop {
+        if state.should_cancel_vectorization(&table_name) {
+            state.clear_vectorization_cancellation(&table_name);
+            cancelled = true;
+            break;
+        }
+
+        let rows = {
+            let conn = conn.lock();
+            state.duckdb.get_text_for_vectorization(
+                &conn,
+                &table_name,
+                &columns,
+                EXTRACTION_BATCH_SIZE,
+                last_row_id,
+                None,
+            )?
+        };
+        let Some(&(batch_last_row_id, _)) = rows.last() else {
+            break;
+        };
+
+        let mut extracted: Vec<(i64, serde_json::Value)> = Vec::new();
+        for (row_id, text) in &rows {
+            let text: String = text.chars().take(EXTRACTION_MAX_TEXT_CHARS).collect();
+            if text.trim().is_empty() {
+                continue;
+            }
+            let prompt = format!(
+                "Extract the requested fields from the text below. Use null for any field the text doesn't mention; don't guess.\n\n{}",
+                text
+            );
+            match state
+                .ollama
+                .chat_json(&model, vec![("user".into(), prompt)], None, schema.clone())
+                .await
+            {
+                Ok(value) if value.is_object() => extracted.push((*row_id, value)),
+                Ok(_) => failed += 1,
+                Err(e) => {
+                    warn!(
+                        target: "ollama",
+                        "{}: extraction failed for row {}: {}",
+                        table_name,
+                        row_id,
+                        e
+                    );
+                    failed += 1;
+                }
+            }
+        }
+
+        {
+            let conn = conn.lock();
+            state
+                .duckdb
+                .store_extracted_values(&conn, &table_name, &targets, &extracted)?;
+        }
+
+        last_row_id = batch_last_row_id;
+        processed += rows.len() as i64;
+        emit(
+            processed,
+            "processing",
+            estimate_eta(processed, total_rows, started.elapsed()),
+        );
+    }
+
+    emit(processed, if cancelled { "cancelled" } else { "completed" }, None);
+    info!(
+        target: "ollama",
+        "Extracted {} field(s) from {} row(s) of {} ({} failed)",
+        targets.len(),
+        processed,
+        table_name,
+        failed
+    );
+
+    Ok(ExtractionResult {
+        table_name,
+        columns: targets.into_iter().map(|(name, _)| name).collect(),
+        rows_processed: processed,
+        rows_failed: failed,
+        cancelled,
+    })
 }
 
 #[tauri::command]
@@ -1,10 +1,16 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use tauri::State;
+use tracing::warn;
 
 use crate::error::{AppError, Result};
-use crate::models::{Project, ProjectStats, ProjectSummary};
+use crate::models::{
+    ActiveJob, ClearTarget, ClearedData, DataDirInfo, GlobalHit, GlobalSearchError,
+    GlobalSearchResult, Project, ProjectStats, ProjectSummary,
+};
+use crate::services::StorageService;
 use crate::state::AppState;
 
 #[tauri::command]
@@ -29,8 +35,11 @@ pub async fn open_project(state: State<'_, AppState>, id: String) -> Result<Proj
     storage.get_project(&id)
 }
 
+/// Move a project to the trash; `restore_project` brings it back
 #[tauri::command]
 pub async fn delete_project(state: State<'_, AppState>, id: String) -> Result<()> {
+    let _job = state.start_project_job(&id, "deleting the project")?;
+
     // Close any open connection first
     state.duckdb.close_connection(&id);
 
@@ -38,6 +47,68 @@ pub async fn delete_project(state: State<'_, AppState>, id: String) -> Result<()
     storage.delete_project(&id)
 }
 
+#[tauri::command]
+pub async fn list_trashed_projects(state: State<'_, AppState>) -> Result<Vec<Project>> {
+    state.storage.lock().list_trashed_projects()
+}
+
+#[tauri::command]
+pub async fn restore_project(state: State<'_, AppState>, id: String) -> Result<Project> {
+    let project = state.storage.lock().restore_project(&id)?;
+    state
+        .duckdb
+        .register_settings(&project.id, project.duckdb_settings.clone());
+    Ok(project)
+}
+
+/// Permanently delete a project and its database, whether or not it's in the trash
+#[tauri::command]
+pub async fn purge_project(state: State<'_, AppState>, id: String) -> Result<()> {
+    let _job = state.start_project_job(&id, "deleting the project")?;
+
+    state.duckdb.close_connection(&id);
+    state.storage.lock().purge_project(&id)
+}
+
+/// Days a trashed project is kept before being purged at startup
+#[tauri::command]
+pub async fn get_trash_retention_days(state: State<'_, AppState>) -> Result<u32> {
+    state.storage.lock().trash_retention_days()
+}
+
+#[tauri::command]
+pub async fn set_trash_retention_days(state: State<'_, AppState>, days: u32) -> Result<()> {
+    state.storage.lock().set_trash_retention_days(days)
+}
+
+/// Wipe one kind of data (or all of it) from a project while keeping the project itself
+#[tauri::command]
+pub async fn clear_project_data(
+    state: State<'_, AppState>,
+    project_id: String,
+    what: ClearTarget,
+) -> Result<ClearedData> {
+    let _job = state.start_project_job(&project_id, "clearing project data")?;
+
+    let storage = state.storage.lock();
+    let project = storage.get_project(&project_id)?;
+    let db_path = storage.get_database_path(&project);
+    drop(storage);
+
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    state.duckdb.clear_project_data(&conn, what)
+}
+
+/// What's holding the project's lock, so the UI can say why an operation was refused
+#[tauri::command]
+pub async fn get_active_jobs(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<Vec<ActiveJob>> {
+    Ok(state.active_jobs(&project_id))
+}
+
 #[tauri::command]
 pub async fn update_project(
     state: State<'_, AppState>,
@@ -49,6 +120,71 @@ pub async fn update_project(
     storage.update_project(&id, name, description)
 }
 
+/// Time budget per project for `global_search`, including waiting for its connection
+const GLOBAL_SEARCH_PROJECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Search table names, documents, saved queries and conversation titles in every project
+/// Projects that fail to open are reported as errors and skipped; ones that run out
+/// of time keep the hits found so far and are reported as errors too
+#[tauri::command]
+pub async fn global_search(
+    state: State<'_, AppState>,
+    query: String,
+) -> Result<GlobalSearchResult> {
+    let query = query.trim();
+    let mut hits = Vec::new();
+    let mut errors = Vec::new();
+    if query.is_empty() {
+        return Ok(GlobalSearchResult { hits, errors });
+    }
+
+    let projects = state.storage.lock().list_full_projects()?;
+
+    for project in projects {
+        let deadline = Instant::now() + GLOBAL_SEARCH_PROJECT_TIMEOUT;
+        let db_path = state.storage.lock().get_database_path(&project);
+
+        let searched = state
+            .duckdb
+            .get_connection(&project.id, &db_path)
+            .and_then(|conn| {
+                let conn = conn.try_lock_until(deadline).ok_or_else(|| {
+                    AppError::Custom("Database is busy with another operation".into())
+                })?;
+                state.duckdb.search_names(&conn, query, deadline)
+            });
+
+        match searched {
+            Ok((found, timed_out)) => {
+                hits.extend(found.into_iter().map(|(kind, label)| GlobalHit {
+                    project_id: project.id.clone(),
+                    project_name: project.name.clone(),
+                    kind,
+                    label,
+                }));
+                if timed_out {
+                    warn!(target: "search", "Search of project {} timed out", project.id);
+                    errors.push(GlobalSearchError {
+                        project_id: project.id,
+                        project_name: project.name,
+                        error: "Search timed out, results are incomplete".into(),
+                    });
+                }
+            }
+            Err(e) => {
+                warn!(target: "search", "Skipping project {}: {}", project.id, e);
+                errors.push(GlobalSearchError {
+                    project_id: project.id,
+                    project_name: project.name,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(GlobalSearchResult { hits, errors })
+}
+
 #[tauri::command]
 pub async fn get_all_project_stats(state: State<'_, AppState>) -> Result<Vec<ProjectStats>> {
     let storage = state.storage.lock();
@@ -57,12 +193,26 @@ pub async fn get_all_project_stats(state: State<'_, AppState>) -> Result<Vec<Pro
 
     let mut all_stats = Vec::new();
 
+    // One broken project shouldn't fail the whole dashboard: failures are reported
+    // on that project's stats and collection carries on
+    let empty_stats = |project_id: String, storage_size: u64, error: String| ProjectStats {
+        project_id,
+        table_count: 0,
+        total_rows: 0,
+        conversation_count: 0,
+        saved_query_count: 0,
+        document_count: 0,
+        storage_size,
+        error: Some(error),
+    };
+
     for project_summary in projects {
         let storage = state.storage.lock();
         let project = match storage.get_project(&project_summary.id) {
             Ok(p) => p,
             Err(e) => {
-                eprintln!("[stats] Failed to get project {}: {}", project_summary.id, e);
+                warn!(target: "stats", "Failed to get project {}: {}", project_summary.id, e);
+                all_stats.push(empty_stats(project_summary.id, 0, e.to_string()));
                 continue;
             }
         };
@@ -72,21 +222,18 @@ pub async fn get_all_project_stats(state: State<'_, AppState>) -> Result<Vec<Pro
         let conn = match state.duckdb.get_connection(&project_summary.id, &db_path) {
             Ok(c) => c,
             Err(e) => {
-                eprintln!("[stats] Failed to connect to database for {}: {}", project_summary.id, e);
+                warn!(target: "stats", "Failed to connect to database for {}: {}", project_summary.id, e);
                 // Return stats with zeros if we can't connect
-                all_stats.push(ProjectStats {
-                    project_id: project_summary.id,
-                    table_count: 0,
-                    total_rows: 0,
-                    conversation_count: 0,
-                    saved_query_count: 0,
-                    document_count: 0,
-                    storage_size: fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0),
-                });
+                all_stats.push(empty_stats(
+                    project_summary.id,
+                    fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0),
+                    e.to_string(),
+                ));
                 continue;
             }
         };
         let conn = conn.lock();
+        let mut errors: Vec<String> = Vec::new();
 
         // Ensure metadata tables exist before querying them
         if let Err(e) = conn.execute_batch(
@@ -122,7 +269,8 @@ pub async fn get_all_project_stats(state: State<'_, AppState>) -> Result<Vec<Pro
             );
             "#,
         ) {
-            eprintln!("[stats] Failed to create metadata tables for {}: {}", project_summary.id, e);
+            warn!(target: "stats", "Failed to create metadata tables for {}: {}", project_summary.id, e);
+            errors.push(e.to_string());
         }
 
         // Get table count and total rows
@@ -133,7 +281,8 @@ pub async fn get_all_project_stats(state: State<'_, AppState>) -> Result<Vec<Pro
                 (count, rows)
             }
             Err(e) => {
-                eprintln!("[stats] Failed to get tables for {}: {}", project_summary.id, e);
+                warn!(target: "stats", "Failed to get tables for {}: {}", project_summary.id, e);
+                errors.push(e.to_string());
                 (0, 0)
             }
         };
@@ -178,12 +327,50 @@ pub async fn get_all_project_stats(state: State<'_, AppState>) -> Result<Vec<Pro
             saved_query_count,
             document_count,
             storage_size,
+            error: if errors.is_empty() { None } else { Some(errors.join("; ")) },
         });
     }
 
     Ok(all_stats)
 }
 
+#[tauri::command]
+pub async fn get_data_dir() -> Result<DataDirInfo> {
+    StorageService::resolve_data_dir()
+}
+
+/// Point the app at a different data directory, or back to the default when `path` is None
+/// With `copy_existing`, current projects are copied over unless the target already has some
+/// The DUCKBAKE_DATA_DIR environment variable still takes precedence on the next launch
+#[tauri::command]
+pub async fn set_data_dir(
+    state: State<'_, AppState>,
+    path: Option<String>,
+    copy_existing: bool,
+) -> Result<DataDirInfo> {
+    let default_dir = PathBuf::from(StorageService::resolve_data_dir()?.default_data_dir);
+    let new_dir = path.as_ref().map(PathBuf::from).unwrap_or(default_dir);
+    StorageService::check_writable(&new_dir)?;
+
+    // Checkpoint and release every database so the files are complete and not in use
+    state.duckdb.close_all_connections();
+
+    let mut storage = state.storage.lock();
+    if copy_existing {
+        storage.copy_data_to(&new_dir)?;
+    }
+
+    let new_storage = StorageService::open(new_dir)?;
+    StorageService::save_data_dir_override(path.as_deref().map(Path::new))?;
+    for project in new_storage.list_full_projects()? {
+        state.duckdb.register_settings(&project.id, project.duckdb_settings);
+    }
+    *storage = new_storage;
+    drop(storage);
+
+    StorageService::resolve_data_dir()
+}
+
 #[tauri::command]
 pub async fn export_project(
     state: State<'_, AppState>,
@@ -199,9 +386,7 @@ pub async fn export_project(
     {
         let conn = state.duckdb.get_connection(&project_id, &db_path)?;
         let conn = conn.lock();
-        conn.execute_batch("CHECKPOINT;").map_err(|e| {
-            AppError::Custom(format!("Failed to checkpoint database: {}", e))
-        })?;
+        state.duckdb.checkpoint(&conn)?;
     } // Connection Arc is dropped here
 
     // Close connection from cache to release file lock
@@ -254,6 +439,7 @@ pub async fn import_project(
     // Update all project_id references in the imported database to match the new project ID
     // The exported database has the old project's ID, we need to update it
     let conn = state.duckdb.get_connection(&project.id, &db_path)?;
+    let mut chunk_restore_error = None;
     {
         let conn = conn.lock();
 
@@ -280,13 +466,13 @@ pub async fn import_project(
 
             // Step 1: Backup documents
             if let Err(e) = conn.execute_batch("CREATE TEMP TABLE _temp_docs AS SELECT * FROM _duckbake_documents;") {
-                eprintln!("[import] Failed to backup documents: {}", e);
+                warn!(target: "import", "Failed to backup documents: {}", e);
             }
 
             // Step 2: Backup chunks if they exist
             if has_chunks {
                 if let Err(e) = conn.execute_batch("CREATE TEMP TABLE _temp_chunks AS SELECT * FROM _duckbake_document_chunks;") {
-                    eprintln!("[import] Failed to backup chunks: {}", e);
+                    warn!(target: "import", "Failed to backup chunks: {}", e);
                 }
             }
 
@@ -310,25 +496,26 @@ pub async fn import_project(
                     headings TEXT,
                     content TEXT NOT NULL,
                     uploaded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-                    is_vectorized BOOLEAN DEFAULT FALSE
+                    is_vectorized BOOLEAN DEFAULT FALSE,
+                    source_path VARCHAR
                 );
             "#;
             if let Err(e) = conn.execute_batch(create_docs) {
-                eprintln!("[import] Failed to recreate documents table: {}", e);
+                warn!(target: "import", "Failed to recreate documents table: {}", e);
             }
 
             // Step 5: Restore documents with new project_id
+            // By name, since exports from older versions may lack newer columns
             let restore_docs = format!(
                 r#"
-                INSERT INTO _duckbake_documents
-                SELECT id, '{}', filename, file_type, file_size, page_count, word_count,
-                       title, author, creation_date, headings, content, uploaded_at, is_vectorized
+                INSERT INTO _duckbake_documents BY NAME
+                SELECT * REPLACE ('{}' AS project_id)
                 FROM _temp_docs;
                 "#,
                 project.id
             );
             if let Err(e) = conn.execute_batch(&restore_docs) {
-                eprintln!("[import] Failed to restore documents: {}", e);
+                warn!(target: "import", "Failed to restore documents: {}", e);
             }
 
             // Step 6: Recreate chunks table without foreign key constraint
@@ -342,17 +529,20 @@ pub async fn import_project(
                     start_offset INTEGER NOT NULL,
                     end_offset INTEGER NOT NULL,
                     embedding FLOAT[],
-                    embedding_model VARCHAR
+                    embedding_model VARCHAR,
+                    heading_path VARCHAR,
+                    embedded_hash VARCHAR
                 );
             "#;
             if let Err(e) = conn.execute_batch(create_chunks) {
-                eprintln!("[import] Failed to recreate chunks table: {}", e);
+                warn!(target: "import", "Failed to recreate chunks table: {}", e);
             }
 
-            // Step 7: Restore chunks if they existed
+            // Step 7: Restore chunks if they existed. A failure here would silently drop
+            // every chunk and embedding, so it fails the import instead
             if has_chunks {
-                if let Err(e) = conn.execute_batch("INSERT INTO _duckbake_document_chunks SELECT * FROM _temp_chunks;") {
-                    eprintln!("[import] Failed to restore chunks: {}", e);
+                if let Err(e) = conn.execute_batch("INSERT INTO _duckbake_document_chunks BY NAME SELECT * FROM _temp_chunks;") {
+                    chunk_restore_error = Some(e);
                 }
             }
 
@@ -378,5 +568,13 @@ pub async fn import_project(
         ).ok();
     }
 
+    if let Some(e) = chunk_restore_error {
+        // Don't leave a half-imported project behind
+        drop(conn);
+        state.duckdb.close_connection(&project.id);
+        let _ = state.storage.lock().delete_project(&project.id);
+        return Err(AppError::Custom(format!("Failed to restore document chunks: {}", e)));
+    }
+
     Ok(project)
 }
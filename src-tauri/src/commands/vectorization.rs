@@ -1,11 +1,36 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Instant;
+
+use duckdb::Connection;
+use parking_lot::Mutex;
 use tauri::{Emitter, State, Window};
-
-use crate::error::Result;
-use crate::models::{VectorizationProgress, VectorizationStatus};
+use tracing::{debug, info, warn};
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    estimate_eta, progress_percent, ExportFormat, ExportResult, Project, SimilarityMetric,
+    VectorizationProgress, VectorizationStatus,
+};
+use crate::services::{quote_ident, DuckDbService, OllamaService};
 use crate::state::AppState;
 
 const BATCH_SIZE: usize = 50;
-const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+/// Cap on distinct texts remembered per job so huge unique columns don't exhaust memory
+const MAX_CACHED_EMBEDDINGS: usize = 10_000;
+
+/// A batch slower than this many times the average is treated as a model reload
+const LATENCY_SPIKE_FACTOR: f64 = 5.0;
+/// Ignore spikes on batches faster than this, small batches are noisy
+const LATENCY_SPIKE_MIN_SECS: f64 = 10.0;
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
 
 #[tauri::command]
 pub async fn get_vectorization_status(
@@ -46,39 +71,122 @@ pub async fn vectorize_table(
     project_id: String,
     table_name: String,
     columns: Vec<String>,
+    resume: Option<bool>,
+    metric: Option<SimilarityMetric>,
+    normalize: Option<bool>,
+    model: Option<String>,
+    where_clause: Option<String>,
 ) -> Result<()> {
-    let db_path = {
+    let _job = state.start_project_job(&project_id, &format!("vectorizing {}", table_name))?;
+
+    let (db_path, project_model) = {
         let storage = state.storage.lock();
         let project = storage.get_project(&project_id)?;
-        storage.get_database_path(&project)
+        (
+            storage.get_database_path(&project),
+            project.embedding_model_name().to_string(),
+        )
     };
 
-    // Get total row count
+    // Only rows matching the filter are embedded, checked before any work starts
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let row_filter = match where_clause.as_deref().map(str::trim) {
+        Some(filter) if !filter.is_empty() => Some(DuckDbService::validate_row_filter(
+            &conn.lock(),
+            &table_name,
+            filter,
+        )?),
+        _ => None,
+    };
+    let filter_sql = row_filter
+        .as_deref()
+        .map(|f| format!(" AND ({})", f))
+        .unwrap_or_default();
+
+    // Let the UI know we're counting rows, which can be slow on large tables
+    let _ = window.emit(
+        "vectorization-progress",
+        VectorizationProgress {
+            table_name: table_name.clone(),
+            total_rows: 0,
+            processed_rows: 0,
+            status: "counting".to_string(),
+            error: None,
+            cache_hit_rate: None,
+            batch_index: None,
+            progress_percent: 0.0,
+            eta_seconds: None,
+        },
+    );
+
+    // Get total row count
     let total_rows: i64 = {
         let conn = conn.lock();
         conn.query_row(
-            &format!("SELECT COUNT(*) FROM \"{}\"", table_name),
+            &format!(
+                "SELECT COUNT(*) FROM {} WHERE true{}",
+                quote_ident(&table_name),
+                filter_sql
+            ),
             [],
             |row| row.get(0),
         )
         .unwrap_or(0)
     };
 
+    let column_key = columns.join("+");
+    let metric = metric.unwrap_or_default();
+    let normalize = normalize.unwrap_or(true);
+    let model = model.unwrap_or(project_model);
+
+    // When resuming, pick up after the last rowid a previous run stored
+    let (start_row_id, resumed_rows) = {
+        let conn = conn.lock();
+        state.duckdb.init_embeddings_table(&conn)?;
+        let checkpoint = if resume.unwrap_or(false) {
+            state
+                .duckdb
+                .get_vectorization_checkpoint(&conn, &table_name, &column_key, &model)
+        } else {
+            None
+        };
+        match checkpoint {
+            Some(row_id) => {
+                let done: i64 = conn
+                    .query_row(
+                        &format!(
+                            "SELECT COUNT(*) FROM {} WHERE rowid <= ?{}",
+                            quote_ident(&table_name),
+                            filter_sql
+                        ),
+                        [row_id],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or(0);
+                (Some(row_id), done)
+            }
+            None => (None, 0),
+        }
+    };
+
     // Emit initial progress - loading model
     let _ = window.emit(
         "vectorization-progress",
         VectorizationProgress {
             table_name: table_name.clone(),
             total_rows,
-            processed_rows: 0,
+            processed_rows: resumed_rows,
             status: "loading_model".to_string(),
             error: None,
+            cache_hit_rate: None,
+            batch_index: None,
+            progress_percent: progress_percent(resumed_rows, total_rows),
+            eta_seconds: None,
         },
     );
 
     // Warm up the embedding model first (loads it into memory)
-    if let Err(e) = state.ollama.warmup_embedding_model(Some(DEFAULT_EMBEDDING_MODEL)).await {
+    if let Err(e) = state.ollama.warmup_embedding_model(Some(&model)).await {
         let _ = window.emit(
             "vectorization-progress",
             VectorizationProgress {
@@ -87,6 +195,10 @@ pub async fn vectorize_table(
                 processed_rows: 0,
                 status: "error".to_string(),
                 error: Some(e.to_string()),
+                cache_hit_rate: None,
+                batch_index: None,
+                progress_percent: progress_percent(0, total_rows),
+                eta_seconds: None,
             },
         );
         return Err(e);
@@ -98,24 +210,39 @@ pub async fn vectorize_table(
         VectorizationProgress {
             table_name: table_name.clone(),
             total_rows,
-            processed_rows: 0,
+            processed_rows: resumed_rows,
             status: "processing".to_string(),
             error: None,
+            cache_hit_rate: None,
+            batch_index: None,
+            progress_percent: progress_percent(resumed_rows, total_rows),
+            eta_seconds: None,
         },
     );
 
-    // Initialize embeddings table and remove existing embeddings
-    {
+    // Starting fresh: remove this model's existing embeddings, leaving other models intact
+    if start_row_id.is_none() {
         let conn = conn.lock();
-        state.duckdb.init_embeddings_table(&conn)?;
-        state.duckdb.remove_vectorization(&conn, &table_name)?;
+        state
+            .duckdb
+            .remove_vectorization(&conn, &table_name, Some(&model))?;
     }
 
     // Clear any previous cancellation flag for this table
     state.clear_vectorization_cancellation(&table_name);
 
-    let mut processed = 0i64;
-    let mut offset = 0usize;
+    let mut processed = resumed_rows;
+    // Rows resumed from a previous run don't count towards this run's rate
+    let started = Instant::now();
+    let mut last_row_id = start_row_id.unwrap_or(-1);
+
+    // Repeated values (categoricals, boilerplate) are embedded once per job
+    let mut cache: HashMap<u64, Vec<f32>> = HashMap::new();
+    let mut cache_hits = 0i64;
+    let mut cache_lookups = 0i64;
+    let mut batch_index = 0i64;
+    let mut embedding_secs = 0.0f64;
+    let mut embedded_batches = 0i64;
 
     loop {
         // Check if cancellation was requested
@@ -129,6 +256,10 @@ pub async fn vectorize_table(
                     processed_rows: processed,
                     status: "cancelled".to_string(),
                     error: None,
+                    cache_hit_rate: None,
+                    batch_index: None,
+                    progress_percent: progress_percent(processed, total_rows),
+                    eta_seconds: None,
                 },
             );
             return Ok(());
@@ -142,7 +273,8 @@ pub async fn vectorize_table(
                 &table_name,
                 &columns,
                 BATCH_SIZE,
-                offset,
+                last_row_id,
+                row_filter.as_deref(),
             )?
         };
 
@@ -151,16 +283,129 @@ pub async fn vectorize_table(
         }
 
         let batch_count = rows.len();
+        batch_index += 1;
+        let batch_last_row_id = rows.last().map(|(id, _)| *id).unwrap_or(last_row_id);
 
         // Extract texts for embedding
         let texts: Vec<String> = rows.iter().map(|(_, text)| text.clone()).collect();
         let row_ids: Vec<i64> = rows.iter().map(|(id, _)| *id).collect();
 
+        // Only send texts that haven't been embedded yet, each one once
+        let hashes: Vec<u64> = texts.iter().map(|t| text_hash(t)).collect();
+        let mut uncached: Vec<(u64, String)> = Vec::new();
+        for (hash, text) in hashes.iter().zip(texts.iter()) {
+            if !cache.contains_key(hash) && !uncached.iter().any(|(h, _)| h == hash) {
+                uncached.push((*hash, text.clone()));
+            }
+        }
+        cache_lookups += texts.len() as i64;
+        cache_hits += (texts.len() - uncached.len()) as i64;
+
         // Generate embeddings
-        let embeddings = state
-            .ollama
-            .generate_embeddings(texts.clone(), Some(DEFAULT_EMBEDDING_MODEL))
-            .await?;
+        let mut fresh: HashMap<u64, Vec<f32>> = HashMap::new();
+        if !uncached.is_empty() {
+            // One batch can take minutes on a cold model; let the UI show it's still working
+            let _ = window.emit(
+                "vectorization-progress",
+                VectorizationProgress {
+                    table_name: table_name.clone(),
+                    total_rows,
+                    processed_rows: processed,
+                    status: "embedding_batch".to_string(),
+                    error: None,
+                    cache_hit_rate: None,
+                    batch_index: Some(batch_index),
+                    progress_percent: progress_percent(processed, total_rows),
+                    eta_seconds: estimate_eta(
+                        processed - resumed_rows,
+                        total_rows - resumed_rows,
+                        started.elapsed(),
+                    ),
+                },
+            );
+
+            let batch_texts: Vec<String> = uncached.iter().map(|(_, text)| text.clone()).collect();
+            let batch_started = Instant::now();
+            let embedded = match state
+                .ollama
+                .generate_embeddings(batch_texts.clone(), Some(&model))
+                .await
+            {
+                Ok(embedded) => embedded,
+                // Ollama may have unloaded the model (keep_alive expiry, memory pressure):
+                // load it again and retry the batch once
+                Err(e) if OllamaService::is_model_unload_error(&e) => {
+                    warn!(
+                        target: "vectorization",
+                        "{}: reloading {} after: {}",
+                        table_name,
+                        model,
+                        e
+                    );
+                    let _ = window.emit(
+                        "vectorization-progress",
+                        VectorizationProgress {
+                            table_name: table_name.clone(),
+                            total_rows,
+                            processed_rows: processed,
+                            status: "reloading_model".to_string(),
+                            error: None,
+                            cache_hit_rate: None,
+                            batch_index: Some(batch_index),
+                            progress_percent: progress_percent(processed, total_rows),
+                            eta_seconds: None,
+                        },
+                    );
+                    state.ollama.warmup_embedding_model(Some(&model)).await?;
+                    state
+                        .ollama
+                        .generate_embeddings(batch_texts, Some(&model))
+                        .await?
+                }
+                Err(e) => return Err(e),
+            };
+
+            // A batch far slower than usual usually means the model was cold-loaded again
+            let batch_secs = batch_started.elapsed().as_secs_f64();
+            if embedded_batches > 0 {
+                let average = embedding_secs / embedded_batches as f64;
+                if batch_secs > LATENCY_SPIKE_MIN_SECS
+                    && batch_secs > average * LATENCY_SPIKE_FACTOR
+                {
+                    warn!(
+                        target: "vectorization",
+                        "{}: batch {} took {:.1}s (average {:.1}s), model was likely reloaded",
+                        table_name,
+                        batch_index,
+                        batch_secs,
+                        average
+                    );
+                }
+            }
+            embedding_secs += batch_secs;
+            embedded_batches += 1;
+
+            fresh = uncached.iter().map(|(hash, _)| *hash).zip(embedded).collect();
+        }
+
+        // Fan results back out to every row sharing the same text
+        let embeddings: Vec<Vec<f32>> = hashes
+            .iter()
+            .map(|hash| {
+                fresh
+                    .get(hash)
+                    .or_else(|| cache.get(hash))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        for (hash, embedding) in fresh {
+            if cache.len() >= MAX_CACHED_EMBEDDINGS {
+                break;
+            }
+            cache.insert(hash, embedding);
+        }
 
         // Store embeddings
         let embedding_rows: Vec<(i64, String, Vec<f32>)> = row_ids
@@ -168,22 +413,33 @@ pub async fn vectorize_table(
             .zip(texts.into_iter())
             .zip(embeddings.into_iter())
             .map(|((id, text), emb)| (id, text, emb))
+            .filter(|(_, _, emb)| !emb.is_empty())
             .collect();
 
         {
             let conn = conn.lock();
-            let column_key = columns.join("+");
             state.duckdb.store_embeddings(
                 &conn,
                 &table_name,
                 &column_key,
                 embedding_rows,
-                DEFAULT_EMBEDDING_MODEL,
+                &model,
+                metric,
+                normalize,
+                row_filter.as_deref(),
             )?;
         }
 
+        // Only move on once the batch is committed, so a failed batch is retried whole
+        last_row_id = batch_last_row_id;
         processed += batch_count as i64;
-        offset += batch_count;
+        debug!(
+            target: "vectorization",
+            "{}: {}/{} rows embedded",
+            table_name,
+            processed,
+            total_rows
+        );
 
         // Emit progress
         let _ = window.emit(
@@ -194,11 +450,31 @@ pub async fn vectorize_table(
                 processed_rows: processed,
                 status: "processing".to_string(),
                 error: None,
+                cache_hit_rate: None,
+                batch_index: None,
+                progress_percent: progress_percent(processed, total_rows),
+                eta_seconds: estimate_eta(
+                    processed - resumed_rows,
+                    total_rows - resumed_rows,
+                    started.elapsed(),
+                ),
             },
         );
     }
 
     // Emit completion
+    let cache_hit_rate = if cache_lookups > 0 {
+        cache_hits as f64 / cache_lookups as f64
+    } else {
+        0.0
+    };
+    info!(
+        target: "vectorization",
+        "{}: {} rows, {:.1}% embedding cache hits",
+        table_name,
+        cache_lookups,
+        cache_hit_rate * 100.0
+    );
     let _ = window.emit(
         "vectorization-progress",
         VectorizationProgress {
@@ -207,6 +483,10 @@ pub async fn vectorize_table(
             processed_rows: processed,
             status: "completed".to_string(),
             error: None,
+            cache_hit_rate: Some(cache_hit_rate),
+            batch_index: None,
+            progress_percent: progress_percent(processed, total_rows),
+            eta_seconds: None,
         },
     );
 
@@ -218,7 +498,13 @@ pub async fn remove_vectorization(
     state: State<'_, AppState>,
     project_id: String,
     table_name: String,
+    model: Option<String>,
 ) -> Result<()> {
+    let _job = state.start_project_job(
+        &project_id,
+        &format!("removing embeddings of {}", table_name),
+    )?;
+
     let storage = state.storage.lock();
     let project = storage.get_project(&project_id)?;
     let db_path = storage.get_database_path(&project);
@@ -226,7 +512,61 @@ pub async fn remove_vectorization(
 
     let conn = state.duckdb.get_connection(&project_id, &db_path)?;
     let conn = conn.lock();
-    state.duckdb.remove_vectorization(&conn, &table_name)
+    state
+        .duckdb
+        .remove_vectorization(&conn, &table_name, model.as_deref())
+}
+
+/// Choose the embedding model for a project. The model is probed for its vector
+/// length, which can only change while the project has no stored embeddings
+#[tauri::command]
+pub async fn set_project_embedding_model(
+    state: State<'_, AppState>,
+    project_id: String,
+    model: String,
+) -> Result<Project> {
+    let (db_path, recorded_dimension) = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(&project_id)?;
+        (storage.get_database_path(&project), project.embedding_dimension)
+    };
+
+    let dimension = state
+        .ollama
+        .generate_embeddings(vec!["dimension probe".to_string()], Some(&model))
+        .await?
+        .first()
+        .map(Vec::len)
+        .filter(|len| *len > 0)
+        .ok_or_else(|| AppError::Custom(format!("Model '{}' returned no embedding", model)))?;
+
+    // Projects that never switched model have no dimension recorded, so fall back
+    // to the length of the vectors they already store
+    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
+    let conn = conn.lock();
+    let existing_dimension =
+        recorded_dimension.or_else(|| state.duckdb.stored_embedding_dimension(&conn));
+    if let Some(existing) = existing_dimension.filter(|existing| *existing != dimension) {
+        if state.duckdb.has_embeddings(&conn) {
+            return Err(AppError::Custom(format!(
+                "Model '{}' produces {}-dimensional vectors but this project's embeddings are {}-dimensional. Remove existing vectorizations before switching.",
+                model, dimension, existing
+            )));
+        }
+    }
+    drop(conn);
+
+    info!(
+        target: "vectorization",
+        "Project {} now embeds with {} ({} dimensions)",
+        project_id,
+        model,
+        dimension
+    );
+    state
+        .storage
+        .lock()
+        .update_embedding_model(&project_id, model, dimension)
 }
 
 #[tauri::command]
@@ -245,29 +585,19 @@ pub async fn semantic_search(
     table_name: String,
     query: String,
     limit: Option<usize>,
+    metric: Option<SimilarityMetric>,
+    model: Option<String>,
 ) -> Result<Vec<serde_json::Value>> {
-    let db_path = {
-        let storage = state.storage.lock();
-        let project = storage.get_project(&project_id)?;
-        storage.get_database_path(&project)
-    };
-
-    // Generate embedding for query
-    let embeddings = state
-        .ollama
-        .generate_embeddings(vec![query], Some(DEFAULT_EMBEDDING_MODEL))
-        .await?;
-
-    let query_embedding = embeddings.into_iter().next().unwrap_or_default();
-
-    let conn = state.duckdb.get_connection(&project_id, &db_path)?;
-    let conn = conn.lock();
+    let search = embed_search_query(&state, &project_id, &table_name, query, metric, model).await?;
+    let conn = search.conn.lock();
 
     let results = state.duckdb.semantic_search(
         &conn,
         &table_name,
-        &query_embedding,
+        &search.model,
+        &search.embedding,
         limit.unwrap_or(10),
+        search.metric,
     )?;
 
     // Convert to JSON
@@ -284,3 +614,97 @@ pub async fn semantic_search(
 
     Ok(json_results)
 }
+
+/// Run a semantic search and write the matching source rows, with their scores, to a file
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_semantic_search(
+    state: State<'_, AppState>,
+    project_id: String,
+    table_name: String,
+    query: String,
+    limit: Option<usize>,
+    file_path: String,
+    format: ExportFormat,
+    metric: Option<SimilarityMetric>,
+    model: Option<String>,
+) -> Result<ExportResult> {
+    let search = embed_search_query(&state, &project_id, &table_name, query, metric, model).await?;
+    let conn = search.conn.lock();
+
+    state.duckdb.export_semantic_search(
+        &conn,
+        &table_name,
+        &search.model,
+        &search.embedding,
+        limit.unwrap_or(10),
+        search.metric,
+        &file_path,
+        format,
+    )
+}
+
+/// A search query embedded the same way as the table it searches
+struct EmbeddedSearch {
+    conn: Arc<Mutex<Connection>>,
+    model: String,
+    metric: SimilarityMetric,
+    embedding: Vec<f32>,
+}
+
+/// Embed a query with the same model the table was vectorized with. Each model's
+/// embeddings are searched on their own; default to the most recently used one
+async fn embed_search_query(
+    state: &AppState,
+    project_id: &str,
+    table_name: &str,
+    query: String,
+    metric: Option<SimilarityMetric>,
+    model: Option<String>,
+) -> Result<EmbeddedSearch> {
+    // Checked before anything else so a bad query never costs an embedding call
+    let query = DuckDbService::validate_search_query(&query)?;
+
+    let (db_path, project_model) = {
+        let storage = state.storage.lock();
+        let project = storage.get_project(project_id)?;
+        (
+            storage.get_database_path(&project),
+            project.embedding_model_name().to_string(),
+        )
+    };
+
+    let conn = state.duckdb.get_connection(project_id, &db_path)?;
+    state.duckdb.require_vector_search(project_id)?;
+
+    let (model, stored_metric, normalized) = {
+        let conn = conn.lock();
+        let model = model
+            .or_else(|| state.duckdb.get_embedding_model(&conn, table_name))
+            .unwrap_or(project_model);
+        let stored_metric = state.duckdb.get_similarity_metric(&conn, table_name, &model);
+        let normalized = state.duckdb.is_normalized(&conn, table_name, &model);
+        (model, stored_metric, normalized)
+    };
+    let metric = metric.or(stored_metric).unwrap_or_default();
+
+    // Generate embedding for query
+    let embeddings = state
+        .ollama
+        .generate_embeddings(vec![query], Some(&model))
+        .await?;
+
+    let mut embedding = embeddings.into_iter().next().unwrap_or_default();
+
+    // Match the stored vectors so dot product and cosine rank identically
+    if normalized {
+        DuckDbService::normalize_embedding(&mut embedding);
+    }
+
+    Ok(EmbeddedSearch {
+        conn,
+        model,
+        metric,
+        embedding,
+    })
+}
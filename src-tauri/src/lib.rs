@@ -1,5 +1,6 @@
 mod commands;
 mod error;
+mod logging;
 mod models;
 mod services;
 mod state;
@@ -7,16 +8,27 @@ mod state;
 use commands::*;
 use state::AppState;
 use tauri::menu::{Menu, MenuItemBuilder, SubmenuBuilder};
-use tauri::Emitter;
+use tauri::{Emitter, Manager, RunEvent};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Logging starts before app state so storage warnings make it into the log file
+    match services::StorageService::resolve_data_dir() {
+        Ok(info) => {
+            if let Err(e) = logging::init(&std::path::Path::new(&info.data_dir).join("logs")) {
+                eprintln!("[logging] {}", e);
+            }
+        }
+        Err(e) => eprintln!("[logging] Could not determine log directory: {}", e),
+    }
+
     let app_state = AppState::new().expect("Failed to initialize app state");
 
     let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_window_state::Builder::new().build());
 
     #[cfg(feature = "updater")]
@@ -114,25 +126,85 @@ pub fn run() {
             list_projects,
             open_project,
             delete_project,
+            list_trashed_projects,
+            restore_project,
+            purge_project,
+            get_trash_retention_days,
+            set_trash_retention_days,
+            clear_project_data,
+            get_active_jobs,
             update_project,
             get_all_project_stats,
+            global_search,
+            get_data_dir,
+            set_data_dir,
             export_project,
             import_project,
             // Database commands
             get_tables,
+            list_tables_fast,
             get_table_schema,
+            diff_tables,
+            get_table_ddl,
+            get_project_schema_sql,
             execute_query,
+            execute_chat_query,
+            suggest_visualization,
+            aggregate,
+            execute_query_streamed,
+            cancel_query_stream,
+            validate_query,
+            materialize_query,
             query_table,
+            query_table_seek,
+            get_random_sample,
+            count_rows,
+            get_distinct_values,
+            rename_column,
+            add_column,
+            add_generated_column,
+            drop_column,
+            change_column_type,
+            deduplicate_table,
             delete_table,
+            checkpoint,
+            get_storage_breakdown,
+            list_open_connections,
+            close_connection,
+            shutdown,
             get_project_context,
+            get_capabilities,
+            attach_database,
+            detach_database,
+            list_attached_databases,
+            copy_result_as_tsv,
+            get_cell_value,
+            set_duckdb_settings,
+            get_duckdb_settings,
             // Import commands
             preview_import,
+            describe_file,
+            peek_file,
+            estimate_row_count,
             import_file,
+            get_import_target,
+            set_project_import_defaults,
+            get_recent_imports,
+            reimport,
+            validate_import,
             get_supported_extensions,
             // Ollama commands
             check_ollama_status,
             list_ollama_models,
+            set_project_chat_model,
+            get_project_chat_model,
             send_chat_message,
+            send_chat_message_and_run,
+            parse_viz_spec,
+            generate_conversation_title,
+            explain_table,
+            summarize_table,
+            extract_structured,
             pull_ollama_model,
             delete_ollama_model,
             // Vectorization commands
@@ -140,8 +212,10 @@ pub fn run() {
             get_text_columns,
             vectorize_table,
             remove_vectorization,
+            set_project_embedding_model,
             cancel_vectorization,
             semantic_search,
+            export_semantic_search,
             // Conversation commands
             list_conversations,
             create_conversation,
@@ -149,6 +223,8 @@ pub fn run() {
             update_conversation,
             delete_conversation,
             add_message,
+            save_draft,
+            get_draft,
             // Saved query commands
             list_saved_queries,
             save_query,
@@ -156,14 +232,31 @@ pub fn run() {
             delete_saved_query,
             // Document commands
             upload_document,
+            upload_document_text,
             get_documents,
             get_document,
             delete_document,
+            delete_documents,
             vectorize_document,
+            revectorize_document,
+            cancel_document_vectorization,
+            vectorize_documents,
+            set_project_vision_model,
             get_supported_document_extensions,
             semantic_search_documents,
             get_document_chunks_by_id,
+            get_document_chunks_paginated,
+            // Health commands
+            self_test,
+            get_log_path,
+            set_log_level,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Flush and close databases before the process goes away
+            if let RunEvent::ExitRequested { .. } | RunEvent::Exit = event {
+                app.state::<AppState>().duckdb.close_all_connections();
+            }
+        });
 }
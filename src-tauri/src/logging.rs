@@ -0,0 +1,75 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+use crate::error::{AppError, Result};
+
+/// File name prefix for the daily rolling log files
+const LOG_FILE_PREFIX: &str = "duckbake.log";
+
+/// Default verbosity; SQL text and embedded content are only logged at debug and below
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+struct Logger {
+    log_dir: PathBuf,
+    level: reload::Handle<LevelFilter, Registry>,
+    // Flushes buffered lines when dropped, so it lives as long as the process
+    _guard: WorkerGuard,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Send log output to a daily rolling file in `log_dir` as well as stderr
+/// Safe to call more than once; only the first call installs the subscriber
+pub fn init(log_dir: &Path) -> Result<()> {
+    if LOGGER.get().is_some() {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(log_dir)?;
+    let appender = tracing_appender::rolling::daily(log_dir, LOG_FILE_PREFIX);
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+
+    let (level_layer, level) = reload::Layer::new(DEFAULT_LEVEL);
+    tracing_subscriber::registry()
+        .with(level_layer)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(fmt::layer().with_ansi(false).with_writer(file_writer))
+        .try_init()
+        .map_err(|e| AppError::Custom(format!("Failed to initialize logging: {}", e)))?;
+
+    let _ = LOGGER.set(Logger {
+        log_dir: log_dir.to_path_buf(),
+        level,
+        _guard: guard,
+    });
+    Ok(())
+}
+
+/// Directory holding the log files, if logging was initialized
+pub fn log_dir() -> Option<PathBuf> {
+    LOGGER.get().map(|l| l.log_dir.clone())
+}
+
+/// Change the verbosity at runtime: "error", "warn", "info", "debug" or "trace"
+pub fn set_level(level: &str) -> Result<()> {
+    let filter: LevelFilter = level
+        .parse()
+        .map_err(|_| AppError::Custom(format!("Unknown log level: {}", level)))?;
+
+    let logger = LOGGER
+        .get()
+        .ok_or_else(|| AppError::Custom("Logging is not initialized".into()))?;
+    logger
+        .level
+        .reload(filter)
+        .map_err(|e| AppError::Custom(format!("Failed to change log level: {}", e)))?;
+
+    tracing::info!(target: "logging", "Log level set to {}", filter);
+    Ok(())
+}
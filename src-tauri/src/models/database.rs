@@ -1,5 +1,9 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use super::VizSpec;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TableInfo {
@@ -10,6 +14,17 @@ pub struct TableInfo {
     pub vectorized_columns: Vec<String>,
 }
 
+/// Lighter table listing built from DuckDB's catalog estimates, no table scans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSummary {
+    pub name: String,
+    pub estimated_row_count: i64,
+    pub column_count: i64,
+    pub estimated_size_bytes: Option<i64>, // Checkpointed blocks only, None when storage info is unavailable
+    pub is_vectorized: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VectorizationStatus {
@@ -17,7 +32,13 @@ pub struct VectorizationStatus {
     pub is_vectorized: bool,
     pub vectorized_columns: Vec<String>,
     pub embedding_count: i64,
-    pub embedding_model: Option<String>,
+    pub embedding_model: Option<String>, // Most recently used model
+    pub embedding_models: Vec<String>,
+    pub last_vectorized_at: Option<String>,
+    pub is_partial: bool, // Only rows matching `row_filter` were embedded
+    pub row_filter: Option<String>,
+    #[serde(default)]
+    pub needs_reindex: bool, // Rows were rebuilt since, so the embeddings point at old rowids
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,8 +47,41 @@ pub struct VectorizationProgress {
     pub table_name: String,
     pub total_rows: i64,
     pub processed_rows: i64,
-    pub status: String, // "pending", "processing", "completed", "error"
+    pub status: String, // "pending", "counting", "loading_model", "processing", "embedding_batch", "reloading_model", "completed", "cancelled", "error"
     pub error: Option<String>,
+    pub cache_hit_rate: Option<f64>, // Share of rows whose text was already embedded, set on completion
+    pub batch_index: Option<i64>, // Set while waiting on an "embedding_batch", counted from 1
+    pub progress_percent: f64,
+    pub eta_seconds: Option<f64>,
+}
+
+/// Outcome of an `extract_structured` run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionResult {
+    pub table_name: String,
+    pub columns: Vec<String>, // Columns the extracted fields were written to
+    pub rows_processed: i64,
+    pub rows_failed: i64, // Rows the model gave no usable answer for, left NULL
+    pub cancelled: bool,
+}
+
+/// Share of work done as a percentage, 0.0 when there is nothing to do
+pub fn progress_percent(processed: i64, total: i64) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+    (processed as f64 / total as f64 * 100.0).clamp(0.0, 100.0)
+}
+
+/// Seconds left at the rate seen so far, None until some work has been done
+/// `processed` and `total` should only count work done since `elapsed` started
+pub fn estimate_eta(processed: i64, total: i64, elapsed: Duration) -> Option<f64> {
+    if processed <= 0 || total <= 0 {
+        return None;
+    }
+    let remaining = (total - processed).max(0) as f64;
+    Some(elapsed.as_secs_f64() / processed as f64 * remaining)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +100,64 @@ pub struct TableSchema {
     pub columns: Vec<ColumnInfo>,
 }
 
+/// Outcome of `deduplicate_table`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupeResult {
+    pub table_name: String,
+    pub key_columns: Vec<String>, // Every column when the caller gave none
+    pub rows_before: i64,
+    pub rows_removed: i64,
+    pub embeddings_stale: bool, // The table was vectorized and is flagged for re-indexing
+}
+
+/// Outcome of `change_column_type`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnTypeChange {
+    pub schema: TableSchema, // The table after the change
+    pub old_type: String,
+    pub failed_casts: i64, // Non-null values that didn't convert and are now NULL
+}
+
+/// A column computed from an expression over the table's other columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedColumn {
+    pub table_name: String,
+    pub column_name: String,
+    pub expression: String,
+    pub view_name: Option<String>, // Set when the column lives on a view rather than the table
+}
+
+/// Differences between two tables, table B being the newer one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableDiff {
+    pub schema_changes: Vec<SchemaChange>,
+    pub compared_columns: Vec<String>, // Columns in both tables, which rows are compared on
+    pub rows_only_in_a: Option<RowDiff>, // None when row comparison wasn't requested
+    pub rows_only_in_b: Option<RowDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaChange {
+    pub column: String,
+    pub change: String, // "added" (only in B), "removed" (only in A), "retyped"
+    pub type_a: Option<String>,
+    pub type_b: Option<String>,
+}
+
+/// Rows of one table missing from the other, capped to a sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowDiff {
+    pub count: i64,
+    pub rows: QueryResult,
+    pub truncated: bool, // More differing rows exist than were returned
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryResult {
@@ -53,6 +165,136 @@ pub struct QueryResult {
     pub rows: Vec<serde_json::Value>,
     pub row_count: usize,
     pub execution_time_ms: u64,
+    #[serde(default)]
+    pub affected_rows: Option<usize>, // Set for INSERT/UPDATE/DELETE statements
+    #[serde(default)]
+    pub has_rowid: bool, // Rows carry a `_rowid` column, see `query_table`
+    #[serde(default)]
+    pub estimated_cardinality: Option<u64>, // Planner's row estimate for read queries
+}
+
+/// Messages sent over the channel passed to `execute_query_streamed`, in order:
+/// the column names, any number of row batches, then completion
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "event",
+    content = "data"
+)]
+pub enum QueryStreamEvent {
+    Columns {
+        columns: Vec<String>,
+    },
+    RowBatch {
+        rows: Vec<serde_json::Value>,
+    },
+    Complete {
+        row_count: usize,
+        execution_time_ms: u64,
+        affected_rows: Option<usize>, // Set for INSERT/UPDATE/DELETE statements
+        cancelled: bool,
+    },
+}
+
+/// One page of a table read in key order, for fast forward scrolling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeekPage {
+    pub result: QueryResult,
+    pub last_key: Option<serde_json::Value>, // Pass back as `after_key` to get the next page
+    pub has_more: bool,
+}
+
+/// Result of checking a statement against the schema without running it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryValidation {
+    pub valid: bool,
+    pub columns: Vec<ColumnInfo>, // Output columns, empty for statements that return no rows
+    pub error: Option<QueryError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryError {
+    pub message: String,
+    pub line: Option<usize>,   // From 1, when DuckDB points at the problem
+    pub column: Option<usize>, // From 1, in characters
+}
+
+/// Closest real name to a table or column a query referenced that doesn't exist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameSuggestion {
+    pub kind: String, // "table" or "column"
+    pub missing: String,
+    pub did_you_mean: String,
+    pub confidence: f64, // 0.0 to 1.0, from edit distance relative to name length
+    pub unambiguous: bool, // No other name is as close
+}
+
+/// Aggregate function applied to the measure column by `aggregate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateFn {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFn {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggregateFn::Count => "count",
+            AggregateFn::Sum => "sum",
+            AggregateFn::Avg => "avg",
+            AggregateFn::Min => "min",
+            AggregateFn::Max => "max",
+        }
+    }
+}
+
+/// Period `aggregate` truncates a date or timestamp group column to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeBucket {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeBucket {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeBucket::Day => "day",
+            TimeBucket::Week => "week",
+            TimeBucket::Month => "month",
+            TimeBucket::Year => "year",
+        }
+    }
+}
+
+/// Grouped aggregate of a table with a chart spec for showing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateResult {
+    pub result: QueryResult,
+    pub viz: VizSpec, // `sql` is the generated query
+}
+
+/// Outcome of running a query block from a chat reply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatQueryResult {
+    pub sql: String, // The statement that ran, after any correction
+    pub result: Option<QueryResult>,
+    pub error: Option<String>,
+    pub did_you_mean: Option<NameSuggestion>,
+    pub corrected: bool, // A misspelled name was replaced and the query re-run
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,3 +324,171 @@ pub struct TableContext {
 pub struct ProjectContext {
     pub tables: Vec<TableContext>,
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionCapabilities {
+    pub list_cosine_similarity: bool,
+    pub array_cosine_similarity: bool,
+    pub vss_extension: bool,
+    pub excel_reader: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachedDatabase {
+    pub alias: String,
+    pub file_path: String,
+    pub db_type: String, // "duckdb", "sqlite"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowCount {
+    pub count: i64,
+    pub exact: bool, // false when taken from DuckDB's table metadata estimate
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DistinctValue {
+    pub value: serde_json::Value,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DistinctValues {
+    pub column: String,
+    pub values: Vec<DistinctValue>,
+    pub truncated: bool, // More distinct values exist beyond the returned ones
+}
+
+/// A generated plain-English description of a table, cached per table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableNote {
+    pub table_name: String,
+    pub summary: String,
+    pub model: String,
+    pub updated_at: String,
+}
+
+/// Which kind of project data `clear_project_data` removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearTarget {
+    Tables, // User tables, along with their embeddings and notes
+    Embeddings,
+    Documents,
+    Conversations,
+    SavedQueries,
+    AllUserData,
+}
+
+/// What `clear_project_data` removed, for confirmation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearedData {
+    pub tables_dropped: Vec<String>,
+    pub embeddings_removed: usize, // Row embeddings plus document chunk embeddings
+    pub documents_removed: usize,
+    pub conversations_removed: usize,
+    pub messages_removed: usize,
+    pub saved_queries_removed: usize,
+}
+
+/// Bytes of a project's database file by what they hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageBreakdown {
+    pub file_size: u64,
+    pub categories: Vec<StorageCategory>, // Sums to file_size
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageCategory {
+    pub name: String, // "user_tables", "embeddings", "documents", "document_chunks", "conversations", "other"
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenConnection {
+    pub project_id: String,
+    pub last_used_at: String,
+    pub locked: bool, // Currently held by a running operation
+}
+
+/// File format for data written out with DuckDB's COPY
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+    Json,
+}
+
+impl ExportFormat {
+    /// Options clause for `COPY ... TO`
+    pub fn copy_options(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "(FORMAT CSV, HEADER)",
+            ExportFormat::Parquet => "(FORMAT PARQUET)",
+            ExportFormat::Json => "(FORMAT JSON)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    pub file_path: String,
+    pub rows_exported: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimilarityMetric {
+    #[default]
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+impl SimilarityMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SimilarityMetric::Cosine => "cosine",
+            SimilarityMetric::Dot => "dot",
+            SimilarityMetric::Euclidean => "euclidean",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "cosine" => Some(SimilarityMetric::Cosine),
+            "dot" => Some(SimilarityMetric::Dot),
+            "euclidean" => Some(SimilarityMetric::Euclidean),
+            _ => None,
+        }
+    }
+
+    /// DuckDB list function that scores a stored embedding against the query
+    pub fn sql_function(&self) -> &'static str {
+        match self {
+            SimilarityMetric::Cosine => "list_cosine_similarity",
+            SimilarityMetric::Dot => "list_dot_product",
+            SimilarityMetric::Euclidean => "list_distance",
+        }
+    }
+
+    /// Similarities rank highest-first; distances rank lowest-first
+    pub fn order_direction(&self) -> &'static str {
+        match self {
+            SimilarityMetric::Euclidean => "ASC",
+            _ => "DESC",
+        }
+    }
+}
@@ -17,6 +17,8 @@ pub struct Document {
     pub content: String,
     pub uploaded_at: String,
     pub is_vectorized: bool,
+    #[serde(default)]
+    pub source_path: Option<String>, // Original file for image documents, whose content is a description
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +34,42 @@ pub struct DocumentInfo {
     pub uploaded_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentPage {
+    pub documents: Vec<DocumentInfo>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentChunkPage {
+    pub chunks: Vec<DocumentChunk>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// Outcome of a bulk document operation for a single document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkDocumentResult {
+    pub document_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// What `revectorize_document` had to re-embed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentRevectorization {
+    pub document_id: String,
+    pub embedded_chunks: i64, // New, changed, or embedded with another model
+    pub unchanged_chunks: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DocumentChunk {
@@ -40,8 +78,18 @@ pub struct DocumentChunk {
     pub chunk_index: i32,
     pub chunk_type: String, // "paragraph", "section", "heading"
     pub content: String,
-    pub start_offset: i32,
+    pub start_offset: i32, // Character index into Document.content
     pub end_offset: i32,
+    pub heading_path: Option<String>, // e.g. "Introduction > Setup > Requirements"
+}
+
+/// Options controlling how a document is split into chunks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkConfig {
+    /// Emit an extra "heading" chunk per markdown heading, at the cost of more embeddings
+    #[serde(default)]
+    pub include_heading_chunks: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +104,7 @@ pub struct DocumentMetadata {
     pub author: Option<String>,
     pub creation_date: Option<String>,
     pub headings: Vec<HeadingInfo>,
+    pub source_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,4 +124,6 @@ pub struct DocumentVectorizationProgress {
     pub processed_chunks: i64,
     pub status: String, // "pending", "loading_model", "processing", "completed", "cancelled", "error"
     pub error: Option<String>,
+    pub progress_percent: f64,
+    pub eta_seconds: Option<f64>,
 }
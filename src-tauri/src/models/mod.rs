@@ -2,8 +2,12 @@ mod project;
 mod database;
 mod document;
 mod ollama;
+mod health;
+mod settings;
 
 pub use project::*;
 pub use database::*;
 pub use document::*;
 pub use ollama::*;
+pub use health::*;
+pub use settings::*;
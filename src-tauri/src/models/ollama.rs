@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::QueryResult;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OllamaStatus {
@@ -14,6 +16,93 @@ pub struct OllamaModel {
     pub size: u64,
     pub digest: String,
     pub modified_at: String,
+    pub is_embedding: bool, // Best guess from the name and family, embedding-only models can't chat
+}
+
+/// Output format the chat system prompt asks the model for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseMode {
+    #[default]
+    Duckbake, // ```duckbake JSON blocks the UI executes and visualizes
+    RawSql,   // Plain ```sql blocks
+    Plain,    // No format instructions, general Q&A
+}
+
+/// Sampling options for a one-shot completion, passed through to Ollama as-is
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenerateOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>, // Cap on generated tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+}
+
+/// How a chat query block's result is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VizType {
+    #[default]
+    Table,
+    Bar,
+    Line,
+    Pie,
+}
+
+/// A ```duckbake query block parsed out of an assistant response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VizSpec {
+    pub sql: String,
+    #[serde(default)]
+    pub viz: VizType,
+    #[serde(default)]
+    pub x_key: Option<String>,
+    #[serde(default)]
+    pub y_key: Option<String>,
+    #[serde(default)]
+    pub start: usize, // Byte range of the whole fenced block in the response
+    #[serde(default)]
+    pub end: usize,
+}
+
+impl VizSpec {
+    /// Why the spec can't be rendered, if it can't: a query is always needed, and bar
+    /// and line charts need both axes
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.sql.trim().is_empty() {
+            return Err("no SQL".into());
+        }
+        let has_key = |key: &Option<String>| key.as_deref().is_some_and(|k| !k.trim().is_empty());
+        if matches!(self.viz, VizType::Bar | VizType::Line)
+            && !(has_key(&self.x_key) && has_key(&self.y_key))
+        {
+            let chart = if self.viz == VizType::Bar { "bar" } else { "line" };
+            return Err(format!("{} chart without xKey and yKey", chart));
+        }
+        Ok(())
+    }
+}
+
+/// Chart recommended for a query result by `suggest_visualization`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VizSuggestion {
+    pub viz: VizType,
+    pub x_key: Option<String>,
+    pub y_key: Option<String>,
+    pub confidence: f64, // 0.0-1.0
+}
+
+/// Outcome of running a chat block's SQL, sent as a `chat-block-result` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuckbakeBlockResult {
+    pub block: VizSpec,
+    pub result: Option<QueryResult>,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +123,8 @@ pub struct Conversation {
     pub title: String,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub model: Option<String>, // Project chat model when the conversation was started
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +135,8 @@ pub struct ConversationWithMessages {
     pub title: String,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub model: Option<String>,
     pub messages: Vec<ChatMessage>,
 }
 
@@ -75,6 +168,16 @@ pub struct OllamaModelInfo {
     pub size: u64,
     pub digest: String,
     pub modified_at: String,
+    #[serde(default)]
+    pub details: Option<OllamaModelDetails>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OllamaModelDetails {
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub families: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,5 +1,12 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
+use crate::services::ImportMode;
+
+/// Embedding model used when a project hasn't picked one
+pub const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Project {
@@ -9,6 +16,94 @@ pub struct Project {
     pub created_at: String,
     pub updated_at: String,
     pub database_file: String,
+    #[serde(default)]
+    pub duckdb_settings: DuckDbSettings,
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+    #[serde(default)]
+    pub embedding_dimension: Option<usize>, // Vector length the project's embeddings were made with
+    #[serde(default)]
+    pub vision_model: Option<String>, // Multimodal model that describes image documents
+    #[serde(default)]
+    pub chat_model: Option<String>, // Ollama model the project's chat uses by default
+    #[serde(default)]
+    pub import_defaults: ImportDefaults,
+    #[serde(default)]
+    pub deleted_at: Option<String>, // Set while the project is in the trash
+}
+
+impl Project {
+    /// The project's chosen embedding model, or the default
+    pub fn embedding_model_name(&self) -> &str {
+        self.embedding_model.as_deref().unwrap_or(DEFAULT_EMBEDDING_MODEL)
+    }
+}
+
+/// A long-running or destructive operation currently holding a project's lock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveJob {
+    pub operation: String, // e.g. "vectorizing orders"
+    pub started_at: String,
+}
+
+/// Import settings used when the caller doesn't give its own
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDefaults {
+    pub mode: Option<ImportMode>,
+    pub table_name_template: Option<String>, // e.g. "raw_{filename}_{date}"
+}
+
+impl ImportDefaults {
+    /// Table name for an imported file: the template with `{filename}` replaced by the
+    /// file's name without extension and `{date}` by today's date (YYYYMMDD), or just
+    /// the file's name when there is no template
+    pub fn table_name_for(&self, file_path: &str) -> String {
+        let stem: String = Path::new(file_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        // Collapse and trim the underscores left by spaces and punctuation
+        let filename = stem
+            .split('_')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("_");
+        let filename = if filename.is_empty() { "import".to_string() } else { filename };
+
+        match self.table_name_template.as_deref() {
+            Some(template) => template
+                .replace("{filename}", &filename)
+                .replace("{date}", &chrono::Local::now().format("%Y%m%d").to_string()),
+            None => filename,
+        }
+    }
+}
+
+/// Where an import goes once the project's defaults are filled in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportTarget {
+    pub table_name: String,
+    pub mode: ImportMode,
+}
+
+/// DuckDB tuning applied whenever a project's connection is opened
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuckDbSettings {
+    pub threads: Option<i64>,
+    pub memory_limit: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +139,30 @@ impl Default for ProjectsFile {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalHit {
+    pub project_id: String,
+    pub project_name: String,
+    pub kind: String, // "table", "document", "saved_query", "conversation"
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalSearchError {
+    pub project_id: String,
+    pub project_name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalSearchResult {
+    pub hits: Vec<GlobalHit>,
+    pub errors: Vec<GlobalSearchError>, // Projects that were skipped or cut short
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectStats {
@@ -54,4 +173,5 @@ pub struct ProjectStats {
     pub saved_query_count: u32,
     pub document_count: u32,
     pub storage_size: u64,
+    pub error: Option<String>, // Why stats are missing or partial for this project
 }
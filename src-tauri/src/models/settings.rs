@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::{ImportMode, ImportOptions};
+
+/// App-wide preferences persisted in settings.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    #[serde(default)]
+    pub recent_imports: Vec<RecentImport>,
+    #[serde(default)]
+    pub trash_retention_days: Option<u32>, // Days before trashed projects are purged on startup
+}
+
+/// Settings needed before the data directory is known, kept in the OS config dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapConfig {
+    pub data_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirInfo {
+    pub data_dir: String,
+    pub default_data_dir: String,
+    pub source: String, // "env", "config", "default"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentImport {
+    pub id: String,
+    pub project_id: String,
+    pub file_path: String,
+    pub table_name: String,
+    pub mode: ImportMode,
+    #[serde(default)]
+    pub options: ImportOptions,
+    pub imported_at: String,
+}
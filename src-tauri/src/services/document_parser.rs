@@ -5,7 +5,11 @@ use std::path::Path;
 use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
 
 use crate::error::{AppError, Result};
-use crate::models::{DocumentChunk, DocumentMetadata, HeadingInfo};
+use crate::models::{ChunkConfig, DocumentChunk, DocumentMetadata, HeadingInfo};
+
+/// Hard ceiling on chunk length in bytes; longer text is split before embedding
+/// so the model doesn't silently truncate it
+const MAX_CHUNK_SIZE: usize = 1000;
 
 pub struct DocumentParser;
 
@@ -24,8 +28,9 @@ impl DocumentParser {
             "md" | "markdown" => Ok("md".into()),
             "docx" => Ok("docx".into()),
             "pdf" => Ok("pdf".into()),
+            "png" | "jpg" | "jpeg" => Ok("image".into()),
             _ => Err(AppError::Custom(format!(
-                "Unsupported document type: {}. Supported types: txt, md, docx, pdf",
+                "Unsupported document type: {}. Supported types: txt, md, docx, pdf, png, jpg",
                 extension
             ))),
         }
@@ -39,9 +44,17 @@ impl DocumentParser {
             "markdown".into(),
             "docx".into(),
             "pdf".into(),
+            "png".into(),
+            "jpg".into(),
+            "jpeg".into(),
         ]
     }
 
+    /// Whether a file is an image, whose content has to come from a vision model
+    pub fn is_image(file_path: &str) -> bool {
+        Self::detect_file_type(file_path).is_ok_and(|t| t == "image")
+    }
+
     /// Parse document and extract content and metadata
     pub fn parse_document(file_path: &str) -> Result<(String, DocumentMetadata)> {
         let file_type = Self::detect_file_type(file_path)?;
@@ -59,6 +72,9 @@ impl DocumentParser {
             "md" => Self::parse_markdown(file_path, filename, file_size),
             "docx" => Self::parse_docx(file_path, filename, file_size),
             "pdf" => Self::parse_pdf(file_path, filename, file_size),
+            "image" => Err(AppError::Custom(
+                "Image documents are described by a vision model rather than parsed".into(),
+            )),
             _ => Err(AppError::Custom(format!(
                 "Unsupported file type: {}",
                 file_type
@@ -66,15 +82,66 @@ impl DocumentParser {
         }
     }
 
+    /// Build an image document from the description a vision model wrote for it.
+    /// The description is chunked and embedded like any text; the image itself
+    /// stays on disk and is referenced by its path.
+    pub fn image_document(
+        file_path: &str,
+        description: String,
+    ) -> Result<(String, DocumentMetadata)> {
+        let file_size = fs::metadata(file_path).map_err(|e| {
+            AppError::Custom(format!("Cannot access file '{}': {}", file_path, e))
+        })?.len() as i64;
+        let filename = Path::new(file_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let source_path = fs::canonicalize(file_path)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| file_path.to_string());
+
+        let (content, mut metadata) = Self::txt_metadata(description, filename, file_size);
+        metadata.file_type = "image".into();
+        metadata.source_path = Some(source_path);
+        Ok((content, metadata))
+    }
+
+    /// Parse in-memory text (e.g. pasted notes) as a txt or md document
+    pub fn parse_text_content(
+        title: &str,
+        content: String,
+        file_type: &str,
+    ) -> Result<(String, DocumentMetadata)> {
+        let file_size = content.len() as i64;
+        let (content, mut metadata) = match file_type {
+            "txt" => Self::txt_metadata(content, title.to_string(), file_size),
+            "md" | "markdown" => Self::markdown_metadata(content, title.to_string(), file_size),
+            _ => {
+                return Err(AppError::Custom(format!(
+                    "Unsupported text document type: {}. Supported types: txt, md",
+                    file_type
+                )))
+            }
+        };
+        metadata.title = Some(title.to_string());
+        Ok((content, metadata))
+    }
+
     /// Parse plain text file
     fn parse_txt(path: &str, filename: String, file_size: i64) -> Result<(String, DocumentMetadata)> {
         let content = fs::read_to_string(path).map_err(|e| {
             AppError::Custom(format!("Failed to read '{}': {}. Ensure the file is UTF-8 encoded.", filename, e))
         })?;
 
+        Ok(Self::txt_metadata(content, filename, file_size))
+    }
+
+    /// Build metadata for plain text content
+    fn txt_metadata(content: String, filename: String, file_size: i64) -> (String, DocumentMetadata) {
         let word_count = content.split_whitespace().count() as i32;
 
-        Ok((
+        (
             content,
             DocumentMetadata {
                 filename,
@@ -86,8 +153,9 @@ impl DocumentParser {
                 author: None,
                 creation_date: None,
                 headings: vec![],
+                source_path: None,
             },
-        ))
+        )
     }
 
     /// Parse markdown file with heading extraction
@@ -100,6 +168,15 @@ impl DocumentParser {
             AppError::Custom(format!("Failed to read '{}': {}. Ensure the file is UTF-8 encoded.", filename, e))
         })?;
 
+        Ok(Self::markdown_metadata(content, filename, file_size))
+    }
+
+    /// Build metadata for markdown content, extracting headings
+    fn markdown_metadata(
+        content: String,
+        filename: String,
+        file_size: i64,
+    ) -> (String, DocumentMetadata) {
         let word_count = content.split_whitespace().count() as i32;
 
         // Extract headings using pulldown-cmark
@@ -148,7 +225,7 @@ impl DocumentParser {
             .find(|h| h.level == 1)
             .map(|h| h.text.clone());
 
-        Ok((
+        (
             content,
             DocumentMetadata {
                 filename,
@@ -160,8 +237,9 @@ impl DocumentParser {
                 author: None,
                 creation_date: None,
                 headings,
+                source_path: None,
             },
-        ))
+        )
     }
 
     /// Parse DOCX file
@@ -197,6 +275,7 @@ impl DocumentParser {
                 author,
                 creation_date,
                 headings: vec![],
+                source_path: None,
             },
         ))
     }
@@ -351,6 +430,7 @@ impl DocumentParser {
                 author,
                 creation_date,
                 headings: vec![],
+                source_path: None,
             },
         ))
     }
@@ -403,176 +483,526 @@ impl DocumentParser {
     }
 
     /// Split document into semantic chunks for vectorization
-    pub fn chunk_document(document_id: &str, content: &str, file_type: &str) -> Vec<DocumentChunk> {
-        match file_type {
-            "md" => Self::chunk_markdown(document_id, content),
+    pub fn chunk_document(
+        document_id: &str,
+        content: &str,
+        file_type: &str,
+        config: &ChunkConfig,
+    ) -> Vec<DocumentChunk> {
+        let mut chunks = match file_type {
+            "md" => Self::chunk_markdown(document_id, content, config),
             _ => Self::chunk_by_paragraphs(document_id, content),
+        };
+
+        // Ids are assigned last since small trailing chunks get merged into their neighbour
+        for chunk in &mut chunks {
+            chunk.id = Self::chunk_id(document_id, chunk.chunk_index, &chunk.content);
+        }
+        chunks
+    }
+
+    /// Deterministic chunk id, so re-chunking unchanged content yields the same ids
+    /// Uses FNV-1a rather than std's hasher, whose output may change between releases
+    pub fn chunk_id(document_id: &str, chunk_index: i32, content: &str) -> String {
+        let hash = content.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        format!("{}-{}-{:016x}", document_id, chunk_index, hash)
+    }
+
+    /// Build a chunk whose offsets are character indices into the original content
+    /// `start`/`end` are byte positions, converted so the UI can slice by character
+    fn make_chunk(
+        document_id: &str,
+        content: &str,
+        chunk_index: i32,
+        chunk_type: &str,
+        text: String,
+        start: usize,
+        end: usize,
+        heading_path: Option<String>,
+    ) -> DocumentChunk {
+        let start_offset = content[..start].chars().count();
+        let end_offset = start_offset + content[start..end].chars().count();
+
+        DocumentChunk {
+            id: String::new(), // Filled in by chunk_document once content is final
+            document_id: document_id.to_string(),
+            chunk_index,
+            chunk_type: chunk_type.to_string(),
+            content: text,
+            start_offset: start_offset as i32,
+            end_offset: end_offset as i32,
+            heading_path,
         }
     }
 
+    /// Narrow a byte span so it excludes leading and trailing whitespace
+    fn trim_span(content: &str, start: usize, end: usize) -> (usize, usize) {
+        let slice = &content[start..end];
+        let leading = slice.len() - slice.trim_start().len();
+        let trimmed_len = slice.trim().len();
+        (start + leading, start + leading + trimmed_len)
+    }
+
+    /// Split an oversized byte span into trimmed spans of at most `MAX_CHUNK_SIZE`,
+    /// cutting at a paragraph break, then a sentence end, then whitespace, and only
+    /// mid-word when a stretch has no whitespace at all
+    fn split_span(content: &str, start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut piece_start = start;
+
+        while end - piece_start > MAX_CHUNK_SIZE {
+            let mut window_end = piece_start + MAX_CHUNK_SIZE;
+            while !content.is_char_boundary(window_end) {
+                window_end -= 1;
+            }
+            let window = &content[piece_start..window_end];
+
+            let cut = window
+                .rfind("\n\n")
+                .map(|i| i + 2)
+                .or_else(|| {
+                    window
+                        .char_indices()
+                        .filter(|(i, c)| {
+                            matches!(c, '.' | '!' | '?')
+                                && window[i + c.len_utf8()..]
+                                    .chars()
+                                    .next()
+                                    .is_some_and(char::is_whitespace)
+                        })
+                        .map(|(i, c)| i + c.len_utf8())
+                        .last()
+                })
+                .or_else(|| {
+                    window
+                        .char_indices()
+                        .rev()
+                        .find(|(_, c)| c.is_whitespace())
+                        .map(|(i, c)| i + c.len_utf8())
+                })
+                .filter(|cut| *cut > 0)
+                .unwrap_or(window.len());
+
+            let (s, e) = Self::trim_span(content, piece_start, piece_start + cut);
+            if s < e {
+                spans.push((s, e));
+            }
+            piece_start += cut;
+        }
+
+        let (s, e) = Self::trim_span(content, piece_start, end);
+        if s < e {
+            spans.push((s, e));
+        }
+        spans
+    }
+
+    /// Chunks for one markdown section, split into several when it's over the ceiling
+    fn section_chunks(
+        document_id: &str,
+        content: &str,
+        first_index: i32,
+        text: String,
+        start: usize,
+        end: usize,
+        heading_path: Option<String>,
+    ) -> Vec<DocumentChunk> {
+        if text.len() <= MAX_CHUNK_SIZE {
+            return vec![Self::make_chunk(
+                document_id,
+                content,
+                first_index,
+                "section",
+                text,
+                start,
+                end,
+                heading_path,
+            )];
+        }
+
+        Self::split_span(content, start, end)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (s, e))| {
+                Self::make_chunk(
+                    document_id,
+                    content,
+                    first_index + i as i32,
+                    "section",
+                    content[s..e].replace('\r', ""),
+                    s,
+                    e,
+                    heading_path.clone(),
+                )
+            })
+            .collect()
+    }
+
     /// Chunk content by paragraphs with size limits
     fn chunk_by_paragraphs(document_id: &str, content: &str) -> Vec<DocumentChunk> {
         let mut chunks = Vec::new();
-        let paragraphs: Vec<&str> = content.split("\n\n").collect();
 
         let mut current_chunk = String::new();
         let mut chunk_index = 0;
-        let mut char_offset = 0i32;
-        let mut chunk_start = 0i32;
+        // Byte span in `content` covered by the chunk being built
+        let mut chunk_start = 0usize;
+        let mut chunk_end = 0usize;
+        let mut position = 0usize;
 
-        const MAX_CHUNK_SIZE: usize = 1000;
         const MIN_CHUNK_SIZE: usize = 100;
 
-        for para in paragraphs {
-            let para = para.trim();
+        for raw_para in content.split("\n\n") {
+            let para_start = position;
+            position += raw_para.len() + 2; // Account for \n\n
+
+            let para = raw_para.trim();
             if para.is_empty() {
-                char_offset += 2; // Account for \n\n
                 continue;
             }
+            let (para_start, para_end) =
+                Self::trim_span(content, para_start, para_start + raw_para.len());
 
             // If adding this paragraph would exceed max size, save current chunk
             if !current_chunk.is_empty()
                 && current_chunk.len() + para.len() + 2 > MAX_CHUNK_SIZE
             {
-                chunks.push(DocumentChunk {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    document_id: document_id.to_string(),
+                chunks.push(Self::make_chunk(
+                    document_id,
+                    content,
                     chunk_index,
-                    chunk_type: "paragraph".to_string(),
-                    content: current_chunk.clone(),
-                    start_offset: chunk_start,
-                    end_offset: char_offset,
-                });
+                    "paragraph",
+                    current_chunk.clone(),
+                    chunk_start,
+                    chunk_end,
+                    None,
+                ));
                 chunk_index += 1;
-                chunk_start = char_offset;
                 current_chunk.clear();
             }
 
+            // A paragraph over the ceiling on its own is split by sentence
+            if para.len() > MAX_CHUNK_SIZE {
+                if !current_chunk.is_empty() {
+                    chunks.push(Self::make_chunk(
+                        document_id,
+                        content,
+                        chunk_index,
+                        "paragraph",
+                        current_chunk.clone(),
+                        chunk_start,
+                        chunk_end,
+                        None,
+                    ));
+                    chunk_index += 1;
+                    current_chunk.clear();
+                }
+                for (start, end) in Self::split_span(content, para_start, para_end) {
+                    chunks.push(Self::make_chunk(
+                        document_id,
+                        content,
+                        chunk_index,
+                        "paragraph",
+                        content[start..end].to_string(),
+                        start,
+                        end,
+                        None,
+                    ));
+                    chunk_index += 1;
+                }
+                continue;
+            }
+
             // Add paragraph to current chunk
-            if !current_chunk.is_empty() {
+            if current_chunk.is_empty() {
+                chunk_start = para_start;
+            } else {
                 current_chunk.push_str("\n\n");
             }
             current_chunk.push_str(para);
-            char_offset += para.len() as i32 + 2;
+            chunk_end = para_end;
         }
 
-        // Save final chunk if it meets minimum size
-        if !current_chunk.is_empty() && current_chunk.len() >= MIN_CHUNK_SIZE {
-            chunks.push(DocumentChunk {
-                id: uuid::Uuid::new_v4().to_string(),
-                document_id: document_id.to_string(),
-                chunk_index,
-                chunk_type: "paragraph".to_string(),
-                content: current_chunk,
-                start_offset: chunk_start,
-                end_offset: char_offset,
-            });
-        } else if !current_chunk.is_empty() && !chunks.is_empty() {
-            // Append to previous chunk if too small
-            if let Some(last) = chunks.last_mut() {
-                last.content.push_str("\n\n");
-                last.content.push_str(&current_chunk);
-                last.end_offset = char_offset;
+        // Save the final chunk. One under the minimum size is appended to the previous
+        // chunk instead, unless that would take the previous chunk over the ceiling
+        if !current_chunk.is_empty() {
+            let merge_into_last = current_chunk.len() < MIN_CHUNK_SIZE
+                && chunks.last().is_some_and(|last: &DocumentChunk| {
+                    last.content.len() + 2 + current_chunk.len() <= MAX_CHUNK_SIZE
+                });
+            if merge_into_last {
+                if let Some(last) = chunks.last_mut() {
+                    last.content.push_str("\n\n");
+                    last.content.push_str(&current_chunk);
+                    last.end_offset = content[..chunk_end].chars().count() as i32;
+                }
+            } else {
+                chunks.push(Self::make_chunk(
+                    document_id,
+                    content,
+                    chunk_index,
+                    "paragraph",
+                    current_chunk,
+                    chunk_start,
+                    chunk_end,
+                    None,
+                ));
             }
-        } else if !current_chunk.is_empty() {
-            // First and only chunk, keep it regardless of size
-            chunks.push(DocumentChunk {
-                id: uuid::Uuid::new_v4().to_string(),
-                document_id: document_id.to_string(),
-                chunk_index,
-                chunk_type: "paragraph".to_string(),
-                content: current_chunk,
-                start_offset: chunk_start,
-                end_offset: char_offset,
-            });
         }
 
         chunks
     }
 
+    /// Build a short "heading" chunk for a section that starts with a heading line
+    /// Holds the heading text plus the first sentence of the section body
+    fn heading_chunk(
+        document_id: &str,
+        content: &str,
+        chunk_index: i32,
+        section: &str,
+        section_start: usize,
+        heading_path: Option<String>,
+    ) -> Option<DocumentChunk> {
+        let (heading_line, body) = section.split_once('\n').unwrap_or((section, ""));
+        if !heading_line.starts_with('#') {
+            return None;
+        }
+
+        let heading = heading_line.trim_start_matches('#').trim();
+        if heading.is_empty() {
+            return None;
+        }
+
+        let body = body.trim_start();
+        let sentence_end = body
+            .char_indices()
+            .find(|(i, c)| {
+                matches!(c, '.' | '!' | '?' | '\n')
+                    && body[i + c.len_utf8()..].chars().next().is_none_or(char::is_whitespace)
+            })
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(body.len());
+        let first_sentence = body[..sentence_end].trim();
+
+        let text = if first_sentence.is_empty() || first_sentence.starts_with('#') {
+            heading.to_string()
+        } else {
+            format!("{}\n{}", heading, first_sentence)
+        };
+
+        Some(Self::make_chunk(
+            document_id,
+            content,
+            chunk_index,
+            "heading",
+            text,
+            section_start,
+            section_start + heading_line.len(),
+            heading_path,
+        ))
+    }
+
     /// Chunk markdown by sections (headings)
-    fn chunk_markdown(document_id: &str, content: &str) -> Vec<DocumentChunk> {
+    fn chunk_markdown(
+        document_id: &str,
+        content: &str,
+        config: &ChunkConfig,
+    ) -> Vec<DocumentChunk> {
         let mut chunks = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
 
         let mut current_chunk = String::new();
         let mut chunk_index = 0;
-        let mut chunk_start = 0i32;
-        let mut char_offset = 0i32;
+        // Byte span in `content` covered by the chunk being built
+        let mut chunk_start = 0usize;
+        let mut chunk_end = 0usize;
+        let mut position = 0usize;
+
+        // Stack of (level, text) for the headings enclosing the current line
+        let mut heading_stack: Vec<(usize, String)> = Vec::new();
+        let mut chunk_heading_path: Option<String> = None;
 
-        const MAX_CHUNK_SIZE: usize = 1000;
+        for raw_line in content.split_inclusive('\n') {
+            let line_start = position;
+            position += raw_line.len();
+            let line = raw_line.trim_end_matches(['\n', '\r']);
+            let line_end = line_start + line.len();
 
-        for line in lines {
             let is_heading = line.starts_with('#');
 
-            // If this is a heading and we have content, save current chunk
-            if is_heading && !current_chunk.trim().is_empty() {
-                chunks.push(DocumentChunk {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    document_id: document_id.to_string(),
+            // A heading starts a new section; long sections are split at line boundaries,
+            // except that a heading is never left alone in a chunk without its body
+            let heading_only = current_chunk.trim_start().starts_with('#')
+                && !current_chunk.trim().contains('\n');
+            let starts_new_chunk = !current_chunk.trim().is_empty()
+                && (is_heading
+                    || (!heading_only && current_chunk.len() + line.len() + 1 > MAX_CHUNK_SIZE));
+
+            if starts_new_chunk {
+                let (start, end) = Self::trim_span(content, chunk_start, chunk_end);
+                if config.include_heading_chunks {
+                    if let Some(chunk) = Self::heading_chunk(
+                        document_id,
+                        content,
+                        chunk_index,
+                        current_chunk.trim(),
+                        start,
+                        chunk_heading_path.clone(),
+                    ) {
+                        chunks.push(chunk);
+                        chunk_index += 1;
+                    }
+                }
+                let sections = Self::section_chunks(
+                    document_id,
+                    content,
                     chunk_index,
-                    chunk_type: "section".to_string(),
-                    content: current_chunk.trim().to_string(),
-                    start_offset: chunk_start,
-                    end_offset: char_offset,
-                });
-                chunk_index += 1;
-                chunk_start = char_offset;
+                    current_chunk.trim().to_string(),
+                    start,
+                    end,
+                    chunk_heading_path.clone(),
+                );
+                chunk_index += sections.len() as i32;
+                chunks.extend(sections);
                 current_chunk.clear();
             }
 
-            // Check if adding this line would exceed max size
-            if !current_chunk.is_empty()
-                && current_chunk.len() + line.len() + 1 > MAX_CHUNK_SIZE
-                && !is_heading
-            {
-                chunks.push(DocumentChunk {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    document_id: document_id.to_string(),
-                    chunk_index,
-                    chunk_type: "section".to_string(),
-                    content: current_chunk.trim().to_string(),
-                    start_offset: chunk_start,
-                    end_offset: char_offset,
-                });
-                chunk_index += 1;
-                chunk_start = char_offset;
-                current_chunk.clear();
+            if is_heading {
+                let level = line.chars().take_while(|c| *c == '#').count();
+                let text = line[level..].trim().to_string();
+                while heading_stack.last().is_some_and(|(l, _)| *l >= level) {
+                    heading_stack.pop();
+                }
+                heading_stack.push((level, text));
             }
 
-            if !current_chunk.is_empty() {
+            if current_chunk.is_empty() {
+                chunk_start = line_start;
+                chunk_heading_path = if heading_stack.is_empty() {
+                    None
+                } else {
+                    Some(
+                        heading_stack
+                            .iter()
+                            .map(|(_, text)| text.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" > "),
+                    )
+                };
+            } else {
                 current_chunk.push('\n');
             }
             current_chunk.push_str(line);
-            char_offset += line.len() as i32 + 1;
+            chunk_end = line_end;
         }
 
         // Save final chunk
         if !current_chunk.trim().is_empty() {
-            chunks.push(DocumentChunk {
-                id: uuid::Uuid::new_v4().to_string(),
-                document_id: document_id.to_string(),
+            let (start, end) = Self::trim_span(content, chunk_start, chunk_end);
+            if config.include_heading_chunks {
+                if let Some(chunk) = Self::heading_chunk(
+                    document_id,
+                    content,
+                    chunk_index,
+                    current_chunk.trim(),
+                    start,
+                    chunk_heading_path.clone(),
+                ) {
+                    chunks.push(chunk);
+                    chunk_index += 1;
+                }
+            }
+            chunks.extend(Self::section_chunks(
+                document_id,
+                content,
                 chunk_index,
-                chunk_type: "section".to_string(),
-                content: current_chunk.trim().to_string(),
-                start_offset: chunk_start,
-                end_offset: char_offset,
-            });
+                current_chunk.trim().to_string(),
+                start,
+                end,
+                chunk_heading_path,
+            ));
         }
 
         // If no chunks were created, treat as single chunk
         if chunks.is_empty() && !content.trim().is_empty() {
-            chunks.push(DocumentChunk {
-                id: uuid::Uuid::new_v4().to_string(),
-                document_id: document_id.to_string(),
-                chunk_index: 0,
-                chunk_type: "section".to_string(),
-                content: content.trim().to_string(),
-                start_offset: 0,
-                end_offset: content.len() as i32,
-            });
+            let (start, end) = Self::trim_span(content, 0, content.len());
+            chunks.extend(Self::section_chunks(
+                document_id,
+                content,
+                0,
+                content.trim().to_string(),
+                start,
+                end,
+                None,
+            ));
         }
 
         chunks
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Text of `content` between two character offsets
+    fn slice_chars(content: &str, start: i32, end: i32) -> String {
+        content
+            .chars()
+            .skip(start as usize)
+            .take((end - start) as usize)
+            .collect()
+    }
+
+    #[test]
+    fn paragraph_chunk_offsets_slice_multibyte_content() {
+        let content = (0..12)
+            .map(|i| format!("Paragraphe {} — café, naïve, 日本語 and 🦆 text. ", i).repeat(4))
+            .map(|p| p.trim().to_string())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let chunks =
+            DocumentParser::chunk_document("doc", &content, "txt", &ChunkConfig::default());
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert_eq!(slice_chars(&content, chunk.start_offset, chunk.end_offset), chunk.content);
+        }
+    }
+
+    #[test]
+    fn long_markdown_section_is_split_under_the_limit() {
+        let body = (0..200)
+            .map(|i| format!("Sentence {} of a long section. ", i))
+            .collect::<String>();
+        let body = &body[..body.char_indices().nth(5000).unwrap().0];
+        let content = format!("# Overview\n{}\n", body);
+
+        let chunks = DocumentParser::chunk_document("doc", &content, "md", &ChunkConfig::default());
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.content.len() <= MAX_CHUNK_SIZE);
+            assert_eq!(slice_chars(&content, chunk.start_offset, chunk.end_offset), chunk.content);
+            assert_eq!(chunk.heading_path.as_deref(), Some("Overview"));
+        }
+    }
+
+    #[test]
+    fn short_tail_after_a_full_chunk_is_kept_separate() {
+        let full = "word ".repeat(198).trim_end().to_string();
+        let tail = "A short closing line.";
+        let content = format!("{}\n\n{}", full, tail);
+
+        let chunks =
+            DocumentParser::chunk_document("doc", &content, "txt", &ChunkConfig::default());
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].content, tail);
+        for chunk in &chunks {
+            assert!(chunk.content.len() <= MAX_CHUNK_SIZE);
+            assert_eq!(slice_chars(&content, chunk.start_offset, chunk.end_offset), chunk.content);
+        }
+    }
+}
@@ -1,90 +1,725 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use duckdb::Connection;
 use parking_lot::Mutex;
 use serde_json::{json, Value};
+use tracing::{debug, info, warn};
 
 use crate::error::{AppError, Result};
 use crate::models::{
-    ColumnInfo, Document, DocumentChunk, DocumentInfo, QueryResult, TableInfo, TableSchema,
-    VectorizationStatus,
+    AggregateFn, AggregateResult, AttachedDatabase, BulkDocumentResult, ChatQueryResult,
+    ClearTarget, ClearedData, ColumnInfo, ColumnTypeChange, ConnectionCapabilities, DedupeResult,
+    DistinctValue, DistinctValues, Document, DocumentChunk, DocumentChunkPage, DocumentInfo,
+    DocumentPage, DuckDbSettings, ExportFormat, ExportResult, GeneratedColumn, NameSuggestion,
+    OpenConnection, QueryError, QueryResult, QueryStreamEvent, QueryValidation, RowCount, RowDiff,
+    SchemaChange, SeekPage, SimilarityMetric, StorageBreakdown, StorageCategory, TableContext,
+    TableDiff, TableInfo, TableNote, TableSchema, TableSummary, TimeBucket, VectorizationStatus,
+    VizSpec, VizSuggestion, VizType,
 };
+use crate::services::{ImportMode, ImportResult};
+
+/// Placeholder for an `EmbeddingParam`, casting its bound text back to a list
+const EMBEDDING_PARAM: &str = "?::FLOAT[]";
+
+/// An embedding bound as a query parameter. duckdb-rs can't bind list values, so the
+/// vector goes in as its text form (`[0.1,0.2,...]`) and `EMBEDDING_PARAM` casts it;
+/// every query that stores or compares embeddings binds them through this.
+struct EmbeddingParam<'a>(&'a [f32]);
+
+impl duckdb::ToSql for EmbeddingParam<'_> {
+    fn to_sql(&self) -> duckdb::Result<duckdb::types::ToSqlOutput<'_>> {
+        let values: Vec<String> = self.0.iter().map(|f| f.to_string()).collect();
+        Ok(duckdb::types::ToSqlOutput::from(format!("[{}]", values.join(","))))
+    }
+}
+
+/// Quote an identifier (table, column, alias) for interpolation into SQL,
+/// doubling any embedded double quotes
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Row embeddings; a row can carry vectors from several models side by side
+const EMBEDDINGS_TABLE_DDL: &str = r#"
+    CREATE TABLE IF NOT EXISTS _duckbake_embeddings (
+        table_name VARCHAR NOT NULL,
+        source_column VARCHAR NOT NULL,
+        row_id INTEGER NOT NULL,
+        content TEXT NOT NULL,
+        embedding FLOAT[] NOT NULL,
+        embedding_model VARCHAR NOT NULL,
+        created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (table_name, source_column, embedding_model, row_id)
+    );
+    CREATE INDEX IF NOT EXISTS idx_embeddings_table
+        ON _duckbake_embeddings(table_name, source_column);
+"#;
+
+/// One config row per table + column key + embedding model
+const VECTOR_CONFIG_DDL: &str = r#"
+    CREATE TABLE IF NOT EXISTS _duckbake_vector_config (
+        table_name VARCHAR NOT NULL,
+        column_name VARCHAR NOT NULL,
+        embedding_model VARCHAR NOT NULL,
+        vector_column_name VARCHAR NOT NULL,
+        last_updated TIMESTAMP,
+        last_row_id BIGINT,
+        similarity_metric VARCHAR,
+        normalized BOOLEAN,
+        PRIMARY KEY (table_name, column_name, embedding_model)
+    );
+"#;
+
+/// Generated table summaries, so they aren't regenerated on every visit
+const TABLE_NOTES_DDL: &str = r#"
+    CREATE TABLE IF NOT EXISTS _duckbake_table_notes (
+        table_name VARCHAR PRIMARY KEY,
+        summary TEXT NOT NULL,
+        model VARCHAR NOT NULL,
+        updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    );
+"#;
+
+/// Computed columns that DuckDB couldn't add to their table, kept so the table's
+/// `<table>_computed` view can be rebuilt with all of them
+const COMPUTED_COLUMNS_DDL: &str = r#"
+    CREATE TABLE IF NOT EXISTS _duckbake_computed_columns (
+        table_name VARCHAR NOT NULL,
+        column_name VARCHAR NOT NULL,
+        expression VARCHAR NOT NULL,
+        created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (table_name, column_name)
+    );
+"#;
+
+/// Prefix reserved for DuckBake's internal tables
+pub const RESERVED_TABLE_PREFIX: &str = "_duckbake_";
+
+/// Upper bound on how many distinct values are returned for a single column
+const MAX_DISTINCT_VALUES: u32 = 1000;
+
+/// Upper bound on differing rows returned per direction by `diff_tables`
+const MAX_DIFF_ROWS: u32 = 1000;
+
+/// Longest search query that will be embedded, in characters
+const MAX_SEARCH_QUERY_CHARS: usize = 4000;
+
+/// Names less similar than this to a missing one aren't worth suggesting
+const MIN_SUGGESTION_CONFIDENCE: f64 = 0.5;
+
+/// How similar an unambiguous suggestion must be for chat queries to be re-run with it
+const AUTO_CORRECT_CONFIDENCE: f64 = 0.75;
+
+/// Most categories a pie chart is suggested for
+const PIE_MAX_SLICES: usize = 7;
+
+/// Bar charts with more categories than this are suggested with low confidence
+const BAR_MAX_CATEGORIES: usize = 50;
+
+/// Most groups `aggregate` returns
+const MAX_AGGREGATE_GROUPS: u32 = 1000;
+
+/// Schema holding read-only views over documents, e.g. `SELECT * FROM docs.documents`
+pub const DOCUMENT_VIEW_SCHEMA: &str = "docs";
+
+/// Views exposing document metadata, full text and chunks to SQL
+/// DuckDB views aren't updatable, so these can't be used to modify documents
+const DOCUMENT_VIEWS_DDL: &str = r#"
+    CREATE SCHEMA IF NOT EXISTS docs;
+
+    CREATE OR REPLACE VIEW docs.documents AS
+    SELECT id, filename, file_type, file_size, page_count, word_count,
+           title, author, creation_date, uploaded_at, is_vectorized
+    FROM _duckbake_documents;
+
+    CREATE OR REPLACE VIEW docs.document_content AS
+    SELECT id, filename, title, content
+    FROM _duckbake_documents;
+
+    CREATE OR REPLACE VIEW docs.document_chunks AS
+    SELECT c.id, c.document_id, d.filename, c.chunk_index, c.chunk_type, c.heading_path,
+           c.content, c.start_offset, c.end_offset, c.embedding IS NOT NULL AS is_vectorized
+    FROM _duckbake_document_chunks c
+    JOIN _duckbake_documents d ON d.id = c.document_id;
+"#;
 
 pub struct DuckDbService {
     connections: Mutex<HashMap<String, Arc<Mutex<Connection>>>>,
+    /// Functions/extensions probed when each connection was opened
+    capabilities: Mutex<HashMap<String, ConnectionCapabilities>>,
+    /// External databases attached to each project's connection
+    attachments: Mutex<HashMap<String, Vec<AttachedDatabase>>>,
+    /// Desired DuckDB settings, reapplied whenever a connection is opened
+    settings: Mutex<HashMap<String, DuckDbSettings>>,
+    /// When each cached connection was last handed out
+    last_used: Mutex<HashMap<String, chrono::DateTime<chrono::Utc>>>,
 }
 
 impl DuckDbService {
     pub fn new() -> Self {
         DuckDbService {
             connections: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(HashMap::new()),
+            attachments: Mutex::new(HashMap::new()),
+            settings: Mutex::new(HashMap::new()),
+            last_used: Mutex::new(HashMap::new()),
         }
     }
 
     pub fn get_connection(&self, project_id: &str, db_path: &PathBuf) -> Result<Arc<Mutex<Connection>>> {
         let mut connections = self.connections.lock();
+        self.last_used
+            .lock()
+            .insert(project_id.to_string(), chrono::Utc::now());
 
         if let Some(conn) = connections.get(project_id) {
             return Ok(conn.clone());
         }
 
         let conn = Connection::open(db_path)?;
+        if let Some(settings) = self.settings.lock().get(project_id) {
+            if let Err(e) = Self::apply_settings(&conn, settings) {
+                warn!(target: "duckdb", "Failed to apply settings for {}: {}", project_id, e);
+            }
+        }
+        let capabilities = Self::probe_capabilities(&conn);
+        self.capabilities
+            .lock()
+            .insert(project_id.to_string(), capabilities);
+
         let conn = Arc::new(Mutex::new(conn));
         connections.insert(project_id.to_string(), conn.clone());
+        info!(target: "duckdb", "Opened connection for project {}", project_id);
 
         Ok(conn)
     }
 
     pub fn close_connection(&self, project_id: &str) {
         let mut connections = self.connections.lock();
-        connections.remove(project_id);
+        if connections.remove(project_id).is_some() {
+            info!(target: "duckdb", "Closed connection for project {}", project_id);
+        }
+        self.capabilities.lock().remove(project_id);
+        self.attachments.lock().remove(project_id);
+        self.last_used.lock().remove(project_id);
+    }
+
+    /// List the projects with a cached connection, most recently used first
+    pub fn list_open_connections(&self) -> Vec<OpenConnection> {
+        let connections = self.connections.lock();
+        let last_used = self.last_used.lock();
+
+        let mut open: Vec<OpenConnection> = connections
+            .iter()
+            .map(|(project_id, conn)| OpenConnection {
+                project_id: project_id.clone(),
+                last_used_at: last_used
+                    .get(project_id)
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default(),
+                locked: conn.is_locked(),
+            })
+            .collect();
+        open.sort_by(|a, b| b.last_used_at.cmp(&a.last_used_at));
+        open
+    }
+
+    /// Checkpoint and drop every open connection, returning how many were closed.
+    /// Safe to call repeatedly: once the cache is empty there is nothing left to do.
+    pub fn close_all_connections(&self) -> usize {
+        let connections: Vec<(String, Arc<Mutex<Connection>>)> =
+            self.connections.lock().drain().collect();
+
+        for (project_id, conn) in &connections {
+            // Don't hang shutdown on a connection stuck in a long-running query
+            match conn.try_lock_for(Duration::from_secs(2)) {
+                Some(conn) => {
+                    if let Err(e) = self.checkpoint(&conn) {
+                        warn!(target: "duckdb", "{} on shutdown: {}", project_id, e);
+                    }
+                }
+                None => warn!(
+                    target: "duckdb",
+                    "Connection for {} is busy; closing without checkpoint",
+                    project_id
+                ),
+            }
+        }
+
+        self.capabilities.lock().clear();
+        self.attachments.lock().clear();
+        self.last_used.lock().clear();
+        connections.len()
+    }
+
+    /// Remember the desired settings for a project so they survive reconnects
+    pub fn register_settings(&self, project_id: &str, settings: DuckDbSettings) {
+        self.settings.lock().insert(project_id.to_string(), settings);
+    }
+
+    /// Run the SET statements for any configured settings
+    pub fn apply_settings(conn: &Connection, settings: &DuckDbSettings) -> Result<()> {
+        if let Some(threads) = settings.threads {
+            if threads < 1 {
+                return Err(AppError::Custom("threads must be at least 1".into()));
+            }
+            conn.execute_batch(&format!("SET threads = {}", threads))?;
+        }
+        if let Some(memory_limit) = &settings.memory_limit {
+            // e.g. "4GB", "512MB", "80%"
+            let valid = !memory_limit.is_empty()
+                && memory_limit
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '%' || c == ' ');
+            if !valid {
+                return Err(AppError::Custom(format!(
+                    "Invalid memory limit '{}'",
+                    memory_limit
+                )));
+            }
+            conn.execute_batch(&format!("SET memory_limit = '{}'", memory_limit))?;
+        }
+        Ok(())
+    }
+
+    /// Read the effective settings back from the connection
+    pub fn get_settings(&self, conn: &Connection) -> Result<DuckDbSettings> {
+        let (threads, memory_limit): (i64, String) = conn.query_row(
+            "SELECT CAST(current_setting('threads') AS BIGINT), CAST(current_setting('memory_limit') AS VARCHAR)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(DuckDbSettings {
+            threads: Some(threads),
+            memory_limit: Some(memory_limit),
+        })
+    }
+
+    /// Flush the write-ahead log into the main database file so the file on disk
+    /// is complete, e.g. before copying it
+    pub fn checkpoint(&self, conn: &Connection) -> Result<()> {
+        conn.execute_batch("CHECKPOINT").map_err(|e| {
+            AppError::Custom(format!(
+                "Failed to checkpoint database (is a transaction still open?): {}",
+                e
+            ))
+        })
+    }
+
+    /// Check which optional functions and extensions this DuckDB build provides
+    pub fn probe_capabilities(conn: &Connection) -> ConnectionCapabilities {
+        let has_function = |name: &str| -> bool {
+            conn.query_row(
+                "SELECT COUNT(*) > 0 FROM duckdb_functions() WHERE function_name = ?",
+                [name],
+                |row| row.get(0),
+            )
+            .unwrap_or(false)
+        };
+
+        // Installed extensions aren't loaded (and their functions aren't listed) until
+        // autoloading kicks in on first use
+        let has_extension = |name: &str| -> bool {
+            conn.query_row(
+                "SELECT COUNT(*) > 0 FROM duckdb_extensions() WHERE extension_name = ? AND (installed OR loaded)",
+                [name],
+                |row| row.get(0),
+            )
+            .unwrap_or(false)
+        };
+
+        ConnectionCapabilities {
+            list_cosine_similarity: has_function("list_cosine_similarity"),
+            array_cosine_similarity: has_function("array_cosine_similarity"),
+            vss_extension: has_extension("vss"),
+            excel_reader: has_function("st_read") || has_extension("spatial"),
+        }
+    }
+
+    /// Get the cached capabilities for a project's open connection
+    pub fn get_capabilities(&self, project_id: &str) -> ConnectionCapabilities {
+        self.capabilities
+            .lock()
+            .get(project_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Return an actionable error if semantic search isn't supported by this build
+    pub fn require_vector_search(&self, project_id: &str) -> Result<()> {
+        if self.get_capabilities(project_id).list_cosine_similarity {
+            Ok(())
+        } else {
+            Err(AppError::Custom(
+                "Semantic search requires DuckDB vss/list functions which aren't available in this build".into(),
+            ))
+        }
+    }
+
+    /// Attach an external DuckDB or SQLite database read-only under an alias
+    pub fn attach_database(
+        &self,
+        conn: &Connection,
+        project_id: &str,
+        file_path: &str,
+        alias: &str,
+    ) -> Result<AttachedDatabase> {
+        let valid_alias = !alias.is_empty()
+            && alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !alias.starts_with(|c: char| c.is_ascii_digit());
+        if !valid_alias {
+            return Err(AppError::Custom(format!(
+                "Invalid alias '{}': use letters, digits and underscores, not starting with a digit",
+                alias
+            )));
+        }
+        if matches!(
+            alias.to_lowercase().as_str(),
+            "main" | "temp" | "system" | "memory"
+        ) {
+            return Err(AppError::Custom(format!("Alias '{}' is reserved", alias)));
+        }
+        if self.list_attachments(project_id).iter().any(|a| a.alias == alias) {
+            return Err(AppError::Custom(format!("Alias '{}' is already attached", alias)));
+        }
+
+        let extension = std::path::Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        let db_type = match extension.as_str() {
+            "sqlite" | "sqlite3" | "db" => "sqlite",
+            _ => "duckdb",
+        };
+        let type_option = if db_type == "sqlite" { "TYPE SQLITE, " } else { "" };
+
+        conn.execute_batch(&format!(
+            "ATTACH '{}' AS {} ({}READ_ONLY)",
+            file_path.replace('\'', "''"),
+            quote_ident(alias),
+            type_option
+        ))?;
+
+        let attached = AttachedDatabase {
+            alias: alias.to_string(),
+            file_path: file_path.to_string(),
+            db_type: db_type.to_string(),
+        };
+        self.attachments
+            .lock()
+            .entry(project_id.to_string())
+            .or_default()
+            .push(attached.clone());
+
+        Ok(attached)
+    }
+
+    /// Detach a previously attached database
+    pub fn detach_database(&self, conn: &Connection, project_id: &str, alias: &str) -> Result<()> {
+        let mut attachments = self.attachments.lock();
+        let list = attachments.entry(project_id.to_string()).or_default();
+        if !list.iter().any(|a| a.alias == alias) {
+            return Err(AppError::Custom(format!("No database attached as '{}'", alias)));
+        }
+
+        conn.execute_batch(&format!("DETACH {}", quote_ident(alias)))?;
+        list.retain(|a| a.alias != alias);
+        Ok(())
+    }
+
+    /// List databases attached to a project's connection
+    pub fn list_attachments(&self, project_id: &str) -> Vec<AttachedDatabase> {
+        self.attachments
+            .lock()
+            .get(project_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Get tables from attached databases, named as alias.table
+    pub fn get_attached_tables(&self, conn: &Connection, project_id: &str) -> Result<Vec<TableInfo>> {
+        let mut result = Vec::new();
+
+        for attached in self.list_attachments(project_id) {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT table_name
+                FROM information_schema.tables
+                WHERE table_catalog = ?
+                ORDER BY table_name
+                "#,
+            )?;
+            let tables: Vec<String> = stmt
+                .query_map([&attached.alias], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            for table_name in tables {
+                let row_count: i64 = conn
+                    .query_row(
+                        &format!(
+                            "SELECT COUNT(*) FROM {}.{}",
+                            quote_ident(&attached.alias),
+                            quote_ident(&table_name)
+                        ),
+                        [],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or(0);
+
+                let column_count: i64 = conn
+                    .query_row(
+                        r#"
+                        SELECT COUNT(*)
+                        FROM information_schema.columns
+                        WHERE table_catalog = ? AND table_name = ?
+                        "#,
+                        [&attached.alias, &table_name],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or(0);
+
+                result.push(TableInfo {
+                    name: format!("{}.{}", attached.alias, table_name),
+                    row_count,
+                    column_count,
+                    is_vectorized: false,
+                    vectorized_columns: vec![],
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Check that a name is safe to use for a user-created table
+    pub fn validate_user_table_name(name: &str) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(AppError::Custom("Table name cannot be empty".into()));
+        }
+        if name.to_lowercase().starts_with(RESERVED_TABLE_PREFIX) {
+            return Err(AppError::Custom(format!(
+                "Table name '{}' is not allowed: names starting with '{}' are reserved for DuckBake's internal tables",
+                name, RESERVED_TABLE_PREFIX
+            )));
+        }
+        if name.contains(['"', '\'', ';']) {
+            return Err(AppError::Custom(format!(
+                "Table name '{}' is not allowed: names cannot contain quotes or semicolons",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Trim a semantic search query, rejecting empty ones (they embed to a near-zero
+    /// vector that ranks everything arbitrarily) and ones too long to be a query
+    pub fn validate_search_query(query: &str) -> Result<String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(AppError::Custom("Search query cannot be empty".into()));
+        }
+        let length = query.chars().count();
+        if length > MAX_SEARCH_QUERY_CHARS {
+            return Err(AppError::Custom(format!(
+                "Search query is too long ({} characters, the limit is {})",
+                length, MAX_SEARCH_QUERY_CHARS
+            )));
+        }
+        Ok(query.to_string())
+    }
+
+    /// Check that a name is safe to use for a column of a user table
+    /// `+` is rejected because vectorized column keys are `+`-joined column names
+    pub fn validate_user_column_name(name: &str) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(AppError::Custom("Column name cannot be empty".into()));
+        }
+        if name.to_lowercase().starts_with(RESERVED_TABLE_PREFIX) {
+            return Err(AppError::Custom(format!(
+                "Column name '{}' is not allowed: names starting with '{}' are reserved for DuckBake",
+                name, RESERVED_TABLE_PREFIX
+            )));
+        }
+        if name.contains(['"', '\'', ';', '+']) {
+            return Err(AppError::Custom(format!(
+                "Column name '{}' is not allowed: names cannot contain quotes, semicolons or '+'",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether a table exists in the project's main schema
+    pub fn table_exists(conn: &Connection, table_name: &str) -> bool {
+        conn.query_row(
+            r#"
+            SELECT COUNT(*) > 0
+            FROM information_schema.tables
+            WHERE table_name = ? AND table_schema = 'main' AND table_catalog = current_database()
+            "#,
+            [table_name],
+            |row| row.get(0),
+        )
+        .unwrap_or(false)
+    }
+
+    /// Remove one kind of project data (or all of it) in a single transaction, then
+    /// checkpoint so the freed space is reclaimed. Internal tables a project never
+    /// created are skipped.
+    pub fn clear_project_data(&self, conn: &Connection, target: ClearTarget) -> Result<ClearedData> {
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let cleared = match Self::clear_project_data_in_transaction(conn, target) {
+            Ok(cleared) => {
+                conn.execute_batch("COMMIT")?;
+                cleared
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        };
+        self.checkpoint(conn)?;
+
+        info!(
+            target: "duckdb",
+            "Cleared {:?}: {} table(s), {} embedding(s), {} document(s), {} conversation(s), {} saved quer(ies)",
+            target,
+            cleared.tables_dropped.len(),
+            cleared.embeddings_removed,
+            cleared.documents_removed,
+            cleared.conversations_removed,
+            cleared.saved_queries_removed
+        );
+        Ok(cleared)
+    }
+
+    fn clear_project_data_in_transaction(
+        conn: &Connection,
+        target: ClearTarget,
+    ) -> Result<ClearedData> {
+        let all = target == ClearTarget::AllUserData;
+        let mut cleared = ClearedData::default();
+
+        // Delete every row of an internal table, if the project has it
+        let delete_all = |table: &str| -> Result<usize> {
+            if !Self::table_exists(conn, table) {
+                return Ok(0);
+            }
+            Ok(conn.execute(&format!("DELETE FROM {}", table), [])?)
+        };
+
+        if all || target == ClearTarget::Tables {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT table_name
+                FROM information_schema.tables
+                WHERE table_schema = 'main' AND table_catalog = current_database()
+                AND table_type = 'BASE TABLE' AND NOT starts_with(lower(table_name), ?)
+                ORDER BY table_name
+                "#,
+            )?;
+            let tables: Vec<String> = stmt
+                .query_map([RESERVED_TABLE_PREFIX], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            for table in &tables {
+                conn.execute(&format!("DROP TABLE {}", quote_ident(table)), [])?;
+                let sequence = format!("_duckbake_seq_{}", table);
+                conn.execute(
+                    &format!("DROP SEQUENCE IF EXISTS {}", quote_ident(&sequence)),
+                    [],
+                )?;
+            }
+            cleared.tables_dropped = tables;
+
+            // Every embedding, config and note belongs to a user table
+            cleared.embeddings_removed += delete_all("_duckbake_embeddings")?;
+            delete_all("_duckbake_vector_config")?;
+            delete_all("_duckbake_table_notes")?;
+            if Self::table_exists(conn, "_duckbake_computed_columns") {
+                for table in &cleared.tables_dropped {
+                    let view = format!("{}_computed", table);
+                    conn.execute(&format!("DROP VIEW IF EXISTS {}", quote_ident(&view)), [])?;
+                }
+                delete_all("_duckbake_computed_columns")?;
+            }
+        }
+
+        if all || target == ClearTarget::Embeddings {
+            cleared.embeddings_removed += delete_all("_duckbake_embeddings")?;
+            delete_all("_duckbake_vector_config")?;
+            if Self::table_exists(conn, "_duckbake_document_chunks") {
+                cleared.embeddings_removed += conn.execute(
+                    "UPDATE _duckbake_document_chunks SET embedding = NULL WHERE embedding IS NOT NULL",
+                    [],
+                )?;
+                conn.execute("UPDATE _duckbake_documents SET is_vectorized = false", [])?;
+            }
+        }
+
+        if all || target == ClearTarget::Documents {
+            delete_all("_duckbake_document_chunks")?;
+            cleared.documents_removed = delete_all("_duckbake_documents")?;
+        }
+
+        if all || target == ClearTarget::Conversations {
+            // Messages reference their conversation, so they go first
+            cleared.messages_removed = delete_all("_duckbake_messages")?;
+            cleared.conversations_removed = delete_all("_duckbake_conversations")?;
+            delete_all("_duckbake_drafts")?;
+        }
+
+        if all || target == ClearTarget::SavedQueries {
+            cleared.saved_queries_removed = delete_all("_duckbake_saved_queries")?;
+        }
+
+        Ok(cleared)
     }
 
+    /// List user tables with exact row counts. Column counts and vectorized columns
+    /// are fetched for all tables at once; only the row counts need a query per
+    /// table (`list_tables_fast` avoids those by using catalog estimates).
     pub fn get_tables(&self, conn: &Connection) -> Result<Vec<TableInfo>> {
         let mut stmt = conn.prepare(
             r#"
-            SELECT table_name
-            FROM information_schema.tables
-            WHERE table_schema = 'main'
-            AND table_name NOT LIKE '_duckbake_%'
-            ORDER BY table_name
+            SELECT t.table_name, COUNT(c.column_name)
+            FROM information_schema.tables t
+            LEFT JOIN information_schema.columns c
+                ON c.table_name = t.table_name AND c.table_schema = t.table_schema
+                AND c.table_catalog = t.table_catalog
+            WHERE t.table_schema = 'main' AND t.table_catalog = current_database()
+            AND NOT starts_with(t.table_name, ?)
+            GROUP BY t.table_name
+            ORDER BY t.table_name
             "#,
         )?;
 
-        let tables: Vec<String> = stmt
-            .query_map([], |row| row.get(0))?
+        let tables: Vec<(String, i64)> = stmt
+            .query_map([RESERVED_TABLE_PREFIX], |row| Ok((row.get(0)?, row.get(1)?)))?
             .filter_map(|r| r.ok())
             .collect();
 
+        let mut vectorized = self.get_all_vectorized_columns(conn);
+
         let mut result = Vec::new();
-        for table_name in tables {
+        for (table_name, column_count) in tables {
             // Get row count
             let row_count: i64 = conn
                 .query_row(
-                    &format!("SELECT COUNT(*) FROM \"{}\"", table_name),
+                    &format!("SELECT COUNT(*) FROM {}", quote_ident(&table_name)),
                     [],
                     |row| row.get(0),
                 )
                 .unwrap_or(0);
 
-            // Get column count
-            let column_count: i64 = conn
-                .query_row(
-                    r#"
-                    SELECT COUNT(*)
-                    FROM information_schema.columns
-                    WHERE table_name = ? AND table_schema = 'main'
-                    "#,
-                    [&table_name],
-                    |row| row.get(0),
-                )
-                .unwrap_or(0);
-
-            // Check vectorization status
-            let vectorized_columns = self.get_vectorized_columns(conn, &table_name);
+            let vectorized_columns = vectorized.remove(&table_name).unwrap_or_default();
 
             result.push(TableInfo {
                 name: table_name,
@@ -98,117 +733,2092 @@ impl DuckDbService {
         Ok(result)
     }
 
-    /// Get list of vectorized columns for a table
-    fn get_vectorized_columns(&self, conn: &Connection, table_name: &str) -> Vec<String> {
-        // Check if embeddings table exists and has entries for this table
-        let query = r#"
-            SELECT DISTINCT source_column
-            FROM _duckbake_embeddings
-            WHERE table_name = ?
-        "#;
-
-        conn.prepare(query)
-            .and_then(|mut stmt| {
-                stmt.query_map([table_name], |row| row.get(0))
-                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
-            })
-            .unwrap_or_default()
-    }
-
-    pub fn get_table_schema(&self, conn: &Connection, table_name: &str) -> Result<TableSchema> {
+    /// List tables using the row and column counts DuckDB keeps in its catalog,
+    /// so large projects don't pay for a `COUNT(*)` per table
+    pub fn list_tables_fast(&self, conn: &Connection) -> Result<Vec<TableSummary>> {
         let mut stmt = conn.prepare(
             r#"
-            SELECT column_name, data_type, is_nullable
-            FROM information_schema.columns
-            WHERE table_name = ? AND table_schema = 'main'
-            ORDER BY ordinal_position
+            SELECT table_name, estimated_size, column_count
+            FROM duckdb_tables()
+            WHERE schema_name = 'main' AND database_name = current_database()
+            AND NOT starts_with(table_name, ?)
+            ORDER BY table_name
             "#,
         )?;
 
-        let columns: Vec<ColumnInfo> = stmt
-            .query_map([table_name], |row| {
-                Ok(ColumnInfo {
-                    name: row.get(0)?,
-                    data_type: row.get(1)?,
-                    nullable: row.get::<_, String>(2)? == "YES",
-                    is_primary_key: false, // TODO: Implement PK detection
-                })
-            })?
+        let tables: Vec<(String, i64, i64)> = stmt
+            .query_map([RESERVED_TABLE_PREFIX], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
             .filter_map(|r| r.ok())
             .collect();
 
-        if columns.is_empty() {
-            return Err(AppError::TableNotFound(table_name.to_string()));
+        let block_size = Self::block_size(conn);
+        let vectorized = self.get_all_vectorized_columns(conn);
+
+        let mut result = Vec::new();
+        for (table_name, estimated_row_count, column_count) in tables {
+            let estimated_size_bytes =
+                block_size.and_then(|size| Self::table_storage_bytes(conn, &table_name, size));
+            let is_vectorized = vectorized.contains_key(&table_name);
+
+            result.push(TableSummary {
+                name: table_name,
+                estimated_row_count,
+                column_count,
+                estimated_size_bytes,
+                is_vectorized,
+            });
         }
 
-        Ok(TableSchema {
-            name: table_name.to_string(),
-            columns,
-        })
+        Ok(result)
     }
 
-    pub fn execute_query(&self, conn: &Connection, sql: &str) -> Result<QueryResult> {
-        let start = Instant::now();
+    /// Size of a storage block in the current database file
+    fn block_size(conn: &Connection) -> Option<i64> {
+        conn.query_row(
+            "SELECT block_size FROM pragma_database_size() WHERE database_name = current_database()",
+            [],
+            |row| row.get(0),
+        )
+        .ok()
+    }
 
-        // First, get column names using DESCRIBE
-        let describe_sql = format!("DESCRIBE {}", sql);
-        let columns: Vec<String> = match conn.prepare(&describe_sql) {
-            Ok(mut desc_stmt) => {
-                let mut cols = Vec::new();
-                if let Ok(mut desc_rows) = desc_stmt.query([]) {
-                    while let Ok(Some(row)) = desc_rows.next() {
-                        if let Ok(name) = row.get::<_, String>(0) {
-                            cols.push(name);
-                        }
+    /// Bytes of checkpointed blocks holding a table's data
+    /// Reads segment metadata only, the table data itself isn't touched
+    fn table_storage_bytes(conn: &Connection, table_name: &str, block_size: i64) -> Option<i64> {
+        conn.query_row(
+            &format!(
+                "SELECT COUNT(DISTINCT block_id) FILTER (WHERE persistent) FROM pragma_storage_info('{}')",
+                table_name.replace('\'', "''")
+            ),
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .ok()
+        .map(|blocks| blocks * block_size)
+    }
+
+    /// Split a project's database file size into what its blocks are used for.
+    /// Anything not attributed to a table (catalog, free blocks, saved queries and
+    /// changes not yet checkpointed) is reported as "other".
+    pub fn get_storage_breakdown(
+        &self,
+        conn: &Connection,
+        db_path: &Path,
+    ) -> Result<StorageBreakdown> {
+        let file_size = std::fs::metadata(db_path).map(|m| m.len()).unwrap_or(0);
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT table_name
+            FROM duckdb_tables()
+            WHERE schema_name = 'main' AND database_name = current_database()
+            "#,
+        )?;
+        let tables: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut categories: Vec<StorageCategory> =
+            ["user_tables", "embeddings", "documents", "document_chunks", "conversations"]
+                .into_iter()
+                .map(|name| StorageCategory {
+                    name: name.to_string(),
+                    bytes: 0,
+                })
+                .collect();
+
+        if let Some(block_size) = Self::block_size(conn) {
+            for table in &tables {
+                let category = match table.as_str() {
+                    "_duckbake_embeddings" | "_duckbake_vector_config" => "embeddings",
+                    "_duckbake_documents" => "documents",
+                    "_duckbake_document_chunks" => "document_chunks",
+                    "_duckbake_conversations" | "_duckbake_messages" | "_duckbake_drafts" => {
+                        "conversations"
                     }
+                    t if !t.starts_with(RESERVED_TABLE_PREFIX) => "user_tables",
+                    _ => continue,
+                };
+                let size = Self::table_storage_bytes(conn, table, block_size).unwrap_or(0);
+                if let Some(entry) = categories.iter_mut().find(|c| c.name == category) {
+                    entry.bytes += size.max(0) as u64;
                 }
-                cols
             }
-            Err(_) => Vec::new(),
-        };
+        }
+
+        let attributed: u64 = categories.iter().map(|c| c.bytes).sum();
+        categories.push(StorageCategory {
+            name: "other".to_string(),
+            bytes: file_size.saturating_sub(attributed),
+        });
+
+        Ok(StorageBreakdown {
+            file_size,
+            categories,
+        })
+    }
+
+    /// Case-insensitive name search over a project's tables, documents, saved queries
+    /// and conversations, returning (kind, label) pairs and whether it timed out
+    /// Sources are searched in turn; once `deadline` has passed the rest are skipped
+    /// and the hits found so far returned
+    pub fn search_names(
+        &self,
+        conn: &Connection,
+        query: &str,
+        deadline: Instant,
+    ) -> Result<(Vec<(String, String)>, bool)> {
+        let pattern = format!(
+            "%{}%",
+            query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+
+        let mut stmt = conn.prepare(
+            "SELECT table_name FROM duckdb_tables() WHERE database_name = current_database()",
+        )?;
+        let existing: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let sources = [
+            (
+                "table",
+                None,
+                r#"
+                SELECT table_name FROM information_schema.tables
+                WHERE table_schema = 'main' AND table_catalog = current_database()
+                AND table_name ILIKE $1 ESCAPE '\'
+                ORDER BY table_name
+                "#,
+            ),
+            (
+                "document",
+                Some("_duckbake_documents"),
+                r#"
+                SELECT COALESCE(title, filename) FROM _duckbake_documents
+                WHERE filename ILIKE $1 ESCAPE '\' OR title ILIKE $1 ESCAPE '\'
+                ORDER BY uploaded_at DESC
+                "#,
+            ),
+            (
+                "saved_query",
+                Some("_duckbake_saved_queries"),
+                r#"
+                SELECT name FROM _duckbake_saved_queries
+                WHERE name ILIKE $1 ESCAPE '\'
+                ORDER BY updated_at DESC
+                "#,
+            ),
+            (
+                "conversation",
+                Some("_duckbake_conversations"),
+                r#"
+                SELECT title FROM _duckbake_conversations
+                WHERE title ILIKE $1 ESCAPE '\'
+                ORDER BY updated_at DESC
+                "#,
+            ),
+        ];
+
+        let mut hits = Vec::new();
+        for (kind, required_table, sql) in sources {
+            if Instant::now() >= deadline {
+                return Ok((hits, true));
+            }
+            if required_table.is_some_and(|t| !existing.iter().any(|e| e == t)) {
+                continue;
+            }
+
+            let mut stmt = conn.prepare(sql)?;
+            // Internal tables are filtered here so every source binds only the pattern
+            let labels = stmt
+                .query_map([&pattern], |row| row.get::<_, String>(0))?
+                .filter_map(|r| r.ok())
+                .filter(|label| kind != "table" || !label.starts_with(RESERVED_TABLE_PREFIX));
+            hits.extend(labels.map(|label| (kind.to_string(), label)));
+        }
+
+        Ok((hits, false))
+    }
+
+    /// Get list of vectorized columns for a table, optionally for one embedding model
+    fn get_vectorized_columns(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        model: Option<&str>,
+    ) -> Vec<String> {
+        // Check if embeddings table exists and has entries for this table
+        let query = r#"
+            SELECT DISTINCT source_column
+            FROM _duckbake_embeddings
+            WHERE table_name = ? AND embedding_model = COALESCE(?, embedding_model)
+        "#;
+
+        conn.prepare(query)
+            .and_then(|mut stmt| {
+                stmt.query_map(duckdb::params![table_name, model], |row| row.get(0))
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Vectorized column keys of every table in one pass over the embeddings,
+    /// empty when nothing has been vectorized yet
+    fn get_all_vectorized_columns(&self, conn: &Connection) -> HashMap<String, Vec<String>> {
+        let query = r#"
+            SELECT DISTINCT table_name, source_column
+            FROM _duckbake_embeddings
+            ORDER BY table_name, source_column
+        "#;
+
+        let pairs: Vec<(String, String)> = conn
+            .prepare(query)
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default();
+
+        let mut columns: HashMap<String, Vec<String>> = HashMap::new();
+        for (table, column) in pairs {
+            columns.entry(table).or_default().push(column);
+        }
+        columns
+    }
+
+    /// Get the primary key column names for a table (empty if it has none)
+    pub fn get_primary_key_columns(&self, conn: &Connection, table_name: &str) -> Vec<String> {
+        let query = r#"
+            SELECT UNNEST(constraint_column_names)
+            FROM duckdb_constraints()
+            WHERE table_name = ? AND schema_name = 'main'
+            AND database_name = current_database()
+            AND constraint_type = 'PRIMARY KEY'
+        "#;
+
+        conn.prepare(query)
+            .and_then(|mut stmt| {
+                stmt.query_map([table_name], |row| row.get(0))
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_table_schema(&self, conn: &Connection, table_name: &str) -> Result<TableSchema> {
+        let primary_key_columns = self.get_primary_key_columns(conn, table_name);
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT column_name, data_type, is_nullable
+            FROM information_schema.columns
+            WHERE table_name = ? AND table_schema = 'main' AND table_catalog = current_database()
+            ORDER BY ordinal_position
+            "#,
+        )?;
+
+        let columns: Vec<ColumnInfo> = stmt
+            .query_map([table_name], |row| {
+                let name: String = row.get(0)?;
+                Ok(ColumnInfo {
+                    is_primary_key: primary_key_columns.contains(&name),
+                    name,
+                    data_type: row.get(1)?,
+                    nullable: row.get::<_, String>(2)? == "YES",
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        if columns.is_empty() {
+            return Err(AppError::TableNotFound(table_name.to_string()));
+        }
+
+        Ok(TableSchema {
+            name: table_name.to_string(),
+            columns,
+        })
+    }
+
+    /// The CREATE TABLE statement DuckDB keeps for a user table
+    pub fn get_table_ddl(&self, conn: &Connection, table_name: &str) -> Result<String> {
+        Self::validate_user_table_name(table_name)?;
+        conn.query_row(
+            r#"
+            SELECT sql FROM duckdb_tables()
+            WHERE table_name = ? AND schema_name = 'main' AND database_name = current_database()
+            "#,
+            [table_name],
+            |row| row.get(0),
+        )
+        .map_err(|_| AppError::TableNotFound(table_name.to_string()))
+    }
+
+    /// CREATE SEQUENCE and CREATE TABLE statements for every user table, enough to
+    /// recreate the schema elsewhere. DuckBake's internal tables are left out.
+    pub fn get_schema_sql(&self, conn: &Connection) -> Result<String> {
+        // Tables imported with a generated id default to a sequence's nextval, so the
+        // sequences have to exist first
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT sql FROM duckdb_sequences()
+            WHERE schema_name = 'main' AND database_name = current_database()
+            ORDER BY sequence_name
+            "#,
+        )?;
+        let mut statements: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT sql FROM duckdb_tables()
+            WHERE schema_name = 'main' AND database_name = current_database()
+              AND NOT starts_with(table_name, ?)
+            ORDER BY table_name
+            "#,
+        )?;
+        statements.extend(
+            stmt.query_map([RESERVED_TABLE_PREFIX], |row| row.get::<_, String>(0))?
+                .filter_map(|r| r.ok()),
+        );
+
+        Ok(statements.join("\n\n"))
+    }
+
+    /// Compare two tables' columns and, when `row_limit` is set, their rows. Rows are
+    /// compared on the columns both tables share with EXCEPT ALL in each direction,
+    /// so duplicates count; retyped columns are compared as text.
+    pub fn diff_tables(
+        &self,
+        conn: &Connection,
+        table_a: &str,
+        table_b: &str,
+        row_limit: Option<u32>,
+    ) -> Result<TableDiff> {
+        let schema_a = self.get_table_schema(conn, table_a)?;
+        let schema_b = self.get_table_schema(conn, table_b)?;
+
+        let mut schema_changes = Vec::new();
+        let mut shared = Vec::new();
+        for column in &schema_a.columns {
+            match schema_b.columns.iter().find(|c| c.name == column.name) {
+                Some(other) => {
+                    let retyped = other.data_type != column.data_type;
+                    if retyped {
+                        schema_changes.push(SchemaChange {
+                            column: column.name.clone(),
+                            change: "retyped".to_string(),
+                            type_a: Some(column.data_type.clone()),
+                            type_b: Some(other.data_type.clone()),
+                        });
+                    }
+                    shared.push((column.name.clone(), retyped));
+                }
+                None => schema_changes.push(SchemaChange {
+                    column: column.name.clone(),
+                    change: "removed".to_string(),
+                    type_a: Some(column.data_type.clone()),
+                    type_b: None,
+                }),
+            }
+        }
+        for column in &schema_b.columns {
+            if !schema_a.columns.iter().any(|c| c.name == column.name) {
+                schema_changes.push(SchemaChange {
+                    column: column.name.clone(),
+                    change: "added".to_string(),
+                    type_a: None,
+                    type_b: Some(column.data_type.clone()),
+                });
+            }
+        }
+
+        let compared_columns: Vec<String> = shared.iter().map(|(name, _)| name.clone()).collect();
+
+        let (rows_only_in_a, rows_only_in_b) = match row_limit {
+            Some(limit) if !shared.is_empty() => {
+                let limit = limit.clamp(1, MAX_DIFF_ROWS);
+                let projection = shared
+                    .iter()
+                    .map(|(name, retyped)| {
+                        if *retyped {
+                            let ident = quote_ident(name);
+                            format!("CAST({} AS VARCHAR) AS {}", ident, ident)
+                        } else {
+                            quote_ident(name)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                (
+                    Some(self.rows_missing_from(conn, &projection, table_a, table_b, limit)?),
+                    Some(self.rows_missing_from(conn, &projection, table_b, table_a, limit)?),
+                )
+            }
+            _ => (None, None),
+        };
+
+        info!(
+            target: "query",
+            "Diffed {} against {}: {} schema change(s)",
+            table_a,
+            table_b,
+            schema_changes.len()
+        );
+
+        Ok(TableDiff {
+            schema_changes,
+            compared_columns,
+            rows_only_in_a,
+            rows_only_in_b,
+        })
+    }
+
+    /// Rows of `from` (projected to the shared columns) that `other` doesn't have
+    fn rows_missing_from(
+        &self,
+        conn: &Connection,
+        projection: &str,
+        from: &str,
+        other: &str,
+        limit: u32,
+    ) -> Result<RowDiff> {
+        let except = format!(
+            "SELECT {} FROM {} EXCEPT ALL SELECT {} FROM {}",
+            projection,
+            quote_ident(from),
+            projection,
+            quote_ident(other)
+        );
+
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM ({}) AS _duckbake_diff", except),
+            [],
+            |row| row.get(0),
+        )?;
+        let rows = self.execute_query(
+            conn,
+            &format!("SELECT * FROM ({}) AS _duckbake_diff LIMIT {}", except, limit),
+        )?;
+
+        Ok(RowDiff {
+            count,
+            truncated: count > rows.row_count as i64,
+            rows,
+        })
+    }
+
+    /// Rename a column, carrying any embeddings made from it over to the new name
+    pub fn rename_column(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<TableSchema> {
+        Self::validate_user_table_name(table_name)?;
+        Self::validate_user_column_name(new_name)?;
+
+        let schema = self.get_table_schema(conn, table_name)?;
+        if !schema.columns.iter().any(|c| c.name == old_name) {
+            return Err(AppError::Custom(format!(
+                "Column '{}' not found in table '{}'",
+                old_name, table_name
+            )));
+        }
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = Self::rename_column_in_transaction(conn, table_name, old_name, new_name);
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+
+        info!(target: "schema", "Renamed {}.{} to {}", table_name, old_name, new_name);
+        self.get_table_schema(conn, table_name)
+    }
+
+    fn rename_column_in_transaction(
+        conn: &Connection,
+        table_name: &str,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        conn.execute(
+            &format!(
+                "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                quote_ident(table_name),
+                quote_ident(old_name),
+                quote_ident(new_name)
+            ),
+            [],
+        )?;
+
+        // Vectorized column keys are `+`-joined names, so swap the renamed part in place
+        for (table, key_column) in Self::vectorized_key_columns(conn)? {
+            conn.execute(
+                &format!(
+                    r#"
+                    UPDATE {table}
+                    SET {key} = array_to_string(
+                        list_transform(string_split({key}, '+'), c -> CASE WHEN c = $2 THEN $3 ELSE c END),
+                        '+'
+                    )
+                    WHERE table_name = $1 AND list_contains(string_split({key}, '+'), $2)
+                    "#,
+                    table = table,
+                    key = key_column
+                ),
+                duckdb::params![table_name, old_name, new_name],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal tables present in this database that key rows by vectorized column,
+    /// paired with the name of that key column
+    fn vectorized_key_columns(conn: &Connection) -> Result<Vec<(&'static str, &'static str)>> {
+        let mut stmt = conn.prepare(
+            "SELECT table_name FROM duckdb_tables() WHERE database_name = current_database()",
+        )?;
+        let existing: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok([
+            ("_duckbake_embeddings", "source_column"),
+            ("_duckbake_vector_config", "column_name"),
+        ]
+        .into_iter()
+        .filter(|(table, _)| existing.iter().any(|t| t == table))
+        .collect())
+    }
+
+    /// Check a type name against DuckDB's own parser without running anything
+    fn validate_data_type(conn: &Connection, data_type: &str) -> Result<()> {
+        if data_type.trim().is_empty() || data_type.contains(';') || data_type.contains("--") {
+            return Err(AppError::Custom(format!("Invalid column type '{}'", data_type)));
+        }
+        conn.prepare(&format!("SELECT CAST(NULL AS {})", data_type))
+            .map_err(|e| AppError::Custom(format!("Invalid column type '{}': {}", data_type, e)))?;
+        Ok(())
+    }
+
+    /// Add a column of the given type, optionally with a default value. The default
+    /// is taken as text and cast to the column type.
+    pub fn add_column(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        name: &str,
+        data_type: &str,
+        default: Option<&str>,
+    ) -> Result<TableSchema> {
+        Self::validate_user_table_name(table_name)?;
+        Self::validate_user_column_name(name)?;
+        Self::validate_data_type(conn, data_type)?;
+
+        let mut sql = format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            quote_ident(table_name),
+            quote_ident(name),
+            data_type
+        );
+        if let Some(default) = default {
+            let default_sql = format!("CAST('{}' AS {})", default.replace('\'', "''"), data_type);
+            // Surface a bad default as a conversion error rather than a failed ALTER
+            conn.query_row(&format!("SELECT {}", default_sql), [], |_| Ok(()))
+                .map_err(|e| {
+                    AppError::Custom(format!(
+                        "Default '{}' is not a valid {}: {}",
+                        default, data_type, e
+                    ))
+                })?;
+            sql.push_str(&format!(" DEFAULT {}", default_sql));
+        }
+
+        conn.execute(&sql, [])?;
+        info!(target: "schema", "Added column {}.{} {}", table_name, name, data_type);
+        self.get_table_schema(conn, table_name)
+    }
+
+    /// Add a column computed from an expression over the table's other columns. DuckDB
+    /// generated columns are used where `ALTER TABLE` supports them; otherwise the column
+    /// is added to a `<table>_computed` view that selects the table plus every such column.
+    pub fn add_generated_column(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        name: &str,
+        expression: &str,
+    ) -> Result<GeneratedColumn> {
+        Self::validate_user_table_name(table_name)?;
+        Self::validate_user_column_name(name)?;
+
+        let expression = expression.trim();
+        if expression.is_empty() || expression.contains(';') || expression.contains("--") {
+            return Err(AppError::Custom(format!("Invalid expression '{}'", expression)));
+        }
+
+        let schema = self.get_table_schema(conn, table_name)?;
+        if schema.columns.iter().any(|c| c.name.eq_ignore_ascii_case(name)) {
+            return Err(AppError::Custom(format!(
+                "Column '{}' already exists in table '{}'",
+                name, table_name
+            )));
+        }
+
+        // Binding the expression catches unknown columns and functions before anything changes
+        conn.prepare(&format!(
+            "SELECT ({}) FROM {} LIMIT 0",
+            expression,
+            quote_ident(table_name)
+        ))
+        .map_err(|e| AppError::Custom(format!("Invalid expression '{}': {}", expression, e)))?;
+
+        let added = conn.execute(
+            &format!(
+                "ALTER TABLE {} ADD COLUMN {} AS ({})",
+                quote_ident(table_name),
+                quote_ident(name),
+                expression
+            ),
+            [],
+        );
+        let view_name = match added {
+            Ok(_) => None,
+            Err(e) => {
+                debug!(target: "schema", "Generated column not supported by ALTER TABLE: {}", e);
+                Some(Self::add_computed_view_column(conn, table_name, name, expression)?)
+            }
+        };
+
+        info!(
+            target: "schema",
+            "Added generated column {}.{} AS ({})",
+            view_name.as_deref().unwrap_or(table_name),
+            name,
+            expression
+        );
+        Ok(GeneratedColumn {
+            table_name: table_name.to_string(),
+            column_name: name.to_string(),
+            expression: expression.to_string(),
+            view_name,
+        })
+    }
+
+    /// Record a computed column and rebuild the table's `<table>_computed` view from all of them
+    fn add_computed_view_column(
+        conn: &Connection,
+        table_name: &str,
+        name: &str,
+        expression: &str,
+    ) -> Result<String> {
+        let view_name = format!("{}_computed", table_name);
+        let name_taken: bool = conn
+            .query_row(
+                r#"
+                SELECT COUNT(*) > 0
+                FROM duckdb_tables()
+                WHERE table_name = ? AND schema_name = 'main' AND database_name = current_database()
+                "#,
+                [&view_name],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        if name_taken {
+            return Err(AppError::Custom(format!(
+                "Can't create view '{}': a table with that name already exists",
+                view_name
+            )));
+        }
+
+        conn.execute_batch(COMPUTED_COLUMNS_DDL)?;
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = Self::add_computed_view_column_in_transaction(
+            conn,
+            table_name,
+            &view_name,
+            name,
+            expression,
+        );
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+
+        Ok(view_name)
+    }
+
+    fn add_computed_view_column_in_transaction(
+        conn: &Connection,
+        table_name: &str,
+        view_name: &str,
+        name: &str,
+        expression: &str,
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO _duckbake_computed_columns (table_name, column_name, expression) VALUES (?, ?, ?)",
+            duckdb::params![table_name, name, expression],
+        )?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT column_name, expression
+            FROM _duckbake_computed_columns
+            WHERE table_name = ?
+            ORDER BY created_at, column_name
+            "#,
+        )?;
+        let columns: Vec<String> = stmt
+            .query_map([table_name], |row| {
+                let column: String = row.get(0)?;
+                let expression: String = row.get(1)?;
+                Ok(format!("({}) AS {}", expression, quote_ident(&column)))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        conn.execute(
+            &format!(
+                "CREATE OR REPLACE VIEW {} AS SELECT *, {} FROM {}",
+                quote_ident(view_name),
+                columns.join(", "),
+                quote_ident(table_name)
+            ),
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Drop a table's `<table>_computed` view and the computed columns behind it
+    pub fn drop_computed_view(&self, conn: &Connection, table_name: &str) -> Result<()> {
+        if !Self::table_exists(conn, "_duckbake_computed_columns") {
+            return Ok(());
+        }
+        conn.execute(
+            &format!(
+                "DROP VIEW IF EXISTS {}",
+                quote_ident(&format!("{}_computed", table_name))
+            ),
+            [],
+        )?;
+        conn.execute(
+            "DELETE FROM _duckbake_computed_columns WHERE table_name = ?",
+            [table_name],
+        )?;
+        Ok(())
+    }
+
+    /// Drop a column along with any embeddings whose vectorized key includes it
+    pub fn drop_column(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        name: &str,
+    ) -> Result<TableSchema> {
+        Self::validate_user_table_name(table_name)?;
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = Self::drop_column_in_transaction(conn, table_name, name);
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+
+        info!(target: "schema", "Dropped column {}.{}", table_name, name);
+        self.get_table_schema(conn, table_name)
+    }
+
+    fn drop_column_in_transaction(conn: &Connection, table_name: &str, name: &str) -> Result<()> {
+        conn.execute(
+            &format!(
+                "ALTER TABLE {} DROP COLUMN {}",
+                quote_ident(table_name),
+                quote_ident(name)
+            ),
+            [],
+        )?;
+
+        for (table, key_column) in Self::vectorized_key_columns(conn)? {
+            conn.execute(
+                &format!(
+                    "DELETE FROM {} WHERE table_name = $1 AND list_contains(string_split({}, '+'), $2)",
+                    table, key_column
+                ),
+                duckdb::params![table_name, name],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Keep one row per distinct combination of `key_columns` (the whole row when none
+    /// are given, less primary key and sequence-numbered columns, which never repeat),
+    /// keeping the first by rowid and the table's original order. The table is rebuilt
+    /// from its own DDL and indexes, so rowids change: a vectorized table's embeddings
+    /// are flagged for re-indexing and its resume checkpoint is cleared.
+    pub fn deduplicate_table(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        key_columns: &[String],
+    ) -> Result<DedupeResult> {
+        let schema = self.get_table_schema(conn, table_name)?;
+        for key in key_columns {
+            if !schema.columns.iter().any(|c| &c.name == key) {
+                return Err(AppError::Custom(format!(
+                    "Column '{}' not found in table '{}'",
+                    key, table_name
+                )));
+            }
+        }
+        let key_columns: Vec<String> = if key_columns.is_empty() {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT column_name FROM duckdb_columns()
+                WHERE table_name = ? AND schema_name = 'main' AND database_name = current_database()
+                AND column_default ILIKE 'nextval(%'
+                "#,
+            )?;
+            let sequence_columns: Vec<String> = stmt
+                .query_map([table_name], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            schema
+                .columns
+                .iter()
+                .filter(|c| !c.is_primary_key && !sequence_columns.contains(&c.name))
+                .map(|c| c.name.clone())
+                .collect()
+        } else {
+            key_columns.to_vec()
+        };
+        if key_columns.is_empty() {
+            return Err(AppError::Custom(format!(
+                "Table '{}' has only key columns, so no two rows can be duplicates",
+                table_name
+            )));
+        }
+        let ddl = self.get_table_ddl(conn, table_name)?;
+
+        // Dropping the table drops its indexes too, so keep their DDL to recreate them
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT sql FROM duckdb_indexes()
+            WHERE table_name = ? AND schema_name = 'main' AND database_name = current_database()
+            AND sql IS NOT NULL
+            "#,
+        )?;
+        let indexes: Vec<String> = stmt
+            .query_map([table_name], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = Self::deduplicate_table_in_transaction(
+            conn,
+            table_name,
+            &ddl,
+            &indexes,
+            &key_columns,
+        );
+        let (rows_before, rows_after) = match result {
+            Ok(counts) => {
+                conn.execute_batch("COMMIT")?;
+                counts
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        };
+        let rows_removed = rows_before - rows_after;
+
+        let embeddings_stale =
+            rows_removed > 0 && self.get_vectorization_status(conn, table_name)?.is_vectorized;
+        if embeddings_stale {
+            self.init_embeddings_table(conn)?;
+            conn.execute(
+                "UPDATE _duckbake_vector_config SET needs_reindex = true, last_row_id = NULL WHERE table_name = ?",
+                [table_name],
+            )?;
+            warn!(
+                target: "vectorization",
+                "Deduplicating {} changed its rowids, its embeddings need re-indexing",
+                table_name
+            );
+        }
+
+        info!(target: "schema", "Removed {} duplicate row(s) from {}", rows_removed, table_name);
+        Ok(DedupeResult {
+            table_name: table_name.to_string(),
+            key_columns,
+            rows_before,
+            rows_removed,
+            embeddings_stale,
+        })
+    }
+
+    /// Copy the distinct rows aside and, if any were duplicates, recreate the table
+    /// and its indexes from them. Returns the row counts before and after.
+    fn deduplicate_table_in_transaction(
+        conn: &Connection,
+        table_name: &str,
+        ddl: &str,
+        indexes: &[String],
+        key_columns: &[String],
+    ) -> Result<(i64, i64)> {
+        let table = quote_ident(table_name);
+        let keys = key_columns.iter().map(|c| quote_ident(c)).collect::<Vec<_>>().join(", ");
+
+        conn.execute_batch(&format!(
+            r#"
+            CREATE OR REPLACE TEMP TABLE _duckbake_dedupe AS
+            SELECT * EXCLUDE (_duckbake_rowid) FROM (
+                SELECT DISTINCT ON ({keys}) rowid AS _duckbake_rowid, *
+                FROM {table}
+                ORDER BY {keys}, rowid
+            )
+            ORDER BY _duckbake_rowid
+            "#,
+            keys = keys,
+            table = table
+        ))?;
+
+        let count = |table: &str| -> Result<i64> {
+            Ok(conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))?)
+        };
+        let rows_before = count(&table)?;
+        let rows_after = count("_duckbake_dedupe")?;
+
+        if rows_after < rows_before {
+            conn.execute_batch(&format!(
+                r#"
+                DROP TABLE {table};
+                {ddl};
+                INSERT INTO {table} BY NAME SELECT * FROM _duckbake_dedupe;
+                "#,
+                table = table,
+                ddl = ddl.trim().trim_end_matches(';')
+            ))?;
+            for index in indexes {
+                conn.execute_batch(index)?;
+            }
+        }
+        conn.execute_batch("DROP TABLE _duckbake_dedupe")?;
+
+        Ok((rows_before, rows_after))
+    }
+
+    /// Change a column's type in place. Values that don't convert become NULL rather
+    /// than failing the change, and how many did is reported.
+    pub fn change_column_type(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        column: &str,
+        new_type: &str,
+    ) -> Result<ColumnTypeChange> {
+        Self::validate_user_table_name(table_name)?;
+        Self::validate_user_column_name(column)?;
+        Self::validate_data_type(conn, new_type)?;
+
+        let schema = self.get_table_schema(conn, table_name)?;
+        let old_type = schema
+            .columns
+            .iter()
+            .find(|c| c.name == column)
+            .map(|c| c.data_type.clone())
+            .ok_or_else(|| {
+                AppError::Custom(format!(
+                    "Column '{}' not found in table '{}'",
+                    column, table_name
+                ))
+            })?;
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = Self::change_column_type_in_transaction(conn, table_name, column, new_type);
+        let failed_casts = match result {
+            Ok(failed) => {
+                conn.execute_batch("COMMIT")?;
+                failed
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        };
+
+        info!(
+            target: "schema",
+            "Changed {}.{} from {} to {}, {} value(s) failed to cast",
+            table_name,
+            column,
+            old_type,
+            new_type,
+            failed_casts
+        );
+        Ok(ColumnTypeChange {
+            schema: self.get_table_schema(conn, table_name)?,
+            old_type,
+            failed_casts,
+        })
+    }
+
+    /// Count the values that won't convert, then convert the column, returning the count
+    fn change_column_type_in_transaction(
+        conn: &Connection,
+        table_name: &str,
+        column: &str,
+        new_type: &str,
+    ) -> Result<i64> {
+        let table = quote_ident(table_name);
+        let column = quote_ident(column);
+
+        let failed: i64 = conn.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM {} WHERE {col} IS NOT NULL AND TRY_CAST({col} AS {ty}) IS NULL",
+                table,
+                col = column,
+                ty = new_type
+            ),
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute(
+            &format!(
+                "ALTER TABLE {} ALTER COLUMN {col} TYPE {ty} USING TRY_CAST({col} AS {ty})",
+                table,
+                col = column,
+                ty = new_type
+            ),
+            [],
+        )?;
+
+        Ok(failed)
+    }
+
+    pub fn execute_query(&self, conn: &Connection, sql: &str) -> Result<QueryResult> {
+        let start = Instant::now();
+        // Query text may contain user data, so it's only logged at debug level
+        debug!(target: "query", "Executing: {}", sql);
+
+        // Data modification has nothing to DESCRIBE; report how many rows changed instead
+        if Self::is_dml_without_returning(sql) {
+            let affected = conn.execute(sql, [])?;
+            info!(
+                target: "query",
+                "Statement affected {} row(s) in {}ms",
+                affected,
+                start.elapsed().as_millis()
+            );
+            return Ok(QueryResult {
+                columns: Vec::new(),
+                rows: Vec::new(),
+                row_count: 0,
+                execution_time_ms: start.elapsed().as_millis() as u64,
+                affected_rows: Some(affected),
+                has_rowid: false,
+                estimated_cardinality: None,
+            });
+        }
+
+        let columns = Self::describe_columns(conn, sql);
+        let estimated_cardinality = Self::estimate_cardinality(conn, sql);
+
+        // Now execute the actual query
+        let mut stmt = conn.prepare(sql)?;
+        let mut row_iter = stmt.query([])?;
+
+        let actual_columns = if columns.is_empty() {
+            row_iter
+                .as_ref()
+                .map(|executed| executed.column_names())
+                .unwrap_or_default()
+        } else {
+            columns
+        };
+
+        let mut rows: Vec<Value> = Vec::new();
+
+        while let Some(row) = row_iter.next()? {
+            let mut row_obj = serde_json::Map::new();
+            for (i, col_name) in actual_columns.iter().enumerate() {
+                let value = self.get_value_from_row(row, i);
+                row_obj.insert(col_name.clone(), value);
+            }
+            rows.push(Value::Object(row_obj));
+        }
+
+        let execution_time_ms = start.elapsed().as_millis() as u64;
+        let row_count = rows.len();
+        info!(target: "query", "Query returned {} row(s) in {}ms", row_count, execution_time_ms);
+
+        Ok(QueryResult {
+            columns: actual_columns,
+            rows,
+            row_count,
+            execution_time_ms,
+            affected_rows: None,
+            has_rowid: false,
+            estimated_cardinality,
+        })
+    }
+
+    /// Rows the planner expects a read query to return, taken from the top operator of
+    /// its `EXPLAIN` plan. None for anything that isn't a single read statement.
+    fn estimate_cardinality(conn: &Connection, sql: &str) -> Option<u64> {
+        let statement = Self::single_read_statement(sql).ok()?;
+        let plan: String = conn
+            .query_row(&format!("EXPLAIN {}", statement), [], |row| row.get(1))
+            .ok()?;
+        Self::parse_estimated_rows(&plan)
+    }
+
+    /// First row estimate in a rendered plan, which belongs to the root operator.
+    /// Newer DuckDB versions print `~1,234 rows`, older ones `EC: 1234`.
+    fn parse_estimated_rows(plan: &str) -> Option<u64> {
+        plan.lines().find_map(|line| {
+            let line = line.to_lowercase();
+            let (rest, needs_suffix) = match line.find('~') {
+                Some(i) => (&line[i + 1..], true),
+                None => (line.split_once("ec:")?.1.trim_start(), false),
+            };
+            let number: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == ',')
+                .collect();
+            // `~` also shows up in filter expressions, e.g. `~~` for LIKE
+            if needs_suffix && !rest[number.len()..].trim_start().starts_with("row") {
+                return None;
+            }
+            number.replace(',', "").parse().ok()
+        })
+    }
+
+    /// Check a statement parses and binds against the current schema without running
+    /// it. Preparing never executes, and only read queries are DESCRIBEd for their
+    /// output columns, so nothing in the database can change.
+    pub fn validate_query(&self, conn: &Connection, sql: &str) -> QueryValidation {
+        if sql.trim().is_empty() {
+            return QueryValidation {
+                valid: false,
+                columns: Vec::new(),
+                error: Some(QueryError {
+                    message: "Query is empty".into(),
+                    line: None,
+                    column: None,
+                }),
+            };
+        }
+
+        // duckdb-rs runs every statement but the last while preparing, so a script
+        // can't be checked without side effects. Only the end is trimmed, keeping
+        // error positions in line with the editor.
+        let statement = sql.trim_end().trim_end_matches(';');
+        if statement.contains(';') {
+            return QueryValidation {
+                valid: false,
+                columns: Vec::new(),
+                error: Some(QueryError {
+                    message: "Only a single statement can be validated".into(),
+                    line: None,
+                    column: None,
+                }),
+            };
+        }
+
+        if let Err(e) = conn.prepare(statement) {
+            let message = e.to_string();
+            let (line, column) = Self::error_position(sql, &message);
+            // Keep the explanation, the excerpt is replaced by line and column
+            let summary = message
+                .lines()
+                .take_while(|l| !l.starts_with("LINE "))
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            return QueryValidation {
+                valid: false,
+                columns: Vec::new(),
+                error: Some(QueryError {
+                    message: summary,
+                    line,
+                    column,
+                }),
+            };
+        }
+
+        let mut columns = Vec::new();
+        if let Ok(statement) = Self::single_read_statement(sql) {
+            if let Ok(mut stmt) = conn.prepare(&format!("DESCRIBE {}", statement)) {
+                if let Ok(mut rows) = stmt.query([]) {
+                    while let Ok(Some(row)) = rows.next() {
+                        let key: Option<String> = row.get(3).ok().flatten();
+                        columns.push(ColumnInfo {
+                            name: row.get(0).unwrap_or_default(),
+                            data_type: row.get(1).unwrap_or_default(),
+                            nullable: row.get::<_, String>(2).map(|n| n == "YES").unwrap_or(true),
+                            is_primary_key: key.as_deref() == Some("PRI"),
+                        });
+                    }
+                }
+            }
+        }
+
+        QueryValidation {
+            valid: true,
+            columns,
+            error: None,
+        }
+    }
+
+    /// Line and column (both from 1) of the caret DuckDB draws under its
+    /// `LINE n: ...` excerpt of the failing statement
+    fn error_position(sql: &str, message: &str) -> (Option<usize>, Option<usize>) {
+        let mut lines = message.lines();
+        while let Some(text) = lines.next() {
+            let Some(rest) = text.strip_prefix("LINE ") else {
+                continue;
+            };
+            let Some((number, excerpt)) = rest.split_once(": ") else {
+                continue;
+            };
+            let Ok(line) = number.parse::<usize>() else {
+                continue;
+            };
+            let prefix_len = text.len() - excerpt.len();
+
+            let column = lines.next().and_then(|caret_line| {
+                let caret = caret_line.find('^')?.checked_sub(prefix_len)?;
+                // Long lines are cut down around the error with "..." on either side,
+                // so find where the excerpt starts in the real line
+                let body = excerpt.strip_prefix("...").unwrap_or(excerpt);
+                let offset = caret.checked_sub(excerpt.len() - body.len())?;
+                let body = body.strip_suffix("...").unwrap_or(body);
+                let source_line = sql.lines().nth(line.checked_sub(1)?)?;
+                let start = source_line.find(body)?;
+                source_line
+                    .get(..start + offset)
+                    .map(|before| before.chars().count() + 1)
+            });
+            return (Some(line), column);
+        }
+        (None, None)
+    }
+
+    /// Output column names of a query using DESCRIBE. Some statements (PRAGMA, SET,
+    /// CALL) can't be described, so an empty list means ask the executed statement
+    fn describe_columns(conn: &Connection, sql: &str) -> Vec<String> {
+        let describe_sql = format!("DESCRIBE {}", sql);
+        match conn.prepare(&describe_sql) {
+            Ok(mut desc_stmt) => {
+                let mut cols = Vec::new();
+                if let Ok(mut desc_rows) = desc_stmt.query([]) {
+                    while let Ok(Some(row)) = desc_rows.next() {
+                        if let Ok(name) = row.get::<_, String>(0) {
+                            cols.push(name);
+                        }
+                    }
+                }
+                cols
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Run a query and hand its rows over in batches as they are read, rather than
+    /// collecting the whole result first. `on_event` returns false to stop early,
+    /// e.g. when the stream was cancelled or its receiver went away; a `Complete`
+    /// event is always sent last.
+    pub fn execute_query_streamed(
+        &self,
+        conn: &Connection,
+        sql: &str,
+        batch_size: usize,
+        on_event: &mut dyn FnMut(QueryStreamEvent) -> bool,
+    ) -> Result<()> {
+        let start = Instant::now();
+        debug!(target: "query", "Streaming: {}", sql);
+
+        if Self::is_dml_without_returning(sql) {
+            let affected = conn.execute(sql, [])?;
+            on_event(QueryStreamEvent::Complete {
+                row_count: 0,
+                execution_time_ms: start.elapsed().as_millis() as u64,
+                affected_rows: Some(affected),
+                cancelled: false,
+            });
+            return Ok(());
+        }
+
+        let columns = Self::describe_columns(conn, sql);
+        let mut stmt = conn.prepare(sql)?;
+        let mut row_iter = stmt.query([])?;
+        let columns = if columns.is_empty() {
+            row_iter
+                .as_ref()
+                .map(|executed| executed.column_names())
+                .unwrap_or_default()
+        } else {
+            columns
+        };
+
+        let mut cancelled = !on_event(QueryStreamEvent::Columns {
+            columns: columns.clone(),
+        });
+        let mut row_count = 0;
+        let mut batch: Vec<Value> = Vec::with_capacity(batch_size);
+
+        while !cancelled {
+            let Some(row) = row_iter.next()? else {
+                break;
+            };
+            let mut row_obj = serde_json::Map::new();
+            for (i, col_name) in columns.iter().enumerate() {
+                row_obj.insert(col_name.clone(), self.get_value_from_row(row, i));
+            }
+            batch.push(Value::Object(row_obj));
+
+            if batch.len() >= batch_size {
+                let sent = batch.len();
+                cancelled = !on_event(QueryStreamEvent::RowBatch {
+                    rows: std::mem::replace(&mut batch, Vec::with_capacity(batch_size)),
+                });
+                if !cancelled {
+                    row_count += sent;
+                }
+            }
+        }
+
+        if !cancelled && !batch.is_empty() {
+            let sent = batch.len();
+            cancelled = !on_event(QueryStreamEvent::RowBatch { rows: batch });
+            if !cancelled {
+                row_count += sent;
+            }
+        }
+
+        let execution_time_ms = start.elapsed().as_millis() as u64;
+        info!(
+            target: "query",
+            "Streamed {} row(s) in {}ms{}",
+            row_count,
+            execution_time_ms,
+            if cancelled { " before being cancelled" } else { "" }
+        );
+
+        on_event(QueryStreamEvent::Complete {
+            row_count,
+            execution_time_ms,
+            affected_rows: None,
+            cancelled,
+        });
+        Ok(())
+    }
+
+    /// Uppercased keyword tokens of a statement, ignoring `--` comment lines
+    fn statement_tokens(sql: &str) -> Vec<String> {
+        sql.lines()
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .to_uppercase()
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Whether a statement is INSERT/UPDATE/DELETE and returns no rows of its own
+    fn is_dml_without_returning(sql: &str) -> bool {
+        let tokens = Self::statement_tokens(sql);
+        let keyword = tokens.first().map(String::as_str).unwrap_or("");
+
+        matches!(keyword, "INSERT" | "UPDATE" | "DELETE") && !tokens.iter().any(|t| t == "RETURNING")
+    }
+
+    /// Run a single query that must only read data, returning at most `max_rows` rows.
+    /// Used for SQL we didn't write ourselves (e.g. from the chat model): only
+    /// SELECT-style statements are accepted, and they run in a transaction that is
+    /// always rolled back.
+    pub fn execute_read_only(
+        &self,
+        conn: &Connection,
+        sql: &str,
+        max_rows: usize,
+    ) -> Result<QueryResult> {
+        let statement = Self::single_read_statement(sql)?;
+
+        let capped = format!(
+            "SELECT * FROM ({}) AS _duckbake_read_only LIMIT {}",
+            statement, max_rows
+        );
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = self.execute_query(conn, &capped);
+        let _ = conn.execute_batch("ROLLBACK");
+        result
+    }
+
+    /// Trim a statement and check it is a single SELECT-style query
+    fn single_read_statement(sql: &str) -> Result<&str> {
+        let statement = sql.trim().trim_end_matches(';').trim();
+        if statement.contains(';') {
+            return Err(AppError::Custom("Only a single statement can be run".into()));
+        }
+
+        let tokens = Self::statement_tokens(statement);
+        let keyword = tokens.first().map(String::as_str).unwrap_or("");
+        if !matches!(keyword, "SELECT" | "WITH" | "FROM" | "VALUES") {
+            return Err(AppError::Custom(format!(
+                "Only read-only queries can be run here, not {}",
+                if keyword.is_empty() { "an empty statement" } else { keyword }
+            )));
+        }
+        Ok(statement)
+    }
+
+    /// Run a query from a chat reply. When it fails on a table or column that doesn't
+    /// exist, the closest real name is suggested, and with `auto_correct` the query
+    /// is re-run once with it if the match is close and unambiguous.
+    pub fn execute_chat_query(
+        &self,
+        conn: &Connection,
+        sql: &str,
+        auto_correct: bool,
+    ) -> ChatQueryResult {
+        let message = match self.execute_query(conn, sql) {
+            Ok(result) => {
+                return ChatQueryResult {
+                    sql: sql.to_string(),
+                    result: Some(result),
+                    error: None,
+                    did_you_mean: None,
+                    corrected: false,
+                };
+            }
+            Err(e) => e.to_string(),
+        };
+
+        let suggestion = self.suggest_name(conn, &message).unwrap_or_else(|e| {
+            warn!(target: "query", "Failed to look up name suggestions: {}", e);
+            None
+        });
+
+        let correctable = suggestion
+            .as_ref()
+            .filter(|s| auto_correct && s.unambiguous && s.confidence >= AUTO_CORRECT_CONFIDENCE);
+        if let Some(suggestion) = correctable {
+            let corrected_sql =
+                Self::replace_identifier(sql, &suggestion.missing, &suggestion.did_you_mean);
+            if corrected_sql != sql {
+                if let Ok(result) = self.execute_query(conn, &corrected_sql) {
+                    info!(
+                        target: "query",
+                        "Corrected {} \"{}\" to \"{}\" in chat query",
+                        suggestion.kind,
+                        suggestion.missing,
+                        suggestion.did_you_mean
+                    );
+                    return ChatQueryResult {
+                        sql: corrected_sql,
+                        result: Some(result),
+                        error: None,
+                        did_you_mean: Some(suggestion.clone()),
+                        corrected: true,
+                    };
+                }
+            }
+        }
+
+        ChatQueryResult {
+            sql: sql.to_string(),
+            result: None,
+            error: Some(message),
+            did_you_mean: suggestion,
+            corrected: false,
+        }
+    }
+
+    /// Closest user table or column name to the one a DuckDB "not found" error
+    /// complains about, None for other errors or when nothing is close enough
+    pub fn suggest_name(&self, conn: &Connection, message: &str) -> Result<Option<NameSuggestion>> {
+        let Some((kind, missing)) = Self::missing_name(message) else {
+            return Ok(None);
+        };
+
+        let query = if kind == "table" {
+            "SELECT DISTINCT table_name FROM information_schema.tables
+             WHERE table_schema = 'main' AND NOT starts_with(table_name, ?)"
+        } else {
+            "SELECT DISTINCT column_name FROM information_schema.columns
+             WHERE table_schema = 'main' AND NOT starts_with(table_name, ?)"
+        };
+        let mut stmt = conn.prepare(query)?;
+        let names: Vec<String> = stmt
+            .query_map([RESERVED_TABLE_PREFIX], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let missing_lower = missing.to_lowercase();
+        let mut scored: Vec<(usize, String)> = names
+            .into_iter()
+            .map(|name| (Self::levenshtein(&missing_lower, &name.to_lowercase()), name))
+            .collect();
+        scored.sort();
+
+        let Some((distance, best)) = scored.first() else {
+            return Ok(None);
+        };
+        let longest = missing.chars().count().max(best.chars().count()).max(1);
+        let confidence = 1.0 - *distance as f64 / longest as f64;
+        if confidence < MIN_SUGGESTION_CONFIDENCE {
+            return Ok(None);
+        }
+
+        Ok(Some(NameSuggestion {
+            kind: kind.to_string(),
+            did_you_mean: best.clone(),
+            confidence,
+            unambiguous: scored.get(1).is_none_or(|(next, _)| next > distance),
+            missing,
+        }))
+    }
+
+    /// Which kind of name a DuckDB error says is missing, and the name itself
+    fn missing_name(message: &str) -> Option<(&'static str, String)> {
+        if let Some(rest) = message.split("Table with name ").nth(1) {
+            let name = rest.split(" does not exist").next()?;
+            return Some(("table", name.trim_matches('"').to_string()));
+        }
+        if let Some(rest) = message.split("Referenced column \"").nth(1) {
+            let name = rest.split("\" not found").next()?;
+            return Some(("column", name.to_string()));
+        }
+        None
+    }
+
+    /// Edit distance between two strings, counted in characters
+    fn levenshtein(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut previous: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut current = vec![i + 1];
+            for (j, cb) in b.iter().enumerate() {
+                let substitution = previous[j] + usize::from(ca != *cb);
+                current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+            }
+            previous = current;
+        }
+        previous[b.len()]
+    }
+
+    /// Replace every reference to identifier `from` with `to`, matching bare names
+    /// case-insensitively and quoted ones exactly. String literals are left alone.
+    fn replace_identifier(sql: &str, from: &str, to: &str) -> String {
+        let replacement = quote_ident(to);
+        let mut out = String::with_capacity(sql.len());
+        let mut chars = sql.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' | '"' => {
+                    let mut literal = String::new();
+                    while let Some(next) = chars.next() {
+                        if next == c {
+                            // A doubled quote is an escaped one, not the end
+                            if chars.peek() == Some(&c) {
+                                chars.next();
+                                literal.push(c);
+                                continue;
+                            }
+                            break;
+                        }
+                        literal.push(next);
+                    }
+                    if c == '"' && literal == from {
+                        out.push_str(&replacement);
+                    } else {
+                        let escaped = literal.replace(c, &format!("{}{}", c, c));
+                        out.push(c);
+                        out.push_str(&escaped);
+                        out.push(c);
+                    }
+                }
+                c if c.is_alphanumeric() || c == '_' => {
+                    let mut word = String::from(c);
+                    while let Some(&next) = chars.peek() {
+                        if !(next.is_alphanumeric() || next == '_') {
+                            break;
+                        }
+                        word.push(next);
+                        chars.next();
+                    }
+                    if word.eq_ignore_ascii_case(from) {
+                        out.push_str(&replacement);
+                    } else {
+                        out.push_str(&word);
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Save the results of a query as a table. Create fails if the table already
+    /// exists, Replace overwrites it and Append inserts by column name, so columns
+    /// the query doesn't produce keep their defaults.
+    pub fn materialize_query(
+        &self,
+        conn: &Connection,
+        sql: &str,
+        table_name: &str,
+        mode: ImportMode,
+    ) -> Result<ImportResult> {
+        Self::validate_user_table_name(table_name)?;
+        let statement = Self::single_read_statement(sql)?;
+        let table = quote_ident(table_name);
+        let start = Instant::now();
+
+        let write_sql = match mode {
+            ImportMode::Create => format!("CREATE TABLE {} AS {}", table, statement),
+            ImportMode::Replace => format!("CREATE OR REPLACE TABLE {} AS {}", table, statement),
+            ImportMode::Append => format!("INSERT INTO {} BY NAME {}", table, statement),
+        };
+        let affected = conn.execute(&write_sql, [])? as i64;
+
+        // CREATE TABLE AS doesn't report a changed row count, so count the new table
+        let rows_written = match mode {
+            ImportMode::Append => affected,
+            _ => conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })?,
+        };
+
+        let column_count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM information_schema.columns WHERE table_name = ? AND table_schema = 'main' AND table_catalog = current_database()",
+            [table_name],
+            |row| row.get(0),
+        )?;
+
+        info!(
+            target: "query",
+            "Materialized {} row(s) into {} in {}ms",
+            rows_written,
+            table_name,
+            start.elapsed().as_millis()
+        );
+
+        let tables_created = match mode {
+            ImportMode::Append => vec![],
+            _ => vec![table_name.to_string()],
+        };
+
+        Ok(ImportResult {
+            table_name: table_name.to_string(),
+            rows_imported: rows_written,
+            columns_count: column_count as usize,
+            tables_created,
+        })
+    }
+
+    /// Format a query result as tab-separated values with a header row
+    /// Cells containing tabs, newlines or quotes are wrapped in double quotes
+    pub fn result_to_tsv(&self, result: &QueryResult) -> String {
+        fn escape(cell: &str) -> String {
+            if cell.contains(['\t', '\n', '\r', '"']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        }
+
+        let mut lines = Vec::with_capacity(result.rows.len() + 1);
+        lines.push(
+            result
+                .columns
+                .iter()
+                .map(|c| escape(c))
+                .collect::<Vec<_>>()
+                .join("\t"),
+        );
+
+        for row in &result.rows {
+            let cells = result
+                .columns
+                .iter()
+                .map(|col| match row.get(col) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => escape(s),
+                    Some(other) => escape(&other.to_string()),
+                })
+                .collect::<Vec<_>>()
+                .join("\t");
+            lines.push(cells);
+        }
+
+        lines.join("\n")
+    }
+
+    /// Recommend a chart for a result from its values: a date/time column with a numeric
+    /// one makes a line chart, a category with a numeric one a bar chart (or a pie when
+    /// there are few categories and the numbers are shares of a whole), anything else
+    /// a table
+    pub fn suggest_visualization(result: &QueryResult) -> VizSuggestion {
+        let table = |confidence: f64| VizSuggestion {
+            viz: VizType::Table,
+            x_key: None,
+            y_key: None,
+            confidence,
+        };
+        if result.rows.is_empty() || result.columns.len() < 2 {
+            return table(0.9);
+        }
+
+        let values = |column: &str| -> Vec<&Value> {
+            result
+                .rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|v| !v.is_null())
+                .collect()
+        };
+        let looks_temporal = |column: &str, values: &[&Value]| {
+            let name = column.to_lowercase();
+            let temporal_name = ["date", "time", "day", "week", "month", "year", "period"]
+                .iter()
+                .any(|hint| name.contains(hint));
+            values.iter().all(|v| match v {
+                Value::String(s) => {
+                    let b = s.as_bytes();
+                    b.len() >= 7
+                        && b[..4].iter().all(u8::is_ascii_digit)
+                        && b[4] == b'-'
+                        && b[5..7].iter().all(u8::is_ascii_digit)
+                }
+                Value::Number(n) => temporal_name && n.is_i64(),
+                _ => false,
+            })
+        };
+
+        let mut temporal = Vec::new();
+        let mut numeric = Vec::new();
+        let mut categorical = Vec::new();
+        for column in &result.columns {
+            let column_values = values(column);
+            if column_values.is_empty() {
+                categorical.push(column);
+            } else if looks_temporal(column, &column_values) {
+                temporal.push(column);
+            } else if column_values.iter().all(|v| v.is_number()) {
+                numeric.push(column);
+            } else {
+                categorical.push(column);
+            }
+        }
+
+        let suggestion = |viz: VizType, x: &String, y: &String, confidence: f64| VizSuggestion {
+            viz,
+            x_key: Some(x.clone()),
+            y_key: Some(y.clone()),
+            confidence,
+        };
+        // Extra columns make it less certain the chart shows what the query was about
+        let fit = if result.columns.len() == 2 { 1.0 } else { 0.8 };
+
+        match (temporal.as_slice(), categorical.as_slice(), numeric.as_slice()) {
+            ([x], [], [y, ..]) => suggestion(VizType::Line, x, y, 0.9 * fit),
+            ([], [x], [y]) => {
+                let mut categories: Vec<String> =
+                    values(x).iter().map(|v| v.to_string()).collect();
+                categories.sort();
+                categories.dedup();
+
+                let amounts: Vec<f64> =
+                    values(y).iter().filter_map(|v| v.as_f64()).collect();
+                let total: f64 = amounts.iter().sum();
+                let name = y.to_lowercase();
+                let is_share = ["percent", "pct", "share", "ratio", "proportion", "fraction"]
+                    .iter()
+                    .any(|hint| name.contains(hint))
+                    || (total - 1.0).abs() < 0.01
+                    || (total - 100.0).abs() < 0.5;
+
+                let non_negative = amounts.iter().all(|a| *a >= 0.0);
+
+                if categories.len() <= PIE_MAX_SLICES && non_negative && is_share {
+                    suggestion(VizType::Pie, x, y, 0.8)
+                } else if categories.len() <= BAR_MAX_CATEGORIES {
+                    suggestion(VizType::Bar, x, y, 0.85)
+                } else {
+                    suggestion(VizType::Bar, x, y, 0.5)
+                }
+            }
+            _ => table(0.6),
+        }
+    }
+
+    /// Group a table by one column and aggregate another, building the SQL from
+    /// validated, quoted names. `Count` without a measure counts rows; `Sum` and `Avg`
+    /// need a numeric measure. Date/time groups come back in order for a line chart,
+    /// others largest first for a bar chart.
+    ///
+    /// With a `bucket`, a date or timestamp group column is truncated to the day, week,
+    /// month or year first. `fill_gaps` then adds the buckets no row falls in, as zero
+    /// for `Count` and `Sum` and null otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub fn aggregate(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        group_by: &str,
+        measure: Option<&str>,
+        agg: AggregateFn,
+        bucket: Option<TimeBucket>,
+        fill_gaps: bool,
+    ) -> Result<AggregateResult> {
+        let schema = self.get_table_schema(conn, table_name)?;
+        let find_column = |name: &str| {
+            schema.columns.iter().find(|c| c.name == name).ok_or_else(|| {
+                AppError::Custom(format!("Column '{}' not found in {}", name, table_name))
+            })
+        };
+        let group_column = find_column(group_by)?;
+        let measure_column = match measure.map(str::trim).filter(|m| !m.is_empty()) {
+            Some(name) => Some(find_column(name)?),
+            None => None,
+        };
+
+        let (value, alias) = match measure_column {
+            None if agg == AggregateFn::Count => ("COUNT(*)".to_string(), "count".to_string()),
+            None => {
+                return Err(AppError::Custom(format!(
+                    "{} needs a measure column",
+                    agg.as_str()
+                )))
+            }
+            Some(column) => {
+                if matches!(agg, AggregateFn::Sum | AggregateFn::Avg)
+                    && !Self::is_numeric_type(&column.data_type)
+                {
+                    return Err(AppError::Custom(format!(
+                        "Can't {} '{}': it is {}, not a number",
+                        agg.as_str(),
+                        column.name,
+                        column.data_type
+                    )));
+                }
+                (
+                    format!("{}({})", agg.as_str().to_uppercase(), quote_ident(&column.name)),
+                    format!("{}_{}", agg.as_str(), column.name),
+                )
+            }
+        };
+
+        let group = quote_ident(&group_column.name);
+        let table = quote_ident(table_name);
+        let temporal = bucket.is_some() || Self::is_temporal_type(&group_column.data_type);
+        // Dates and timestamps would otherwise be read back as day or microsecond counts
+        let group_value = if temporal {
+            format!("CAST({} AS VARCHAR)", group)
+        } else {
+            group.clone()
+        };
+        let sql = match bucket {
+            None => format!(
+                "SELECT {group_value} AS {group}, {value} AS {alias} FROM {table} GROUP BY 1 ORDER BY {order} LIMIT {limit}",
+                alias = quote_ident(&alias),
+                order = if temporal { "1" } else { "2 DESC NULLS LAST, 1" },
+                limit = MAX_AGGREGATE_GROUPS,
+            ),
+            Some(bucket) => {
+                let data_type = group_column.data_type.to_uppercase();
+                if data_type != "DATE" && !data_type.starts_with("TIMESTAMP") {
+                    return Err(AppError::Custom(format!(
+                        "Can't bucket '{}' by {}: it is {}, not a date or timestamp",
+                        group_column.name,
+                        bucket.as_str(),
+                        group_column.data_type
+                    )));
+                }
+                // generate_series only steps timestamps, so bucket as timestamps and
+                // hand dates back as dates (as text, like the unbucketed case)
+                let output_type = if data_type == "DATE" { "DATE" } else { "TIMESTAMP" };
+                let truncated = format!("date_trunc('{}', {})::TIMESTAMP", bucket.as_str(), group);
+
+                if fill_gaps {
+                    // The series is cut at the limit, so refuse rather than silently
+                    // drop the most recent buckets
+                    let span: Option<i64> = conn.query_row(
+                        &format!(
+                            "SELECT date_diff('{bucket}', MIN({truncated}), MAX({truncated})) + 1 FROM {table}",
+                            bucket = bucket.as_str(),
+                        ),
+                        [],
+                        |row| row.get(0),
+                    )?;
+                    if let Some(span) = span.filter(|span| *span > MAX_AGGREGATE_GROUPS as i64) {
+                        return Err(AppError::Custom(format!(
+                            "'{}' spans {} {}s, more than the {} a chart can show; use a wider bucket",
+                            group_column.name,
+                            span,
+                            bucket.as_str(),
+                            MAX_AGGREGATE_GROUPS
+                        )));
+                    }
+
+                    let empty = match agg {
+                        AggregateFn::Count | AggregateFn::Sum => "0",
+                        _ => "NULL",
+                    };
+                    format!(
+                        r#"
+                        WITH buckets AS (
+                            SELECT {truncated} AS bucket, {value} AS value
+                            FROM {table} WHERE {group} IS NOT NULL GROUP BY 1
+                        ), series AS (
+                            SELECT UNNEST(generate_series(
+                                (SELECT MIN(bucket) FROM buckets),
+                                (SELECT MAX(bucket) FROM buckets),
+                                INTERVAL 1 {unit}
+                            )) AS bucket
+                        )
+                        SELECT CAST(series.bucket::{output_type} AS VARCHAR) AS {group},
+                            COALESCE(buckets.value, {empty}) AS {alias}
+                        FROM series LEFT JOIN buckets USING (bucket)
+                        ORDER BY 1 LIMIT {limit}
+                        "#,
+                        unit = bucket.as_str().to_uppercase(),
+                        alias = quote_ident(&alias),
+                        limit = MAX_AGGREGATE_GROUPS,
+                    )
+                } else {
+                    format!(
+                        "SELECT CAST({truncated}::{output_type} AS VARCHAR) AS {group}, {value} AS {alias} FROM {table} WHERE {group} IS NOT NULL GROUP BY 1 ORDER BY 1 LIMIT {limit}",
+                        alias = quote_ident(&alias),
+                        limit = MAX_AGGREGATE_GROUPS,
+                    )
+                }
+            }
+        };
+        let result = self.execute_query(conn, &sql)?;
+
+        Ok(AggregateResult {
+            result,
+            viz: VizSpec {
+                sql,
+                viz: if temporal { VizType::Line } else { VizType::Bar },
+                x_key: Some(group_column.name.clone()),
+                y_key: Some(alias),
+                start: 0,
+                end: 0,
+            },
+        })
+    }
 
-        // Now execute the actual query
-        let mut stmt = conn.prepare(sql)?;
-        let mut row_iter = stmt.query([])?;
+    /// Whether a DuckDB type name (as in `information_schema.columns`) holds numbers
+    fn is_numeric_type(data_type: &str) -> bool {
+        let base = data_type.split('(').next().unwrap_or_default().trim().to_uppercase();
+        matches!(
+            base.as_str(),
+            "TINYINT"
+                | "SMALLINT"
+                | "INTEGER"
+                | "BIGINT"
+                | "HUGEINT"
+                | "UTINYINT"
+                | "USMALLINT"
+                | "UINTEGER"
+                | "UBIGINT"
+                | "UHUGEINT"
+                | "FLOAT"
+                | "DOUBLE"
+                | "DECIMAL"
+        )
+    }
 
-        let mut rows: Vec<Value> = Vec::new();
-        let mut first_row = true;
-        let mut actual_columns = columns.clone();
+    /// Whether a DuckDB type name holds dates or times
+    fn is_temporal_type(data_type: &str) -> bool {
+        let upper = data_type.to_uppercase();
+        upper == "DATE" || upper.starts_with("TIME") // TIME, TIMESTAMP and their variants
+    }
 
-        while let Some(row) = row_iter.next()? {
-            // If we don't have columns yet, infer from first row
-            if first_row && actual_columns.is_empty() {
-                // We'll just use numbered columns as fallback
-                for i in 0..100 {
-                    if row.get::<_, Option<String>>(i).is_ok()
-                        || row.get::<_, Option<i64>>(i).is_ok()
-                        || row.get::<_, Option<f64>>(i).is_ok()
-                    {
-                        actual_columns.push(format!("column_{}", i));
-                    } else {
-                        break;
-                    }
+    /// Fetch a single cell without truncation, identified by primary key or rowid
+    pub fn get_cell_value(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        column: &str,
+        primary_key_value: Option<&Value>,
+        row_id: Option<i64>,
+    ) -> Result<Value> {
+        let schema = self.get_table_schema(conn, table_name)?;
+        if !schema.columns.iter().any(|c| c.name == column) {
+            return Err(AppError::Custom(format!(
+                "Column '{}' not found in table '{}'",
+                column, table_name
+            )));
+        }
+
+        let table = quote_ident(table_name);
+        let column = quote_ident(column);
+
+        let (where_clause, key) = match (primary_key_value, row_id) {
+            (Some(key), _) => {
+                let pk_columns: Vec<&ColumnInfo> =
+                    schema.columns.iter().filter(|c| c.is_primary_key).collect();
+                if pk_columns.len() != 1 {
+                    return Err(AppError::Custom(format!(
+                        "Table '{}' has no single-column primary key; use a rowid instead",
+                        table_name
+                    )));
                 }
-                first_row = false;
+                let key = match key {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (
+                    format!("CAST({} AS VARCHAR) = ?", quote_ident(&pk_columns[0].name)),
+                    key,
+                )
             }
-
-            let mut row_obj = serde_json::Map::new();
-            for (i, col_name) in actual_columns.iter().enumerate() {
-                let value = self.get_value_from_row(row, i);
-                row_obj.insert(col_name.clone(), value);
+            (None, Some(id)) => ("rowid = CAST(? AS BIGINT)".to_string(), id.to_string()),
+            (None, None) => {
+                return Err(AppError::Custom(
+                    "Either a primary key value or a rowid is required".into(),
+                ))
             }
-            rows.push(Value::Object(row_obj));
-        }
+        };
 
-        let execution_time_ms = start.elapsed().as_millis() as u64;
-        let row_count = rows.len();
+        let sql = format!("SELECT {} FROM {} WHERE {} LIMIT 1", column, table, where_clause);
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query([&key])?;
 
-        Ok(QueryResult {
-            columns: actual_columns,
-            rows,
-            row_count,
-            execution_time_ms,
-        })
+        match rows.next()? {
+            Some(row) => Ok(self.get_value_from_row(row, 0)),
+            None => Err(AppError::Custom("Row not found".into())),
+        }
     }
 
     pub fn query_table(
@@ -219,22 +2829,192 @@ impl DuckDbService {
         page_size: u32,
         order_by: Option<&str>,
         order_desc: bool,
+        include_rowid: bool,
     ) -> Result<QueryResult> {
         let offset = page * page_size;
         let order_clause = match order_by {
             Some(col) => {
                 let direction = if order_desc { "DESC" } else { "ASC" };
-                format!(" ORDER BY \"{}\" {}", col.replace("\"", "\"\""), direction)
+                format!(" ORDER BY {} {}", quote_ident(col), direction)
             }
             None => String::new(),
         };
+
+        // Views have no rowid, so those pages come back without one
+        let with_rowid = include_rowid
+            && conn
+                .prepare(&format!("SELECT rowid FROM {} LIMIT 0", quote_ident(table_name)))
+                .is_ok();
+        let projection = if with_rowid { "rowid AS _rowid, *" } else { "*" };
+
+        let sql = format!(
+            "SELECT {} FROM {}{}  LIMIT {} OFFSET {}",
+            projection, quote_ident(table_name), order_clause, page_size, offset
+        );
+        let mut result = self.execute_query(conn, &sql)?;
+        result.has_rowid = with_rowid;
+        Ok(result)
+    }
+
+    /// Read a page of rows ordered by a key column, starting after `after_key`.
+    /// Unlike OFFSET, DuckDB doesn't scan the skipped rows, so deep pages stay fast.
+    /// The key should be unique: rows tied with the last key of a page, and rows
+    /// where the key is NULL, are never returned.
+    pub fn query_table_seek(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        after_key: Option<&Value>,
+        order_by: &str,
+        page_size: u32,
+    ) -> Result<SeekPage> {
+        let data_type: String = conn
+            .query_row(
+                "SELECT data_type FROM information_schema.columns WHERE table_name = ? AND column_name = ? AND table_schema = 'main' AND table_catalog = current_database()",
+                [table_name, order_by],
+                |row| row.get(0),
+            )
+            .map_err(|_| {
+                AppError::Custom(format!("Column {} not found in {}", order_by, table_name))
+            })?;
+
+        let upper = data_type.to_uppercase();
+        if upper.contains('[')
+            || ["STRUCT", "MAP", "UNION", "LIST"].iter().any(|t| upper.starts_with(t))
+        {
+            return Err(AppError::Custom(format!(
+                "Column {} has type {}, which can't be used as a page key",
+                order_by, data_type
+            )));
+        }
+
+        let key = quote_ident(order_by);
+        let seek_clause = match after_key {
+            Some(Value::Null) | None => format!(" WHERE {} IS NOT NULL", key),
+            Some(value) => {
+                let literal = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                format!(
+                    " WHERE {} > CAST('{}' AS {})",
+                    key,
+                    literal.replace('\'', "''"),
+                    data_type
+                )
+            }
+        };
+
+        // One extra row tells whether there is another page
+        let sql = format!(
+            "SELECT * FROM {}{} ORDER BY {} LIMIT {}",
+            quote_ident(table_name),
+            seek_clause,
+            key,
+            page_size as u64 + 1
+        );
+        let mut result = self.execute_query(conn, &sql)?;
+
+        let has_more = result.rows.len() > page_size as usize;
+        result.rows.truncate(page_size as usize);
+        result.row_count = result.rows.len();
+        let last_key = result.rows.last().and_then(|row| row.get(order_by)).cloned();
+
+        Ok(SeekPage {
+            result,
+            last_key,
+            has_more,
+        })
+    }
+
+    /// Return a uniformly random sample of rows. Reservoir sampling makes a single
+    /// pass over the table, so large tables never need a full sort.
+    pub fn get_random_sample(&self, conn: &Connection, table_name: &str, n: u32) -> Result<QueryResult> {
         let sql = format!(
-            "SELECT * FROM \"{}\"{}  LIMIT {} OFFSET {}",
-            table_name, order_clause, page_size, offset
+            "SELECT * FROM {} USING SAMPLE reservoir({} ROWS)",
+            quote_ident(table_name),
+            n
         );
         self.execute_query(conn, &sql)
     }
 
+    /// Count rows in a table, optionally filtered. Unfiltered counts can use the
+    /// estimate DuckDB keeps in its catalog, which avoids scanning huge tables. The
+    /// filter goes through the same checks as a vectorization row filter.
+    pub fn count_rows(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        where_clause: Option<&str>,
+        exact: bool,
+    ) -> Result<RowCount> {
+        let where_clause = where_clause.map(str::trim).filter(|w| !w.is_empty());
+
+        if where_clause.is_none() && !exact {
+            let estimate: Option<i64> = conn
+                .query_row(
+                    r#"
+                    SELECT estimated_size
+                    FROM duckdb_tables()
+                    WHERE table_name = ? AND schema_name = 'main'
+                        AND database_name = current_database()
+                    "#,
+                    [table_name],
+                    |row| row.get(0),
+                )
+                .ok();
+            if let Some(count) = estimate {
+                return Ok(RowCount { count, exact: false });
+            }
+        }
+
+        let mut sql = format!("SELECT COUNT(*) FROM {}", quote_ident(table_name));
+        if let Some(filter) = where_clause {
+            let filter = Self::validate_row_filter(conn, table_name, filter)?;
+            sql.push_str(&format!(" WHERE ({})", filter));
+        }
+        let count: i64 = conn.query_row(&sql, [], |row| row.get(0))?;
+
+        Ok(RowCount { count, exact: true })
+    }
+
+    /// List the most frequent distinct values of a column with their counts.
+    /// One extra row is fetched to tell whether the list was cut off.
+    pub fn get_distinct_values(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        column: &str,
+        limit: u32,
+    ) -> Result<DistinctValues> {
+        let limit = limit.clamp(1, MAX_DISTINCT_VALUES);
+        let sql = format!(
+            "SELECT {col}, COUNT(*) FROM {table} GROUP BY {col} ORDER BY 2 DESC, 1 LIMIT ?",
+            col = quote_ident(column),
+            table = quote_ident(table_name),
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let mut values: Vec<DistinctValue> = stmt
+            .query_map([limit as i64 + 1], |row| {
+                Ok(DistinctValue {
+                    value: self.get_value_from_row(row, 0),
+                    count: row.get(1)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let truncated = values.len() > limit as usize;
+        values.truncate(limit as usize);
+
+        Ok(DistinctValues {
+            column: column.to_string(),
+            values,
+            truncated,
+        })
+    }
+
     fn get_value_from_row(&self, row: &duckdb::Row, idx: usize) -> Value {
         // Try different types
         if let Ok(v) = row.get::<_, Option<i64>>(idx) {
@@ -252,6 +3032,72 @@ impl DuckDbService {
         Value::Null
     }
 
+    /// Whether any table rows or document chunks have stored embeddings
+    pub fn has_embeddings(&self, conn: &Connection) -> bool {
+        // A missing internal table just means nothing was embedded there yet
+        let any =
+            |sql: &str| conn.query_row(sql, [], |row| row.get::<_, bool>(0)).unwrap_or(false);
+
+        any("SELECT EXISTS (SELECT 1 FROM _duckbake_embeddings)")
+            || any("SELECT EXISTS (SELECT 1 FROM _duckbake_document_chunks WHERE embedding IS NOT NULL)")
+    }
+
+    /// Vector length of the embeddings already stored, for projects that never had it
+    /// recorded. None when nothing has been embedded.
+    pub fn stored_embedding_dimension(&self, conn: &Connection) -> Option<usize> {
+        let length = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0)).ok();
+
+        length("SELECT len(embedding) FROM _duckbake_embeddings LIMIT 1")
+            .or_else(|| {
+                length("SELECT len(embedding) FROM _duckbake_document_chunks WHERE embedding IS NOT NULL LIMIT 1")
+            })
+            .map(|len| len as usize)
+    }
+
+    /// Cached summary for a table, if one has been generated
+    pub fn get_table_note(&self, conn: &Connection, table_name: &str) -> Result<Option<TableNote>> {
+        conn.execute_batch(TABLE_NOTES_DDL)?;
+        let note = conn
+            .query_row(
+                r#"
+                SELECT table_name, summary, model, CAST(updated_at AS VARCHAR)
+                FROM _duckbake_table_notes
+                WHERE table_name = ?
+                "#,
+                [table_name],
+                |row| {
+                    Ok(TableNote {
+                        table_name: row.get(0)?,
+                        summary: row.get(1)?,
+                        model: row.get(2)?,
+                        updated_at: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                    })
+                },
+            )
+            .ok();
+        Ok(note)
+    }
+
+    /// Store (or replace) the summary for a table
+    pub fn save_table_note(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        summary: &str,
+        model: &str,
+    ) -> Result<TableNote> {
+        conn.execute_batch(TABLE_NOTES_DDL)?;
+        conn.execute(
+            r#"
+            INSERT OR REPLACE INTO _duckbake_table_notes (table_name, summary, model, updated_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+            [table_name, summary, model],
+        )?;
+        self.get_table_note(conn, table_name)?
+            .ok_or_else(|| AppError::Custom("Failed to save table summary".into()))
+    }
+
     /// Initialize the embeddings table if it doesn't exist
     pub fn init_embeddings_table(&self, conn: &Connection) -> Result<()> {
         // Check if table exists with old schema (had 'id' column) and drop it
@@ -267,26 +3113,201 @@ impl DuckDbService {
             conn.execute_batch("DROP TABLE IF EXISTS _duckbake_embeddings")?;
         }
 
+        conn.execute_batch(EMBEDDINGS_TABLE_DDL)?;
+        conn.execute_batch(VECTOR_CONFIG_DDL)?;
         conn.execute_batch(
             r#"
-            CREATE TABLE IF NOT EXISTS _duckbake_embeddings (
-                table_name VARCHAR NOT NULL,
-                source_column VARCHAR NOT NULL,
-                row_id INTEGER NOT NULL,
-                content TEXT NOT NULL,
-                embedding FLOAT[] NOT NULL,
-                embedding_model VARCHAR NOT NULL,
-                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-                PRIMARY KEY (table_name, source_column, row_id)
-            );
-            CREATE INDEX IF NOT EXISTS idx_embeddings_table
-                ON _duckbake_embeddings(table_name, source_column);
+            ALTER TABLE _duckbake_vector_config ADD COLUMN IF NOT EXISTS last_row_id BIGINT;
+            ALTER TABLE _duckbake_vector_config ADD COLUMN IF NOT EXISTS similarity_metric VARCHAR;
+            ALTER TABLE _duckbake_vector_config ADD COLUMN IF NOT EXISTS normalized BOOLEAN;
+            ALTER TABLE _duckbake_vector_config ADD COLUMN IF NOT EXISTS row_filter VARCHAR;
+            ALTER TABLE _duckbake_vector_config ADD COLUMN IF NOT EXISTS needs_reindex BOOLEAN;
+            "#,
+        )?;
+
+        Self::key_by_embedding_model(conn, "_duckbake_embeddings", EMBEDDINGS_TABLE_DDL)?;
+        Self::key_by_embedding_model(conn, "_duckbake_vector_config", VECTOR_CONFIG_DDL)?;
+        Ok(())
+    }
+
+    /// Tables created before multi-model support had primary keys without the model,
+    /// so a second model's rows would collide. DuckDB can't alter a primary key,
+    /// so rebuild the table from a copy using the current DDL.
+    fn key_by_embedding_model(conn: &Connection, table: &str, ddl: &str) -> Result<()> {
+        let keyed_by_model: bool = conn
+            .query_row(
+                r#"
+                SELECT COALESCE(bool_or(list_contains(constraint_column_names, 'embedding_model')), true)
+                FROM duckdb_constraints()
+                WHERE table_name = ? AND constraint_type = 'PRIMARY KEY'
+                    AND database_name = current_database()
+                "#,
+                [table],
+                |row| row.get(0),
+            )
+            .unwrap_or(true);
+
+        if keyed_by_model {
+            return Ok(());
+        }
+
+        info!(target: "vectorization", "Migrating {} to per-model primary key", table);
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        match Self::key_by_embedding_model_in_transaction(conn, table, ddl) {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn key_by_embedding_model_in_transaction(
+        conn: &Connection,
+        table: &str,
+        ddl: &str,
+    ) -> Result<()> {
+        let backup = format!("{}_backup", table);
+        conn.execute_batch(&format!(
+            r#"
+            CREATE TABLE {backup} AS SELECT * FROM {table};
+            DROP TABLE {table};
+            {ddl}
+            INSERT INTO {table} BY NAME SELECT * FROM {backup};
+            DROP TABLE {backup};
+            "#,
+            backup = backup,
+            table = table,
+            ddl = ddl
+        ))?;
+        Ok(())
+    }
+
+    /// Record (or refresh) the vectorization config for a table + column key,
+    /// including the highest rowid embedded so far as a resume checkpoint and the
+    /// row filter when only some rows are embedded
+    pub fn upsert_vector_config(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        column_name: &str,
+        model: &str,
+        metric: SimilarityMetric,
+        normalized: bool,
+        last_row_id: i64,
+        row_filter: Option<&str>,
+    ) -> Result<()> {
+        conn.execute(
+            r#"
+            INSERT OR REPLACE INTO _duckbake_vector_config
+                (table_name, column_name, embedding_model, vector_column_name, last_updated,
+                 last_row_id, similarity_metric, normalized, row_filter)
+            VALUES (?, ?, ?, 'embedding', CURRENT_TIMESTAMP, ?, ?, ?, ?)
             "#,
+            duckdb::params![
+                table_name,
+                column_name,
+                model,
+                last_row_id,
+                metric.as_str(),
+                normalized,
+                row_filter
+            ],
         )?;
         Ok(())
     }
 
-    /// Store embeddings for a batch of rows
+    /// Get the rowid checkpoint a previous vectorization run reached, if any
+    pub fn get_vectorization_checkpoint(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        column_name: &str,
+        model: &str,
+    ) -> Option<i64> {
+        conn.query_row(
+            r#"
+            SELECT last_row_id
+            FROM _duckbake_vector_config
+            WHERE table_name = ? AND column_name = ? AND embedding_model = ?
+            "#,
+            [table_name, column_name, model],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .ok()
+        .flatten()
+    }
+
+    /// Get the most recently used embedding model for a table, if it has been vectorized
+    pub fn get_embedding_model(&self, conn: &Connection, table_name: &str) -> Option<String> {
+        conn.query_row(
+            r#"
+            SELECT embedding_model
+            FROM _duckbake_vector_config
+            WHERE table_name = ?
+            ORDER BY last_updated DESC
+            LIMIT 1
+            "#,
+            [table_name],
+            |row| row.get(0),
+        )
+        .ok()
+    }
+
+    /// Get the similarity metric recorded for a table's embeddings from a model
+    pub fn get_similarity_metric(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        model: &str,
+    ) -> Option<SimilarityMetric> {
+        conn.query_row(
+            r#"
+            SELECT similarity_metric
+            FROM _duckbake_vector_config
+            WHERE table_name = ? AND embedding_model = ?
+            ORDER BY last_updated DESC
+            LIMIT 1
+            "#,
+            [table_name, model],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .ok()
+        .flatten()
+        .and_then(|m| SimilarityMetric::parse(&m))
+    }
+
+    /// Whether a table's embeddings from a model were normalized to unit length
+    pub fn is_normalized(&self, conn: &Connection, table_name: &str, model: &str) -> bool {
+        conn.query_row(
+            r#"
+            SELECT normalized
+            FROM _duckbake_vector_config
+            WHERE table_name = ? AND embedding_model = ?
+            ORDER BY last_updated DESC
+            LIMIT 1
+            "#,
+            [table_name, model],
+            |row| row.get::<_, Option<bool>>(0),
+        )
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+    }
+
+    /// Scale an embedding to unit (L2) length in place; zero vectors are left untouched
+    pub fn normalize_embedding(embedding: &mut [f32]) {
+        let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for v in embedding.iter_mut() {
+                *v /= norm;
+            }
+        }
+    }
+
+    /// Store embeddings for a batch of rows. The batch and its resume checkpoint are
+    /// committed together or not at all, so a resumed run neither repeats nor skips rows.
     pub fn store_embeddings(
         &self,
         conn: &Connection,
@@ -294,60 +3315,193 @@ impl DuckDbService {
         column_name: &str,
         rows: Vec<(i64, String, Vec<f32>)>, // (row_id, content, embedding)
         model: &str,
+        metric: SimilarityMetric,
+        normalize: bool,
+        row_filter: Option<&str>,
     ) -> Result<()> {
         if rows.is_empty() {
             return Ok(());
         }
 
-        // Build a single INSERT with multiple VALUES for better performance
-        let mut values: Vec<String> = Vec::with_capacity(rows.len());
+        // Highest rowid in this batch becomes the resume checkpoint
+        let last_row_id = rows.iter().map(|(id, _, _)| *id).max().unwrap_or(-1);
 
-        for (row_id, content, embedding) in rows {
-            // Convert Vec<f32> to DuckDB array format
-            let embedding_str = format!(
-                "[{}]",
-                embedding.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(",")
-            );
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = Self::insert_embeddings_in_transaction(
+            conn,
+            table_name,
+            column_name,
+            rows,
+            model,
+            normalize,
+        )
+        .and_then(|()| {
+            self.upsert_vector_config(
+                conn,
+                table_name,
+                column_name,
+                model,
+                metric,
+                normalize,
+                last_row_id,
+                row_filter,
+            )
+        });
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
 
-            // Escape content for SQL
-            let escaped_content = content.replace('\'', "''");
+        Ok(())
+    }
 
-            values.push(format!(
-                "('{}', '{}', {}, '{}', {}::FLOAT[], '{}')",
-                table_name, column_name, row_id, escaped_content, embedding_str, model
-            ));
+    /// Insert the whole batch as one multi-row INSERT, so a batch of rows is a single
+    /// statement rather than a round trip per row
+    fn insert_embeddings_in_transaction(
+        conn: &Connection,
+        table_name: &str,
+        column_name: &str,
+        mut rows: Vec<(i64, String, Vec<f32>)>,
+        model: &str,
+        normalize: bool,
+    ) -> Result<()> {
+        if normalize {
+            for (_, _, embedding) in rows.iter_mut() {
+                Self::normalize_embedding(embedding);
+            }
+        }
+        let embeddings: Vec<EmbeddingParam> = rows
+            .iter()
+            .map(|(_, _, embedding)| EmbeddingParam(embedding))
+            .collect();
+
+        let mut params: Vec<&dyn duckdb::ToSql> = Vec::with_capacity(rows.len() * 6);
+        for ((row_id, content, _), embedding) in rows.iter().zip(&embeddings) {
+            params.extend([
+                &table_name as &dyn duckdb::ToSql,
+                &column_name,
+                row_id,
+                content,
+                embedding,
+                &model,
+            ]);
         }
 
-        // Execute as a single batched insert
         let sql = format!(
-            "INSERT INTO _duckbake_embeddings (table_name, source_column, row_id, content, embedding, embedding_model) VALUES {}",
-            values.join(",")
+            r#"
+            INSERT INTO _duckbake_embeddings
+                (table_name, source_column, row_id, content, embedding, embedding_model)
+            VALUES {}
+            "#,
+            vec![format!("(?, ?, ?, ?, {}, ?)", EMBEDDING_PARAM); rows.len()].join(", ")
         );
-
-        conn.execute_batch(&sql)?;
-
+        conn.execute(&sql, params.as_slice())?;
         Ok(())
     }
 
-    /// Get vectorization status for a table
-    pub fn get_vectorization_status(
-        &self,
-        conn: &Connection,
-        table_name: &str,
-    ) -> Result<VectorizationStatus> {
-        let vectorized_columns = self.get_vectorized_columns(conn, table_name);
+    /// Get vectorization status for a table
+    pub fn get_vectorization_status(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+    ) -> Result<VectorizationStatus> {
+        // Prefer the persisted config; fall back to the embeddings themselves for
+        // tables vectorized before the config was being written
+        let configured_columns: Vec<String> = conn
+            .prepare(
+                r#"
+                SELECT column_name
+                FROM _duckbake_vector_config
+                WHERE table_name = ?
+                ORDER BY column_name
+                "#,
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map([table_name], |row| row.get(0))
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default();
+
+        let vectorized_columns = if configured_columns.is_empty() {
+            self.get_vectorized_columns(conn, table_name, None)
+        } else {
+            configured_columns
+        };
+
+        let (embedding_count, fallback_model, fallback_created_at): (i64, Option<String>, Option<String>) = conn
+            .query_row(
+                r#"
+                SELECT COUNT(*), MAX(embedding_model), CAST(MAX(created_at) AS VARCHAR)
+                FROM _duckbake_embeddings
+                WHERE table_name = ?
+                "#,
+                [table_name],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap_or((0, None, None));
+
+        let embedding_model = self.get_embedding_model(conn, table_name).or(fallback_model);
+
+        let embedding_models: Vec<String> = conn
+            .prepare(
+                r#"
+                SELECT DISTINCT embedding_model
+                FROM _duckbake_embeddings
+                WHERE table_name = ?
+                ORDER BY embedding_model
+                "#,
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map([table_name], |row| row.get(0))
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default();
+
+        // Filtered runs only embed matching rows, so the table is partially covered
+        let row_filter: Option<String> = conn
+            .query_row(
+                r#"
+                SELECT row_filter
+                FROM _duckbake_vector_config
+                WHERE table_name = ?
+                ORDER BY last_updated DESC
+                LIMIT 1
+                "#,
+                [table_name],
+                |row| row.get(0),
+            )
+            .ok()
+            .flatten();
 
-        let (embedding_count, embedding_model): (i64, Option<String>) = conn
+        let last_vectorized_at: Option<String> = conn
             .query_row(
                 r#"
-                SELECT COUNT(*), MAX(embedding_model)
-                FROM _duckbake_embeddings
+                SELECT CAST(MAX(last_updated) AS VARCHAR)
+                FROM _duckbake_vector_config
+                WHERE table_name = ?
+                "#,
+                [table_name],
+                |row| row.get(0),
+            )
+            .ok()
+            .flatten()
+            .or(fallback_created_at);
+
+        // Set by `deduplicate_table`; `upsert_vector_config` clears it per column
+        let needs_reindex: bool = conn
+            .query_row(
+                r#"
+                SELECT COALESCE(bool_or(needs_reindex), false)
+                FROM _duckbake_vector_config
                 WHERE table_name = ?
                 "#,
                 [table_name],
-                |row| Ok((row.get(0)?, row.get(1)?)),
+                |row| row.get(0),
             )
-            .unwrap_or((0, None));
+            .unwrap_or(false);
 
         Ok(VectorizationStatus {
             table_name: table_name.to_string(),
@@ -355,11 +3509,21 @@ impl DuckDbService {
             vectorized_columns,
             embedding_count,
             embedding_model,
+            embedding_models,
+            last_vectorized_at,
+            is_partial: row_filter.is_some(),
+            row_filter,
+            needs_reindex,
         })
     }
 
-    /// Remove vectorization for a table
-    pub fn remove_vectorization(&self, conn: &Connection, table_name: &str) -> Result<()> {
+    /// Remove vectorization for a table, either for every model or just one
+    pub fn remove_vectorization(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        model: Option<&str>,
+    ) -> Result<()> {
         // Check if table exists first
         let exists: bool = conn
             .query_row(
@@ -374,35 +3538,106 @@ impl DuckDbService {
         }
 
         conn.execute(
-            "DELETE FROM _duckbake_embeddings WHERE table_name = ?",
-            [table_name],
+            "DELETE FROM _duckbake_embeddings WHERE table_name = ? AND embedding_model = COALESCE(?, embedding_model)",
+            duckdb::params![table_name, model],
         )?;
+        let _ = conn.execute(
+            "DELETE FROM _duckbake_vector_config WHERE table_name = ? AND embedding_model = COALESCE(?, embedding_model)",
+            duckdb::params![table_name, model],
+        );
         Ok(())
     }
 
+    /// Check a user-supplied row filter is a plain predicate on the table, so it can
+    /// be AND-ed into our own WHERE clauses. Statements, subqueries, comments and
+    /// unbalanced parentheses (which could escape the surrounding parentheses) are
+    /// rejected before DuckDB is asked to bind it.
+    pub fn validate_row_filter(conn: &Connection, table_name: &str, filter: &str) -> Result<String> {
+        let filter = filter.trim();
+        let invalid = |reason: &str| AppError::Custom(format!("Invalid row filter: {}", reason));
+
+        if filter.is_empty() {
+            return Err(invalid("it is empty"));
+        }
+        if filter.contains(';') || filter.contains("--") || filter.contains("/*") {
+            return Err(invalid("semicolons and comments are not allowed"));
+        }
+
+        // Parentheses must balance outside of string literals and quoted identifiers
+        let mut depth = 0i32;
+        let mut quote: Option<char> = None;
+        for c in filter.chars() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') => {
+                    depth -= 1;
+                    if depth < 0 {
+                        return Err(invalid("unbalanced parentheses"));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if depth != 0 || quote.is_some() {
+            return Err(invalid("unbalanced parentheses or quotes"));
+        }
+
+        const FORBIDDEN: [&str; 20] = [
+            "SELECT", "FROM", "WITH", "UNION", "INSERT", "UPDATE", "DELETE", "DROP", "CREATE",
+            "ALTER", "ATTACH", "DETACH", "COPY", "PRAGMA", "INSTALL", "LOAD", "CALL", "EXPORT",
+            "IMPORT", "SET",
+        ];
+        if let Some(keyword) = Self::statement_tokens(filter)
+            .into_iter()
+            .find(|t| FORBIDDEN.contains(&t.as_str()))
+        {
+            return Err(invalid(&format!("{} is not allowed", keyword)));
+        }
+
+        conn.prepare(&format!(
+            "SELECT COUNT(*) FROM {} WHERE ({})",
+            quote_ident(table_name),
+            filter
+        ))
+        .map_err(|e| invalid(&e.to_string()))?;
+
+        Ok(filter.to_string())
+    }
+
     /// Get text content from specified columns for vectorization
+    /// Uses keyset pagination on rowid so later batches don't rescan earlier rows
     pub fn get_text_for_vectorization(
         &self,
         conn: &Connection,
         table_name: &str,
         columns: &[String],
         batch_size: usize,
-        offset: usize,
+        after_row_id: i64,
+        row_filter: Option<&str>,
     ) -> Result<Vec<(i64, String)>> {
         // Combine columns into a single text field
         let column_concat = columns
             .iter()
-            .map(|c| format!("COALESCE(CAST(\"{}\" AS VARCHAR), '')", c))
+            .map(|c| format!("COALESCE(CAST({} AS VARCHAR), '')", quote_ident(c)))
             .collect::<Vec<_>>()
             .join(" || ' ' || ");
 
         let sql = format!(
             r#"
             SELECT rowid, {} as combined_text
-            FROM "{}"
-            LIMIT {} OFFSET {}
+            FROM {}
+            WHERE rowid > {}{}
+            ORDER BY rowid
+            LIMIT {}
             "#,
-            column_concat, table_name, batch_size, offset
+            column_concat,
+            quote_ident(table_name),
+            after_row_id,
+            row_filter.map(|f| format!(" AND ({})", f)).unwrap_or_default(),
+            batch_size
         );
 
         let mut stmt = conn.prepare(&sql)?;
@@ -414,45 +3649,239 @@ impl DuckDbService {
         Ok(rows)
     }
 
-    /// Semantic search using cosine similarity
+    /// Column names and DuckDB types for the top-level properties of a JSON schema.
+    /// Strings with a `date` or `date-time` format get date types; objects and arrays
+    /// are stored as JSON text.
+    pub fn extraction_columns(schema: &Value) -> Result<Vec<(String, &'static str)>> {
+        let properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| {
+                AppError::Custom("Schema must be a JSON schema object with properties".into())
+            })?;
+
+        properties
+            .iter()
+            .map(|(name, property)| {
+                Self::validate_user_column_name(name)?;
+                let format = property.get("format").and_then(Value::as_str);
+                let data_type = match property.get("type").and_then(Value::as_str) {
+                    Some("string") if format == Some("date") => "DATE",
+                    Some("string") if format == Some("date-time") => "TIMESTAMP",
+                    Some("integer") => "BIGINT",
+                    Some("number") => "DOUBLE",
+                    Some("boolean") => "BOOLEAN",
+                    _ => "VARCHAR",
+                };
+                Ok((name.clone(), data_type))
+            })
+            .collect()
+    }
+
+    /// Add any of the extraction columns the table doesn't have yet
+    pub fn add_extraction_columns(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        columns: &[(String, &'static str)],
+    ) -> Result<()> {
+        Self::validate_user_table_name(table_name)?;
+        for (name, data_type) in columns {
+            conn.execute(
+                &format!(
+                    "ALTER TABLE {} ADD COLUMN IF NOT EXISTS {} {}",
+                    quote_ident(table_name),
+                    quote_ident(name),
+                    data_type
+                ),
+                [],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write a batch of extracted objects into their rows in one transaction. Values
+    /// that don't convert to the column type are stored as NULL.
+    pub fn store_extracted_values(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        columns: &[(String, &'static str)],
+        rows: &[(i64, Value)],
+    ) -> Result<()> {
+        let assignments = columns
+            .iter()
+            .map(|(name, data_type)| {
+                format!("{} = TRY_CAST(? AS {})", quote_ident(name), data_type)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "UPDATE {} SET {} WHERE rowid = ?",
+            quote_ident(table_name),
+            assignments
+        );
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = Self::store_extracted_values_in_transaction(conn, &sql, columns, rows);
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn store_extracted_values_in_transaction(
+        conn: &Connection,
+        sql: &str,
+        columns: &[(String, &'static str)],
+        rows: &[(i64, Value)],
+    ) -> Result<()> {
+        let mut stmt = conn.prepare(sql)?;
+        for (row_id, extracted) in rows {
+            // Everything goes in as text and TRY_CAST does the conversion
+            let values: Vec<Option<String>> = columns
+                .iter()
+                .map(|(name, _)| match extracted.get(name) {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(s)) => Some(s.clone()),
+                    Some(other) => Some(other.to_string()),
+                })
+                .collect();
+            let mut params: Vec<&dyn duckdb::ToSql> =
+                values.iter().map(|v| v as &dyn duckdb::ToSql).collect();
+            params.push(row_id);
+            stmt.execute(params.as_slice())?;
+        }
+        Ok(())
+    }
+
+    /// Semantic search scoring stored embeddings with the given metric
     pub fn semantic_search(
         &self,
         conn: &Connection,
         table_name: &str,
+        model: &str,
         query_embedding: &[f32],
         limit: usize,
+        metric: SimilarityMetric,
     ) -> Result<Vec<(i64, String, f64)>> {
-        // Build the embedding array literal
-        let embedding_str = format!(
-            "[{}]",
-            query_embedding.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(",")
-        );
-
         let sql = format!(
             r#"
             SELECT
                 row_id,
                 content,
-                list_cosine_similarity(embedding, {}::FLOAT[]) as similarity
+                {}(embedding, {}) as similarity
             FROM _duckbake_embeddings
-            WHERE table_name = ?
-            ORDER BY similarity DESC
+            WHERE table_name = ? AND embedding_model = ?
+            ORDER BY similarity {}
             LIMIT ?
             "#,
-            embedding_str
+            metric.sql_function(),
+            EMBEDDING_PARAM,
+            metric.order_direction()
         );
 
         let mut stmt = conn.prepare(&sql)?;
         let results: Vec<(i64, String, f64)> = stmt
-            .query_map(duckdb::params![table_name, limit as i64], |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
-            })?
+            .query_map(
+                duckdb::params![
+                    EmbeddingParam(query_embedding),
+                    table_name,
+                    model,
+                    limit as i64
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?
             .filter_map(|r| r.ok())
             .collect();
 
         Ok(results)
     }
 
+    /// Write the full source rows of a semantic search to a file, best match first,
+    /// with the score in a `_similarity` column. Rows matched through several
+    /// vectorized columns are written once, with their best score.
+    #[allow(clippy::too_many_arguments)]
+    pub fn export_semantic_search(
+        &self,
+        conn: &Connection,
+        table_name: &str,
+        model: &str,
+        query_embedding: &[f32],
+        limit: usize,
+        metric: SimilarityMetric,
+        file_path: &str,
+        format: ExportFormat,
+    ) -> Result<ExportResult> {
+        Self::validate_user_table_name(table_name)?;
+        if !Self::table_exists(conn, table_name) {
+            return Err(AppError::TableNotFound(table_name.to_string()));
+        }
+
+        let matches =
+            self.semantic_search(conn, table_name, model, query_embedding, limit, metric)?;
+        let mut seen = std::collections::HashSet::new();
+        let values: Vec<String> = matches
+            .into_iter()
+            .filter(|(row_id, _, _)| seen.insert(*row_id))
+            .enumerate()
+            .map(|(rank, (row_id, _, similarity))| {
+                // Degenerate vectors can score NaN, which isn't a SQL literal
+                let score = if similarity.is_finite() {
+                    similarity.to_string()
+                } else {
+                    "NULL".to_string()
+                };
+                format!("({}, {}, {})", row_id, score, rank)
+            })
+            .collect();
+        let rows_exported = values.len();
+
+        let select = if values.is_empty() {
+            // Still write a file with the table's columns so the export isn't a surprise
+            format!(
+                "SELECT *, NULL::DOUBLE AS _similarity FROM {} LIMIT 0",
+                quote_ident(table_name)
+            )
+        } else {
+            format!(
+                r#"
+                SELECT t.*, m.similarity AS _similarity
+                FROM {} t
+                JOIN (VALUES {}) AS m(row_id, similarity, rank) ON t.rowid = m.row_id
+                ORDER BY m.rank
+                "#,
+                quote_ident(table_name),
+                values.join(", ")
+            )
+        };
+
+        conn.execute_batch(&format!(
+            "COPY ({}) TO '{}' {}",
+            select,
+            file_path.replace('\'', "''"),
+            format.copy_options()
+        ))?;
+
+        info!(
+            target: "vectorization",
+            "Exported {} semantic search match(es) from {} to {}",
+            rows_exported,
+            table_name,
+            file_path
+        );
+
+        Ok(ExportResult {
+            file_path: file_path.to_string(),
+            rows_exported,
+        })
+    }
+
     /// Get text columns from a table (VARCHAR, TEXT types)
     pub fn get_text_columns(&self, conn: &Connection, table_name: &str) -> Result<Vec<String>> {
         let mut stmt = conn.prepare(
@@ -460,7 +3889,7 @@ impl DuckDbService {
             SELECT column_name
             FROM information_schema.columns
             WHERE table_name = ?
-            AND table_schema = 'main'
+            AND table_schema = 'main' AND table_catalog = current_database()
             AND (data_type LIKE '%VARCHAR%' OR data_type LIKE '%TEXT%' OR data_type LIKE '%CHAR%')
             ORDER BY ordinal_position
             "#,
@@ -495,7 +3924,7 @@ impl DuckDbService {
 
         if needs_repair {
             // Repair: recreate tables without FK constraint
-            eprintln!("[duckdb] Repairing document tables to remove FK constraint");
+            info!(target: "duckdb", "Repairing document tables to remove FK constraint");
 
             // Backup and recreate chunks table without FK
             let _ = conn.execute_batch(
@@ -559,15 +3988,85 @@ impl DuckDbService {
 
             CREATE INDEX IF NOT EXISTS idx_documents_project
                 ON _duckbake_documents(project_id);
+
+            ALTER TABLE _duckbake_document_chunks ADD COLUMN IF NOT EXISTS heading_path VARCHAR;
+            ALTER TABLE _duckbake_document_chunks ADD COLUMN IF NOT EXISTS embedded_hash VARCHAR;
+            ALTER TABLE _duckbake_documents ADD COLUMN IF NOT EXISTS source_path VARCHAR;
             "#,
         )?;
+        conn.execute_batch(DOCUMENT_VIEWS_DDL)?;
         Ok(())
     }
 
+    /// Chat context entries for the document views, empty when the project has no documents
+    /// Only metadata gets sample rows; chunk text is too long to be a useful sample
+    pub fn document_view_contexts(&self, conn: &Connection) -> Result<Vec<TableContext>> {
+        self.init_document_tables(conn)?;
+
+        let document_count: i64 =
+            conn.query_row("SELECT COUNT(*) FROM _duckbake_documents", [], |row| row.get(0))?;
+        if document_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut contexts = Vec::new();
+        for (view, with_samples) in [("documents", true), ("document_chunks", false)] {
+            let name = format!("{}.{}", DOCUMENT_VIEW_SCHEMA, view);
+            let row_count: i64 =
+                conn.query_row(&format!("SELECT COUNT(*) FROM {}", name), [], |row| row.get(0))?;
+
+            let mut stmt = conn.prepare(&format!("DESCRIBE {}", name))?;
+            let columns: Vec<ColumnInfo> = stmt
+                .query_map([], |row| {
+                    Ok(ColumnInfo {
+                        name: row.get(0)?,
+                        data_type: row.get(1)?,
+                        nullable: true,
+                        is_primary_key: false,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let sample_rows = if with_samples {
+                self.execute_query(conn, &format!("SELECT * FROM {} LIMIT 3", name))
+                    .ok()
+                    .map(|result| result.rows)
+            } else {
+                None
+            };
+
+            contexts.push(TableContext {
+                name,
+                row_count,
+                columns,
+                sample_rows,
+            });
+        }
+
+        Ok(contexts)
+    }
+
     /// Get all documents for a project (summary info only)
-    pub fn get_documents(&self, conn: &Connection, project_id: &str) -> Result<Vec<DocumentInfo>> {
+    /// When no page size is given every document is returned as a single page
+    pub fn get_documents(
+        &self,
+        conn: &Connection,
+        project_id: &str,
+        page: u32,
+        page_size: Option<u32>,
+    ) -> Result<DocumentPage> {
         self.init_document_tables(conn)?;
 
+        let total: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM _duckbake_documents WHERE project_id = ?",
+            [project_id],
+            |row| row.get(0),
+        )?;
+
+        let page_size = page_size.unwrap_or(total.max(0) as u32);
+        let offset = page as i64 * page_size as i64;
+
         let mut stmt = conn.prepare(
             r#"
             SELECT id, filename, file_type, file_size, page_count, word_count,
@@ -575,11 +4074,12 @@ impl DuckDbService {
             FROM _duckbake_documents
             WHERE project_id = ?
             ORDER BY uploaded_at DESC
+            LIMIT ? OFFSET ?
             "#,
         )?;
 
         let documents: Vec<DocumentInfo> = stmt
-            .query_map([project_id], |row| {
+            .query_map(duckdb::params![project_id, page_size as i64, offset], |row| {
                 Ok(DocumentInfo {
                     id: row.get(0)?,
                     filename: row.get(1)?,
@@ -594,7 +4094,12 @@ impl DuckDbService {
             .filter_map(|r| r.ok())
             .collect();
 
-        Ok(documents)
+        Ok(DocumentPage {
+            documents,
+            total,
+            page,
+            page_size,
+        })
     }
 
     /// Get a single document with full content
@@ -603,7 +4108,7 @@ impl DuckDbService {
             r#"
             SELECT id, project_id, filename, file_type, file_size, page_count, word_count,
                    title, author, creation_date, headings, content,
-                   CAST(uploaded_at AS VARCHAR) as uploaded_at, is_vectorized
+                   CAST(uploaded_at AS VARCHAR) as uploaded_at, is_vectorized, source_path
             FROM _duckbake_documents
             WHERE id = ?
             "#,
@@ -625,6 +4130,7 @@ impl DuckDbService {
                 content: row.get(11)?,
                 uploaded_at: row.get::<_, Option<String>>(12)?.unwrap_or_default(),
                 is_vectorized: row.get(13)?,
+                source_path: row.get(14)?,
             })
         })
         .map_err(|e| AppError::Custom(format!("Document not found: {}", e)))
@@ -641,8 +4147,9 @@ impl DuckDbService {
             r#"
             INSERT INTO _duckbake_documents
                 (id, project_id, filename, file_type, file_size, page_count, word_count,
-                 title, author, creation_date, headings, content, uploaded_at, is_vectorized)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 title, author, creation_date, headings, content, uploaded_at, is_vectorized,
+                 source_path)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             duckdb::params![
                 doc.id,
@@ -659,6 +4166,7 @@ impl DuckDbService {
                 doc.content,
                 doc.uploaded_at,
                 doc.is_vectorized,
+                doc.source_path,
             ],
         )?;
 
@@ -675,8 +4183,13 @@ impl DuckDbService {
 
         for chunk in chunks {
             let escaped_content = chunk.content.replace('\'', "''");
+            let heading_path = chunk
+                .heading_path
+                .as_ref()
+                .map(|p| format!("'{}'", p.replace('\'', "''")))
+                .unwrap_or_else(|| "NULL".to_string());
             values.push(format!(
-                "('{}', '{}', {}, '{}', '{}', {}, {})",
+                "('{}', '{}', {}, '{}', '{}', {}, {}, {})",
                 chunk.id,
                 chunk.document_id,
                 chunk.chunk_index,
@@ -684,13 +4197,14 @@ impl DuckDbService {
                 escaped_content,
                 chunk.start_offset,
                 chunk.end_offset,
+                heading_path,
             ));
         }
 
         let sql = format!(
             r#"
             INSERT INTO _duckbake_document_chunks
-                (id, document_id, chunk_index, chunk_type, content, start_offset, end_offset)
+                (id, document_id, chunk_index, chunk_type, content, start_offset, end_offset, heading_path)
             VALUES {}
             "#,
             values.join(",")
@@ -708,7 +4222,8 @@ impl DuckDbService {
     ) -> Result<Vec<DocumentChunk>> {
         let mut stmt = conn.prepare(
             r#"
-            SELECT id, document_id, chunk_index, chunk_type, content, start_offset, end_offset
+            SELECT id, document_id, chunk_index, chunk_type, content, start_offset, end_offset,
+                   heading_path
             FROM _duckbake_document_chunks
             WHERE document_id = ?
             ORDER BY chunk_index
@@ -725,6 +4240,7 @@ impl DuckDbService {
                     content: row.get(4)?,
                     start_offset: row.get(5)?,
                     end_offset: row.get(6)?,
+                    heading_path: row.get(7)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -733,6 +4249,103 @@ impl DuckDbService {
         Ok(chunks)
     }
 
+    /// Chunks of a document whose embedding is missing, was made with a different model,
+    /// or was made from different content, along with the document's total chunk count.
+    /// Chunks embedded before content hashes were recorded count as unchanged.
+    pub fn get_stale_document_chunks(
+        &self,
+        conn: &Connection,
+        document_id: &str,
+        model: &str,
+    ) -> Result<(Vec<DocumentChunk>, i64)> {
+        let total: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM _duckbake_document_chunks WHERE document_id = ?",
+            [document_id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, document_id, chunk_index, chunk_type, content, start_offset, end_offset,
+                   heading_path
+            FROM _duckbake_document_chunks
+            WHERE document_id = ?
+              AND (embedding IS NULL
+                   OR embedding_model IS DISTINCT FROM ?
+                   OR embedded_hash <> md5(content))
+            ORDER BY chunk_index
+            "#,
+        )?;
+
+        let chunks: Vec<DocumentChunk> = stmt
+            .query_map(duckdb::params![document_id, model], |row| {
+                Ok(DocumentChunk {
+                    id: row.get(0)?,
+                    document_id: row.get(1)?,
+                    chunk_index: row.get(2)?,
+                    chunk_type: row.get(3)?,
+                    content: row.get(4)?,
+                    start_offset: row.get(5)?,
+                    end_offset: row.get(6)?,
+                    heading_path: row.get(7)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok((chunks, total))
+    }
+
+    /// One page of a document's chunks in order, with the total chunk count
+    pub fn get_document_chunks_page(
+        &self,
+        conn: &Connection,
+        document_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> Result<DocumentChunkPage> {
+        let total: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM _duckbake_document_chunks WHERE document_id = ?",
+            [document_id],
+            |row| row.get(0),
+        )?;
+
+        let offset = page as i64 * page_size as i64;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, document_id, chunk_index, chunk_type, content, start_offset, end_offset,
+                   heading_path
+            FROM _duckbake_document_chunks
+            WHERE document_id = ?
+            ORDER BY chunk_index
+            LIMIT ? OFFSET ?
+            "#,
+        )?;
+
+        let chunks: Vec<DocumentChunk> = stmt
+            .query_map(duckdb::params![document_id, page_size as i64, offset], |row| {
+                Ok(DocumentChunk {
+                    id: row.get(0)?,
+                    document_id: row.get(1)?,
+                    chunk_index: row.get(2)?,
+                    chunk_type: row.get(3)?,
+                    content: row.get(4)?,
+                    start_offset: row.get(5)?,
+                    end_offset: row.get(6)?,
+                    heading_path: row.get(7)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(DocumentChunkPage {
+            chunks,
+            total,
+            page,
+            page_size,
+        })
+    }
+
     /// Store embeddings for document chunks
     pub fn store_document_chunk_embeddings(
         &self,
@@ -740,23 +4353,18 @@ impl DuckDbService {
         chunk_embeddings: Vec<(String, Vec<f32>)>, // (chunk_id, embedding)
         model: &str,
     ) -> Result<()> {
+        // The content hash lets `get_stale_document_chunks` spot chunks edited since they
+        // were embedded
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            UPDATE _duckbake_document_chunks
+            SET embedding = {}, embedding_model = ?, embedded_hash = md5(content)
+            WHERE id = ?
+            "#,
+            EMBEDDING_PARAM
+        ))?;
         for (chunk_id, embedding) in chunk_embeddings {
-            let embedding_str = format!(
-                "[{}]",
-                embedding
-                    .iter()
-                    .map(|f| f.to_string())
-                    .collect::<Vec<_>>()
-                    .join(",")
-            );
-
-            conn.execute(
-                &format!(
-                    "UPDATE _duckbake_document_chunks SET embedding = {}::FLOAT[], embedding_model = ? WHERE id = ?",
-                    embedding_str
-                ),
-                duckdb::params![model, chunk_id],
-            )?;
+            stmt.execute(duckdb::params![EmbeddingParam(&embedding), model, chunk_id])?;
         }
 
         Ok(())
@@ -771,45 +4379,51 @@ impl DuckDbService {
         Ok(())
     }
 
-    /// Semantic search across documents
+    /// Semantic search across documents. Only chunks embedded with `model` are
+    /// compared, since vectors from another model with the same dimension would
+    /// still score, just meaninglessly.
     pub fn semantic_search_documents(
         &self,
         conn: &Connection,
         project_id: &str,
+        model: &str,
         query_embedding: &[f32],
         limit: usize,
-    ) -> Result<Vec<(String, String, String, f64)>> {
-        // (doc_id, doc_name, chunk_content, similarity)
-        let embedding_str = format!(
-            "[{}]",
-            query_embedding
-                .iter()
-                .map(|f| f.to_string())
-                .collect::<Vec<_>>()
-                .join(",")
-        );
-
+        offset: usize,
+        metric: SimilarityMetric,
+    ) -> Result<Vec<(String, String, String, Option<String>, f64)>> {
+        // (doc_id, doc_name, chunk_content, heading_path, similarity)
         let sql = format!(
             r#"
             SELECT
                 d.id,
                 d.filename,
                 c.content,
-                list_cosine_similarity(c.embedding, {}::FLOAT[]) as similarity
+                c.heading_path,
+                {}(c.embedding, {}) as similarity
             FROM _duckbake_document_chunks c
             JOIN _duckbake_documents d ON c.document_id = d.id
-            WHERE d.project_id = ? AND c.embedding IS NOT NULL
-            ORDER BY similarity DESC
-            LIMIT ?
+            WHERE d.project_id = ? AND c.embedding IS NOT NULL AND c.embedding_model = ?
+            ORDER BY similarity {}
+            LIMIT ? OFFSET ?
             "#,
-            embedding_str
+            metric.sql_function(),
+            EMBEDDING_PARAM,
+            metric.order_direction()
         );
 
         let mut stmt = conn.prepare(&sql)?;
         let results = stmt
-            .query_map(duckdb::params![project_id, limit as i64], |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
-            })?
+            .query_map(
+                duckdb::params![
+                    EmbeddingParam(query_embedding),
+                    project_id,
+                    model,
+                    limit as i64,
+                    offset as i64
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )?
             .filter_map(|r| r.ok())
             .collect();
 
@@ -842,4 +4456,135 @@ impl DuckDbService {
         )?;
         Ok(())
     }
+
+    /// Delete several documents and their chunks (and chunk embeddings) in one transaction.
+    /// Unknown ids are reported as failures; a database error rolls back every deletion
+    /// and is reported against each id rather than failing the whole call.
+    pub fn delete_documents(
+        &self,
+        conn: &Connection,
+        document_ids: &[String],
+    ) -> Result<Vec<BulkDocumentResult>> {
+        self.init_document_tables(conn)?;
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let mut results = Vec::with_capacity(document_ids.len());
+        for document_id in document_ids {
+            let outcome = conn
+                .execute(
+                    "DELETE FROM _duckbake_document_chunks WHERE document_id = ?",
+                    [document_id],
+                )
+                .and_then(|_| {
+                    conn.execute("DELETE FROM _duckbake_documents WHERE id = ?", [document_id])
+                });
+
+            match outcome {
+                Ok(0) => results.push(BulkDocumentResult {
+                    document_id: document_id.clone(),
+                    success: false,
+                    error: Some("Document not found".into()),
+                }),
+                Ok(_) => results.push(BulkDocumentResult {
+                    document_id: document_id.clone(),
+                    success: true,
+                    error: None,
+                }),
+                Err(e) => {
+                    let _ = conn.execute_batch("ROLLBACK");
+                    warn!(target: "documents", "Bulk delete rolled back: {}", e);
+                    return Ok(document_ids
+                        .iter()
+                        .map(|id| BulkDocumentResult {
+                            document_id: id.clone(),
+                            success: false,
+                            error: Some(format!("Rolled back: {}", e)),
+                        })
+                        .collect());
+                }
+            }
+        }
+        conn.execute_batch("COMMIT")?;
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_ident_handles_spaces_quotes_and_unicode() {
+        assert_eq!(quote_ident("order items"), "\"order items\"");
+        assert_eq!(quote_ident("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote_ident("données été"), "\"données été\"");
+    }
+
+    #[test]
+    fn quoted_identifiers_round_trip_through_duckdb() {
+        let conn = Connection::open_in_memory().unwrap();
+        for name in ["order items", "say \"hi\"", "売上 2024"] {
+            conn.execute_batch(&format!(
+                "CREATE TABLE {} ({} INTEGER)",
+                quote_ident(name),
+                quote_ident(name)
+            ))
+            .unwrap();
+            let column: String = conn
+                .query_row(
+                    "SELECT column_name FROM information_schema.columns WHERE table_name = ?",
+                    [name],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(column, name);
+        }
+    }
+
+    #[test]
+    fn validators_allow_spaces_and_unicode_but_not_quotes() {
+        for name in ["order items", "données été", "売上"] {
+            assert!(DuckDbService::validate_user_table_name(name).is_ok(), "{}", name);
+            assert!(DuckDbService::validate_user_column_name(name).is_ok(), "{}", name);
+        }
+        for name in ["say \"hi\"", "it's", "a;b", "  ", "_DuckBake_notes"] {
+            assert!(DuckDbService::validate_user_table_name(name).is_err(), "{}", name);
+            assert!(DuckDbService::validate_user_column_name(name).is_err(), "{}", name);
+        }
+        assert!(DuckDbService::validate_user_column_name("a+b").is_err());
+    }
+
+    #[test]
+    fn embedding_batch_is_inserted_as_one_statement() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(EMBEDDINGS_TABLE_DDL).unwrap();
+        let batch = |ids: &[i64]| -> Vec<(i64, String, Vec<f32>)> {
+            ids.iter().map(|id| (*id, format!("row {}", id), vec![*id as f32, 1.0])).collect()
+        };
+        let count = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM _duckbake_embeddings", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        let ids: Vec<i64> = (1..=50).collect();
+        DuckDbService::insert_embeddings_in_transaction(&conn, "t", "c", batch(&ids), "m", false)
+            .unwrap();
+        assert_eq!(count(&conn), 50);
+
+        // A duplicate key at the end of the batch fails the single INSERT, so none of
+        // the rows before it are written either
+        let mut ids: Vec<i64> = (51..=99).collect();
+        ids.push(1);
+        let result = DuckDbService::insert_embeddings_in_transaction(
+            &conn,
+            "t",
+            "c",
+            batch(&ids),
+            "m",
+            false,
+        );
+        assert!(result.is_err());
+        assert_eq!(count(&conn), 50);
+    }
 }
@@ -1,9 +1,14 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
 use std::path::Path;
 
 use duckdb::Connection;
 use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
 
 use crate::error::{AppError, Result};
+use crate::services::{quote_ident, DuckDbService, RESERVED_TABLE_PREFIX};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,6 +18,10 @@ pub struct ImportPreview {
     pub columns: Vec<PreviewColumn>,
     pub sample_rows: Vec<Vec<serde_json::Value>>,
     pub total_rows_estimate: Option<i64>,
+    pub detected_delimiter: Option<String>,
+    pub detected_has_header: Option<bool>,
+    pub detected_date_format: Option<String>,
+    pub duplicate_columns: Vec<String>, // Names that clash with an earlier column, ignoring case
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +29,21 @@ pub struct ImportPreview {
 pub struct PreviewColumn {
     pub name: String,
     pub inferred_type: String,
+    pub null_rate: f64,
+    pub distinct_estimate: i64,
+}
+
+/// The raw head of a file, for diagnosing formats the import preview can't read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilePeek {
+    pub file_name: String,
+    pub file_size: u64,
+    pub lines: Vec<String>, // A binary file's head comes back as a single lossy line
+    pub encoding: String, // "utf-8", "utf-8-bom", "utf-16le", "utf-16be", "ascii" or "unknown"
+    pub line_ending: Option<String>, // "lf", "crlf", "cr" or "mixed"; None for a single line
+    pub is_binary: bool,
+    pub truncated: bool, // More of the file follows what was returned
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +52,16 @@ pub struct ImportResult {
     pub table_name: String,
     pub rows_imported: i64,
     pub columns_count: usize,
+    pub tables_created: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportValidation {
+    pub valid: bool,
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+    pub rows_to_import: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +72,41 @@ pub enum ImportMode {
     Append,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOptions {
+    /// Rename clashing column names to `col_1`, `col_2`, ... instead of failing
+    #[serde(default)]
+    pub dedupe_columns: bool,
+    /// Add an auto-incrementing `_row_id` primary key column
+    #[serde(default)]
+    pub add_row_id: bool,
+    /// Add an `_imported_at` column recording when each row was imported
+    #[serde(default)]
+    pub add_import_timestamp: bool,
+}
+
+/// File size above which preview column stats are computed from a sample
+const STATS_SAMPLE_THRESHOLD_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Surrogate key column added by `ImportOptions::add_row_id`
+pub const ROW_ID_COLUMN: &str = "_row_id";
+
+/// Import time column added by `ImportOptions::add_import_timestamp`
+pub const IMPORTED_AT_COLUMN: &str = "_imported_at";
+
+/// UTF-8 byte order mark, as written by Excel and other Windows tools
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// How much of a delimited file is read to inspect its header line
+const HEADER_PROBE_BYTES: u64 = 64 * 1024;
+
+/// Most of a file `peek_file` reads, however many lines are asked for
+const MAX_PEEK_BYTES: u64 = 256 * 1024;
+
+/// How much of a binary file `peek_file` shows
+const BINARY_PEEK_BYTES: usize = 1024;
+
 pub struct FileParser;
 
 impl FileParser {
@@ -57,6 +126,7 @@ impl FileParser {
             "jsonl" | "ndjson" => Ok("jsonl".into()),
             "parquet" | "pq" => Ok("parquet".into()),
             "xlsx" | "xls" => Ok("excel".into()),
+            "sql" => Ok("sql".into()),
             _ => Err(AppError::Custom(format!(
                 "Unsupported file type: {}",
                 extension
@@ -65,6 +135,7 @@ impl FileParser {
     }
 
     /// Generate a preview of the file using DuckDB's sniffing capabilities
+    /// The row count is left out since it requires a full scan; see `estimate_row_count`
     pub fn preview_file(conn: &Connection, file_path: &str) -> Result<ImportPreview> {
         let file_type = Self::detect_file_type(file_path)?;
         let file_name = Path::new(file_path)
@@ -73,8 +144,23 @@ impl FileParser {
             .unwrap_or("unknown")
             .to_string();
 
+        // SQL scripts have no tabular shape to preview until they're executed
+        if file_type == "sql" {
+            return Ok(ImportPreview {
+                file_name,
+                file_type,
+                columns: vec![],
+                sample_rows: vec![],
+                total_rows_estimate: None,
+                detected_delimiter: None,
+                detected_has_header: None,
+                detected_date_format: None,
+                duplicate_columns: vec![],
+            });
+        }
+
         // Use DuckDB to read and preview the file
-        let read_sql = Self::build_read_sql(&file_type, file_path)?;
+        let read_sql = Self::build_read_sql(conn, &file_type, file_path)?;
 
         // Get column info using DESCRIBE
         let describe_sql = format!("DESCRIBE SELECT * FROM {}", read_sql);
@@ -88,9 +174,14 @@ impl FileParser {
             columns.push(PreviewColumn {
                 name,
                 inferred_type: dtype,
+                null_rate: 0.0,
+                distinct_estimate: 0,
             });
         }
 
+        let duplicate_columns =
+            Self::find_duplicate_columns(columns.iter().map(|c| c.name.as_str()));
+
         // Get sample rows (first 10)
         let sample_sql = format!("SELECT * FROM {} LIMIT 10", read_sql);
         let mut stmt = conn.prepare(&sample_sql)?;
@@ -109,83 +200,875 @@ impl FileParser {
             sample_rows.push(row_values);
         }
 
-        // Try to get row count estimate
-        let count_sql = format!("SELECT COUNT(*) FROM {}", read_sql);
-        let total_rows_estimate = conn
-            .query_row(&count_sql, [], |row| row.get::<_, i64>(0))
-            .ok();
+        // Fill in per-column null rate and distinct estimate, sampling large files
+        let is_large = fs::metadata(file_path)
+            .map(|m| m.len() > STATS_SAMPLE_THRESHOLD_BYTES)
+            .unwrap_or(true);
+        Self::compute_column_stats(conn, &read_sql, &mut columns, is_large);
+
+        // Surface what the CSV sniffer decided so the user can correct it
+        let (detected_delimiter, detected_has_header, detected_date_format) =
+            Self::sniff_csv(conn, &file_type, file_path);
 
         Ok(ImportPreview {
             file_name,
             file_type,
             columns,
             sample_rows,
-            total_rows_estimate,
+            total_rows_estimate: None,
+            detected_delimiter,
+            detected_has_header,
+            detected_date_format,
+            duplicate_columns,
+        })
+    }
+
+    /// Column names that repeat an earlier name, compared case-insensitively
+    /// since DuckDB identifiers are case-insensitive
+    fn find_duplicate_columns<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+        let mut seen = HashSet::new();
+        names
+            .filter(|name| !seen.insert(name.to_lowercase()))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    /// Make column names unique, either by renaming clashes or by failing with a clear error
+    /// Renamed columns are selected by position, since their names are ambiguous
+    fn dedupe_read_sql(conn: &Connection, read_sql: String, dedupe: bool) -> Result<String> {
+        let describe_sql = format!("DESCRIBE SELECT * FROM {}", read_sql);
+        let columns = Self::describe_columns(conn, &describe_sql)?;
+        let duplicates = Self::find_duplicate_columns(columns.iter().map(|(n, _)| n.as_str()));
+        if duplicates.is_empty() {
+            return Ok(read_sql);
+        }
+
+        if !dedupe {
+            return Err(AppError::Custom(format!(
+                "File has duplicate column names: {}. Enable column deduplication to rename them",
+                duplicates.join(", ")
+            )));
+        }
+
+        let mut taken = HashSet::new();
+        let mut select_list = Vec::with_capacity(columns.len());
+        for (i, (name, _)) in columns.iter().enumerate() {
+            let mut unique = name.clone();
+            let mut suffix = 1;
+            while !taken.insert(unique.to_lowercase()) {
+                unique = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            select_list.push(format!("#{} AS {}", i + 1, quote_ident(&unique)));
+        }
+
+        Ok(format!("(SELECT {} FROM {})", select_list.join(", "), read_sql))
+    }
+
+    /// Count the rows in a file with a full scan
+    /// Returns None for SQL scripts, which have no rows until executed
+    pub fn estimate_row_count(conn: &Connection, file_path: &str) -> Result<Option<i64>> {
+        let file_type = Self::detect_file_type(file_path)?;
+        if file_type == "sql" {
+            return Ok(None);
+        }
+
+        let read_sql = Self::build_read_sql(conn, &file_type, file_path)?;
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM {}", read_sql),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(Some(count))
+    }
+
+    /// Check that a file would import cleanly without persisting anything
+    /// The full import runs inside a transaction that is always rolled back
+    pub fn validate_import(
+        conn: &Connection,
+        file_path: &str,
+        table_name: &str,
+        mode: ImportMode,
+        options: &ImportOptions,
+    ) -> Result<ImportValidation> {
+        let file_type = Self::detect_file_type(file_path)?;
+
+        let invalid = |error: String, warnings: Vec<String>| ImportValidation {
+            valid: false,
+            error: Some(error),
+            warnings,
+            rows_to_import: None,
+        };
+
+        let mut warnings = Vec::new();
+
+        // SQL scripts have no source schema to compare, so only the dry run applies
+        if file_type != "sql" {
+            if let Err(e) = DuckDbService::validate_user_table_name(table_name) {
+                return Ok(invalid(e.to_string(), vec![]));
+            }
+
+            let read_sql = Self::build_read_sql(conn, &file_type, file_path)?;
+
+            let source_columns = match Self::describe_columns(
+                conn,
+                &format!("DESCRIBE SELECT * FROM {}", read_sql),
+            ) {
+                Ok(cols) => cols,
+                Err(e) => return Ok(invalid(format!("Failed to read file: {}", e), vec![])),
+            };
+
+            if let ImportMode::Append = mode {
+                if !DuckDbService::table_exists(conn, table_name) {
+                    return Ok(invalid(
+                        AppError::TableNotFound(table_name.to_string()).to_string(),
+                        warnings,
+                    ));
+                }
+
+                // Generated columns are filled by their defaults, not from the file
+                let target_columns: Vec<(String, String)> = Self::describe_columns(
+                    conn,
+                    &format!("DESCRIBE {}", quote_ident(table_name)),
+                )
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(name, _)| !Self::is_generated_column(name))
+                .collect();
+
+                if target_columns.len() != source_columns.len() {
+                    return Ok(invalid(
+                        format!(
+                            "File has {} columns but table '{}' has {}",
+                            source_columns.len(),
+                            table_name,
+                            target_columns.len()
+                        ),
+                        warnings,
+                    ));
+                }
+
+                // Columns are inserted by position, so flag any type or name drift
+                for ((src_name, src_type), (dst_name, dst_type)) in
+                    source_columns.iter().zip(target_columns.iter())
+                {
+                    if src_type != dst_type {
+                        warnings.push(format!(
+                            "Column '{}' ({}) will be coerced to {} in '{}'",
+                            src_name, src_type, dst_type, dst_name
+                        ));
+                    } else if src_name != dst_name {
+                        warnings.push(format!(
+                            "Column '{}' will be inserted into '{}'",
+                            src_name, dst_name
+                        ));
+                    }
+                }
+            }
+        }
+
+        // A script that ends the transaction itself would commit for real, so vet it
+        // before starting the dry run
+        if file_type == "sql" {
+            if let Err(e) = Self::read_sql_script(file_path) {
+                return Ok(invalid(e.to_string(), warnings));
+            }
+        }
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = if file_type == "sql" {
+            Self::import_sql_script(conn, file_path, true)
+        } else {
+            Self::import_file(conn, file_path, table_name, mode, options)
+        };
+        conn.execute_batch("ROLLBACK")?;
+
+        Ok(match result {
+            Ok(imported) => ImportValidation {
+                valid: true,
+                error: None,
+                warnings,
+                rows_to_import: Some(imported.rows_imported),
+            },
+            Err(e) => invalid(e.to_string(), warnings),
         })
     }
 
+    /// Run a DESCRIBE statement and return (column_name, column_type) pairs
+    fn describe_columns(conn: &Connection, describe_sql: &str) -> Result<Vec<(String, String)>> {
+        let mut stmt = conn.prepare(describe_sql)?;
+        let columns = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(columns)
+    }
+
     /// Import file into a DuckDB table
     pub fn import_file(
         conn: &Connection,
         file_path: &str,
         table_name: &str,
         mode: ImportMode,
+        options: &ImportOptions,
     ) -> Result<ImportResult> {
         let file_type = Self::detect_file_type(file_path)?;
-        let read_sql = Self::build_read_sql(&file_type, file_path)?;
+        if file_type == "sql" {
+            return Self::import_sql_script(conn, file_path, false);
+        }
+        DuckDbService::validate_user_table_name(table_name)?;
+        let read_sql = Self::build_read_sql(conn, &file_type, file_path)?;
+        let read_sql = Self::dedupe_read_sql(conn, read_sql, options.dedupe_columns)?;
+        let table = quote_ident(table_name);
 
         // Handle import mode
         match mode {
-            ImportMode::Create => {
+            ImportMode::Create | ImportMode::Replace => {
                 // Drop if exists, then create
-                let _ = conn.execute(&format!("DROP TABLE IF EXISTS \"{}\"", table_name), []);
-                let create_sql = format!(
-                    "CREATE TABLE \"{}\" AS SELECT * FROM {}",
-                    table_name, read_sql
-                );
-                conn.execute(&create_sql, [])?;
-            }
-            ImportMode::Replace => {
-                // Truncate and insert
-                let _ = conn.execute(&format!("DROP TABLE IF EXISTS \"{}\"", table_name), []);
-                let create_sql = format!(
-                    "CREATE TABLE \"{}\" AS SELECT * FROM {}",
-                    table_name, read_sql
-                );
-                conn.execute(&create_sql, [])?;
+                let _ = conn.execute(&format!("DROP TABLE IF EXISTS {}", table), []);
+                if options.add_row_id || options.add_import_timestamp {
+                    Self::create_table_with_generated_columns(
+                        conn, table_name, &read_sql, options,
+                    )?;
+                } else {
+                    let create_sql =
+                        format!("CREATE TABLE {} AS SELECT * FROM {}", table, read_sql);
+                    conn.execute(&create_sql, [])?;
+                }
             }
             ImportMode::Append => {
-                // Insert into existing table
-                let insert_sql = format!(
-                    "INSERT INTO \"{}\" SELECT * FROM {}",
-                    table_name, read_sql
-                );
+                if !DuckDbService::table_exists(conn, table_name) {
+                    return Err(AppError::TableNotFound(table_name.to_string()));
+                }
+
+                // Insert into existing table, leaving generated columns to their defaults
+                let target_columns =
+                    Self::describe_columns(conn, &format!("DESCRIBE {}", table))?;
+                let has_generated = target_columns.iter().any(|(n, _)| Self::is_generated_column(n));
+                let insert_sql = if has_generated {
+                    let column_list = target_columns
+                        .iter()
+                        .filter(|(name, _)| !Self::is_generated_column(name))
+                        .map(|(name, _)| quote_ident(name))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("INSERT INTO {} ({}) SELECT * FROM {}", table, column_list, read_sql)
+                } else {
+                    format!("INSERT INTO {} SELECT * FROM {}", table, read_sql)
+                };
                 conn.execute(&insert_sql, [])?;
             }
         }
 
         // Get final row count and column count
         let row_count: i64 = conn.query_row(
-            &format!("SELECT COUNT(*) FROM \"{}\"", table_name),
+            &format!("SELECT COUNT(*) FROM {}", table),
             [],
             |row| row.get(0),
         )?;
 
         let column_count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM information_schema.columns WHERE table_name = ? AND table_schema = 'main'",
+            "SELECT COUNT(*) FROM information_schema.columns WHERE table_name = ? AND table_schema = 'main' AND table_catalog = current_database()",
             [table_name],
             |row| row.get(0),
         )?;
 
+        let tables_created = match mode {
+            ImportMode::Append => vec![],
+            _ => vec![table_name.to_string()],
+        };
+
         Ok(ImportResult {
             table_name: table_name.to_string(),
             rows_imported: row_count,
             columns_count: column_count as usize,
+            tables_created,
+        })
+    }
+
+    fn is_generated_column(name: &str) -> bool {
+        name == ROW_ID_COLUMN || name == IMPORTED_AT_COLUMN
+    }
+
+    /// Create a table with explicit DDL so `_row_id` can be a sequence-backed primary key
+    /// and `_imported_at` a defaulted timestamp, then fill it from the file
+    fn create_table_with_generated_columns(
+        conn: &Connection,
+        table_name: &str,
+        read_sql: &str,
+        options: &ImportOptions,
+    ) -> Result<()> {
+        let source_columns =
+            Self::describe_columns(conn, &format!("DESCRIBE SELECT * FROM {}", read_sql))?;
+        let clash = source_columns.iter().find(|(n, _)| Self::is_generated_column(n));
+        if let Some((name, _)) = clash {
+            return Err(AppError::Custom(format!(
+                "File already has a '{}' column, so it can't be generated",
+                name
+            )));
+        }
+
+        let mut definitions = Vec::with_capacity(source_columns.len() + 2);
+        if options.add_row_id {
+            let sequence = quote_ident(&format!("_duckbake_seq_{}", table_name));
+            conn.execute(&format!("CREATE OR REPLACE SEQUENCE {}", sequence), [])?;
+            definitions.push(format!(
+                "{} BIGINT PRIMARY KEY DEFAULT nextval('{}')",
+                quote_ident(ROW_ID_COLUMN),
+                sequence.replace('\'', "''")
+            ));
+        }
+        definitions.extend(
+            source_columns
+                .iter()
+                .map(|(name, dtype)| format!("{} {}", quote_ident(name), dtype)),
+        );
+        if options.add_import_timestamp {
+            definitions.push(format!(
+                "{} TIMESTAMP DEFAULT current_timestamp",
+                quote_ident(IMPORTED_AT_COLUMN)
+            ));
+        }
+
+        let table = quote_ident(table_name);
+        conn.execute(
+            &format!("CREATE TABLE {} ({})", table, definitions.join(", ")),
+            [],
+        )?;
+
+        let column_list = source_columns
+            .iter()
+            .map(|(name, _)| quote_ident(name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        conn.execute(
+            &format!("INSERT INTO {} ({}) SELECT * FROM {}", table, column_list, read_sql),
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Run a .sql script and report which tables it created and how many rows it added.
+    /// The script runs in its own transaction, so a failure partway leaves nothing
+    /// behind; `in_transaction` is set when the caller (the dry run) already opened one.
+    fn import_sql_script(
+        conn: &Connection,
+        file_path: &str,
+        in_transaction: bool,
+    ) -> Result<ImportResult> {
+        let script = Self::read_sql_script(file_path)?;
+        if in_transaction {
+            return Self::run_sql_script(conn, &script);
+        }
+
+        // `check_sql_script` rejects transaction control, so the script can't end this
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        match Self::run_sql_script(conn, &script) {
+            Ok(result) => {
+                conn.execute_batch("COMMIT")?;
+                Ok(result)
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                Err(e)
+            }
+        }
+    }
+
+    fn run_sql_script(conn: &Connection, script: &str) -> Result<ImportResult> {
+        let before = Self::user_table_row_counts(conn)?;
+        conn.execute_batch(script)?;
+        let after = Self::user_table_row_counts(conn)?;
+
+        let mut tables_created: Vec<String> = after
+            .keys()
+            .filter(|name| !before.contains_key(*name))
+            .cloned()
+            .collect();
+        tables_created.sort();
+
+        let rows_imported: i64 = after
+            .iter()
+            .map(|(name, count)| (count - before.get(name).copied().unwrap_or(0)).max(0))
+            .sum();
+
+        let mut columns_count = 0usize;
+        for name in &tables_created {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM information_schema.columns WHERE table_name = ? AND table_schema = 'main' AND table_catalog = current_database()",
+                [name],
+                |row| row.get(0),
+            )?;
+            columns_count += count as usize;
+        }
+
+        Ok(ImportResult {
+            table_name: tables_created.join(", "),
+            rows_imported,
+            columns_count,
+            tables_created,
+        })
+    }
+
+    /// Read a .sql script, rejecting it if `check_sql_script` does
+    fn read_sql_script(file_path: &str) -> Result<String> {
+        let script = fs::read_to_string(file_path)
+            .map_err(|e| AppError::Custom(format!("Failed to read SQL script: {}", e)))?;
+        Self::check_sql_script(&script)?;
+        Ok(script)
+    }
+
+    /// Reject scripts that reach outside the project database, manage transactions
+    /// (imports and their dry runs wrap the script in one) or touch DuckBake's own tables
+    fn check_sql_script(script: &str) -> Result<()> {
+        for statement in Self::sql_script_statements(script) {
+            let masked = Self::mask_quoted(&statement).to_uppercase();
+            let tokens: Vec<&str> = masked
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .filter(|t| !t.is_empty())
+                .collect();
+            let keyword = tokens.first().copied().unwrap_or("");
+
+            if matches!(keyword, "ATTACH" | "DETACH" | "INSTALL" | "LOAD" | "EXPORT") {
+                return Err(AppError::Custom(format!(
+                    "SQL scripts cannot use {} statements",
+                    keyword
+                )));
+            }
+            if matches!(
+                keyword,
+                "BEGIN" | "START" | "COMMIT" | "END" | "ROLLBACK" | "ABORT" | "CHECKPOINT"
+            ) {
+                return Err(AppError::Custom(format!(
+                    "SQL scripts cannot control transactions ({})",
+                    keyword
+                )));
+            }
+            if keyword == "COPY" && tokens.contains(&"TO") {
+                return Err(AppError::Custom("SQL scripts cannot use COPY ... TO".into()));
+            }
+
+            for (literal, before) in Self::string_literals(&statement) {
+                if Self::reads_file(&before) && Self::is_outside_path(&literal) {
+                    return Err(AppError::Custom(format!(
+                        "SQL scripts cannot read files outside the working folder: '{}'",
+                        literal
+                    )));
+                }
+            }
+
+            let upper = statement.to_uppercase();
+            if upper
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .any(|t| t.starts_with("_DUCKBAKE_"))
+            {
+                return Err(AppError::Custom(
+                    "SQL scripts cannot modify DuckBake internal tables".into(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Split a script into statements on `;`, dropping `--` and `/* */` comments so
+    /// they can't hide anything. Quoted strings and identifiers are kept intact.
+    fn sql_script_statements(script: &str) -> Vec<String> {
+        let mut statements = Vec::new();
+        let mut current = String::new();
+        let mut chars = script.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                // A doubled quote just closes the string and opens another
+                '\'' | '"' => {
+                    current.push(c);
+                    for next in chars.by_ref() {
+                        current.push(next);
+                        if next == c {
+                            break;
+                        }
+                    }
+                }
+                '-' if chars.peek() == Some(&'-') => {
+                    for next in chars.by_ref() {
+                        if next == '\n' {
+                            current.push('\n');
+                            break;
+                        }
+                    }
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut previous = ' ';
+                    for next in chars.by_ref() {
+                        if previous == '*' && next == '/' {
+                            break;
+                        }
+                        previous = next;
+                    }
+                    current.push(' ');
+                }
+                ';' => statements.push(std::mem::take(&mut current)),
+                _ => current.push(c),
+            }
+        }
+        statements.push(current);
+
+        statements.retain(|s| !s.trim().is_empty());
+        statements
+    }
+
+    /// A statement with the inside of quoted strings and identifiers blanked out
+    fn mask_quoted(statement: &str) -> String {
+        let mut masked = String::with_capacity(statement.len());
+        let mut quote = None;
+        for c in statement.chars() {
+            match quote {
+                Some(q) if c == q => {
+                    quote = None;
+                    masked.push(c);
+                }
+                Some(_) => masked.push(' '),
+                None => {
+                    if c == '\'' || c == '"' {
+                        quote = Some(c);
+                    }
+                    masked.push(c);
+                }
+            }
+        }
+        masked
+    }
+
+    /// Single-quoted strings in a statement, each with the statement text before it
+    fn string_literals(statement: &str) -> Vec<(String, String)> {
+        let mut literals = Vec::new();
+        let mut in_identifier = false;
+        let mut start = None;
+        for (i, c) in statement.char_indices() {
+            match (c, start) {
+                ('"', None) => in_identifier = !in_identifier,
+                ('\'', None) if !in_identifier => start = Some(i),
+                ('\'', Some(from)) => {
+                    literals.push((
+                        statement[from + 1..i].to_string(),
+                        statement[..from].to_string(),
+                    ));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        literals
+    }
+
+    /// Whether a string literal following this text is read as a file: a reader
+    /// function's argument or a `FROM 'file.csv'` replacement scan
+    fn reads_file(before: &str) -> bool {
+        let before = before.trim_end().to_uppercase();
+        let (before, in_call) = match before.strip_suffix('(') {
+            Some(rest) => (rest.trim_end().to_string(), true),
+            None => (before, false),
+        };
+        let word_start = before
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let word = &before[word_start..];
+
+        if in_call {
+            word.starts_with("READ_")
+                || matches!(word, "ST_READ" | "PARQUET_SCAN" | "SNIFF_CSV" | "GLOB")
+        } else {
+            matches!(word, "FROM" | "JOIN")
+        }
+    }
+
+    /// Absolute, home-relative, parent-relative or remote paths
+    fn is_outside_path(path: &str) -> bool {
+        let bytes = path.as_bytes();
+        let drive = bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'/' | b'\\');
+        path.starts_with(['/', '\\', '~'])
+            || path.starts_with("..")
+            || drive
+            || path.contains("://")
+    }
+
+    /// Row counts for every user table in the main schema
+    fn user_table_row_counts(conn: &Connection) -> Result<HashMap<String, i64>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT table_name
+            FROM information_schema.tables
+            WHERE table_schema = 'main' AND table_catalog = current_database()
+            AND table_type = 'BASE TABLE'
+            AND NOT starts_with(table_name, ?)
+            "#,
+        )?;
+        let tables: Vec<String> = stmt
+            .query_map([RESERVED_TABLE_PREFIX], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut counts = HashMap::new();
+        for table in tables {
+            let count: i64 = conn
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM {}", quote_ident(&table)),
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap_or(0);
+            counts.insert(table, count);
+        }
+        Ok(counts)
+    }
+
+    /// Compute null rate and approximate distinct count for each preview column
+    /// Large files are sampled so the preview stays fast; failures leave the defaults
+    fn compute_column_stats(
+        conn: &Connection,
+        read_sql: &str,
+        columns: &mut [PreviewColumn],
+        sample: bool,
+    ) {
+        if columns.is_empty() {
+            return;
+        }
+
+        let aggregates = columns
+            .iter()
+            .map(|c| {
+                format!(
+                    "COUNT(*) FILTER (WHERE {0} IS NULL), approx_count_distinct({0})",
+                    quote_ident(&c.name)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Small files are cheap to scan fully, and a 5% sample of them may be empty
+        let sample_clause = if sample { " USING SAMPLE 5%" } else { "" };
+
+        let sql = format!(
+            "SELECT COUNT(*), {} FROM (SELECT * FROM {}{})",
+            aggregates, read_sql, sample_clause
+        );
+
+        let mut stmt = match conn.prepare(&sql) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                warn!(target: "import", "Failed to compute column stats: {}", e);
+                return;
+            }
+        };
+        let mut rows = match stmt.query([]) {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!(target: "import", "Failed to compute column stats: {}", e);
+                return;
+            }
+        };
+        let Ok(Some(row)) = rows.next() else {
+            return;
+        };
+
+        let sampled_rows: i64 = row.get(0).unwrap_or(0);
+        for (i, column) in columns.iter_mut().enumerate() {
+            let null_count: i64 = row.get(1 + i * 2).unwrap_or(0);
+            column.distinct_estimate = row.get(2 + i * 2).unwrap_or(0);
+            if sampled_rows > 0 {
+                column.null_rate = null_count as f64 / sampled_rows as f64;
+            }
+        }
+    }
+
+    /// Run DuckDB's CSV sniffer and return (delimiter, has_header, date_format)
+    /// Returns all None for non-delimited file types or if sniffing fails
+    fn sniff_csv(
+        conn: &Connection,
+        file_type: &str,
+        file_path: &str,
+    ) -> (Option<String>, Option<bool>, Option<String>) {
+        let escaped_path = file_path.replace('\'', "''");
+        let sql = match file_type {
+            "csv" => format!(
+                "SELECT Delimiter, HasHeader, DateFormat FROM sniff_csv('{}')",
+                escaped_path
+            ),
+            "tsv" => format!(
+                "SELECT Delimiter, HasHeader, DateFormat FROM sniff_csv('{}', delim='\\t')",
+                escaped_path
+            ),
+            _ => return (None, None, None),
+        };
+
+        conn.query_row(&sql, [], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<bool>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })
+        .map(|(delimiter, has_header, date_format)| {
+            // DuckDB reports an empty date format when none was detected
+            (delimiter, has_header, date_format.filter(|f| !f.is_empty()))
         })
+        .unwrap_or((None, None, None))
     }
 
     /// Build the read SQL for different file types
-    fn build_read_sql(file_type: &str, file_path: &str) -> Result<String> {
+    /// Delimited files get their header names cleaned so preview and import agree
+    fn build_read_sql(conn: &Connection, file_type: &str, file_path: &str) -> Result<String> {
+        let sql = Self::build_raw_read_sql(file_type, file_path)?;
+        if matches!(file_type, "csv" | "tsv") {
+            return Ok(Self::normalize_header_names(conn, sql, file_path));
+        }
+        Ok(sql)
+    }
+
+    /// Rename header columns that carry a leading BOM or a stray carriage return
+    /// DuckDB usually strips both, but files with a BOM or mixed line endings can
+    /// still yield names like "\u{feff}id" or "name\r"
+    fn normalize_header_names(conn: &Connection, read_sql: String, file_path: &str) -> String {
+        // Only pay for the extra DESCRIBE when the header line looks suspicious
+        if !Self::header_needs_cleanup(file_path) {
+            return read_sql;
+        }
+
+        let describe_sql = format!("DESCRIBE SELECT * FROM {}", read_sql);
+        let columns = match Self::describe_columns(conn, &describe_sql) {
+            Ok(cols) => cols,
+            Err(_) => return read_sql,
+        };
+
+        let renames: Vec<String> = columns
+            .iter()
+            .filter_map(|(name, _)| {
+                let clean = name.trim_start_matches('\u{feff}').trim_end_matches(['\r', '\n']);
+                (clean != name && !clean.is_empty())
+                    .then(|| format!("{} AS {}", quote_ident(name), quote_ident(clean)))
+            })
+            .collect();
+
+        if renames.is_empty() {
+            return read_sql;
+        }
+
+        info!(target: "import", "Cleaning {} header column name(s) in {}", renames.len(), file_path);
+        format!("(SELECT * RENAME ({}) FROM {})", renames.join(", "), read_sql)
+    }
+
+    /// Read the first `max_lines` lines of any file as text, whatever its extension, along
+    /// with a guess at its encoding and line endings. At most `MAX_PEEK_BYTES` are read.
+    pub fn peek_file(file_path: &str, max_lines: usize) -> Result<FilePeek> {
+        let path = Path::new(file_path);
+        let file_size = fs::metadata(path)?.len();
+        let mut head = Vec::with_capacity(file_size.min(MAX_PEEK_BYTES) as usize);
+        fs::File::open(path)?
+            .take(MAX_PEEK_BYTES)
+            .read_to_end(&mut head)?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let (encoding, text) = if head.starts_with(UTF8_BOM) {
+            ("utf-8-bom", String::from_utf8_lossy(&head[UTF8_BOM.len()..]).to_string())
+        } else if head.starts_with(&[0xFF, 0xFE]) {
+            ("utf-16le", Self::decode_utf16(&head[2..], u16::from_le_bytes))
+        } else if head.starts_with(&[0xFE, 0xFF]) {
+            ("utf-16be", Self::decode_utf16(&head[2..], u16::from_be_bytes))
+        } else if head.contains(&0) {
+            // NUL bytes don't turn up in text files without a UTF-16 BOM
+            let shown = &head[..head.len().min(BINARY_PEEK_BYTES)];
+            return Ok(FilePeek {
+                file_name,
+                file_size,
+                lines: vec![String::from_utf8_lossy(shown).to_string()],
+                encoding: "unknown".into(),
+                line_ending: None,
+                is_binary: true,
+                truncated: file_size > shown.len() as u64,
+            });
+        } else {
+            // A multi-byte character cut off by the read limit doesn't make the file invalid
+            let valid = match std::str::from_utf8(&head) {
+                Ok(_) => true,
+                Err(e) => e.error_len().is_none() && file_size > head.len() as u64,
+            };
+            let encoding = if head.is_ascii() {
+                "ascii"
+            } else if valid {
+                "utf-8"
+            } else {
+                "unknown"
+            };
+            (encoding, String::from_utf8_lossy(&head).to_string())
+        };
+
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        let cr = text.matches('\r').count() - crlf;
+        let line_ending = match (crlf > 0, lf > 0, cr > 0) {
+            (false, false, false) => None,
+            (true, false, false) => Some("crlf"),
+            (false, true, false) => Some("lf"),
+            (false, false, true) => Some("cr"),
+            _ => Some("mixed"),
+        };
+
+        let all_lines: Vec<&str> = if line_ending == Some("cr") {
+            text.split('\r').collect()
+        } else {
+            text.lines().collect()
+        };
+        let lines: Vec<String> = all_lines
+            .iter()
+            .take(max_lines)
+            .map(|line| line.to_string())
+            .collect();
+
+        Ok(FilePeek {
+            file_name,
+            file_size,
+            truncated: all_lines.len() > lines.len() || file_size > head.len() as u64,
+            lines,
+            encoding: encoding.into(),
+            line_ending: line_ending.map(str::to_string),
+            is_binary: false,
+        })
+    }
+
+    fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    }
+
+    /// Whether the file starts with a UTF-8 BOM or its first line ends in CRLF
+    fn header_needs_cleanup(file_path: &str) -> bool {
+        let mut head = Vec::with_capacity(HEADER_PROBE_BYTES as usize);
+        if fs::File::open(file_path)
+            .and_then(|f| f.take(HEADER_PROBE_BYTES).read_to_end(&mut head))
+            .is_err()
+        {
+            return false;
+        }
+
+        let first_line = head.split(|b| *b == b'\n').next().unwrap_or(&[]);
+        head.starts_with(UTF8_BOM) || first_line.contains(&b'\r')
+    }
+
+    /// DuckDB table function call that reads the file as-is
+    fn build_raw_read_sql(file_type: &str, file_path: &str) -> Result<String> {
         // Escape single quotes in file path
         let escaped_path = file_path.replace('\'', "''");
 
@@ -219,3 +1102,44 @@ impl FileParser {
         serde_json::Value::Null
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a throwaway CSV fixture and return its path
+    fn write_fixture(bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("duckbake-{}.csv", uuid::Uuid::new_v4()));
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn bom_and_crlf_header_names_are_cleaned() {
+        let path = write_fixture(b"\xEF\xBB\xBFid,name\r\n1,Ada\r\n2,Grace\r\n");
+        let file_path = path.to_str().unwrap();
+        assert!(FileParser::header_needs_cleanup(file_path));
+
+        let conn = Connection::open_in_memory().unwrap();
+        let read_sql = FileParser::build_raw_read_sql("csv", file_path).unwrap();
+        let read_sql = FileParser::normalize_header_names(&conn, read_sql, file_path);
+        let columns = FileParser::describe_columns(
+            &conn,
+            &format!("DESCRIBE SELECT * FROM {}", read_sql),
+        )
+        .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["id", "name"]);
+    }
+
+    #[test]
+    fn plain_header_needs_no_cleanup() {
+        let path = write_fixture(b"id,name\n1,Ada\n");
+        let needs_cleanup = FileParser::header_needs_cleanup(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert!(!needs_cleanup);
+    }
+}
@@ -1,26 +1,119 @@
 use std::time::Duration;
 
+use base64::Engine;
 use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Window};
+use tracing::warn;
 
 use crate::error::{AppError, Result};
-use crate::models::{OllamaModel, OllamaPullProgress, OllamaStatus, OllamaTagsResponse, OllamaVersionResponse};
-
-const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+use crate::models::{
+    GenerateOptions, OllamaModel, OllamaPullProgress, OllamaStatus, OllamaTagsResponse,
+    OllamaVersionResponse, ResponseMode, VizSpec, DEFAULT_EMBEDDING_MODEL,
+};
 
 const OLLAMA_BASE_URL: &str = "http://localhost:11434";
 
+/// Name fragments of known embedding-only model families
+const EMBEDDING_MODEL_PATTERNS: &[&str] = &[
+    "embed", // nomic-embed-text, mxbai-embed-large, snowflake-arctic-embed, ...
+    "all-minilm",
+    "bge-",
+    "bge:",
+    "e5-",
+    "gte-",
+    "paraphrase",
+];
+
+/// Opening of every chat system prompt, whatever the response format
+const CHAT_PROMPT_INTRO: &str = r#"You are a helpful data analyst assistant working with a DuckDB database and document library.
+
+You have access to:
+1. DATABASE TABLES - Structured data you can query with SQL
+2. DOCUMENT EXCERPTS - Relevant passages from uploaded documents (PDFs, Word docs, text files, etc.)
+
+When the user asks about documents, reference the document excerpts provided in the context.
+When the user asks about data/metrics, write SQL queries against the database tables.
+For questions that span both, combine insights from both sources."#;
+
+/// Answers as ```duckbake blocks the UI runs and visualizes
+const DUCKBAKE_FORMAT_PROMPT: &str = r#"RESPONSE FORMAT FOR DATA QUERIES:
+When answering data questions, provide a brief explanation followed by a query block. Do NOT show raw SQL to the user - use this special format instead:
+
+```duckbake
+{"sql": "YOUR SQL QUERY HERE", "viz": "TYPE", "xKey": "column", "yKey": "column"}
+```
+
+Where:
+- sql: The DuckDB SQL query to execute
+- viz: Visualization type - one of: "table", "bar", "line", "pie"
+- xKey: Column for x-axis/labels (required for "bar" and "line", otherwise optional)
+- yKey: Column for y-axis/values (required for "bar" and "line", otherwise optional)
+
+VISUALIZATION GUIDELINES:
+- Use "table" for detailed row-level data, text results, or many columns
+- Use "bar" for comparing categories (e.g., sales by region, counts by type)
+- Use "line" for trends over time (e.g., monthly sales, daily users)
+- Use "pie" for showing proportions of a whole (e.g., market share, percentages) - limit to 5-7 slices
+
+RESPONSE FORMAT FOR DOCUMENT QUESTIONS:
+When answering questions about documents, provide a clear answer based on the document excerpts in the context. Reference the source document name when citing information.
+
+EXAMPLE (Data Query):
+User: "Show me sales by region"
+Response: Here's the breakdown of sales by region:
+
+```duckbake
+{"sql": "SELECT region, SUM(amount) as total_sales FROM orders GROUP BY region ORDER BY total_sales DESC", "viz": "bar", "xKey": "region", "yKey": "total_sales"}
+```
+
+IMPORTANT:
+- Always use valid DuckDB SQL syntax for data queries
+- Keep queries efficient with appropriate LIMIT clauses for large results
+- Choose the most appropriate visualization for the data
+- Provide brief context before the query block
+- You can include multiple query blocks for complex analyses
+- When referencing documents, cite the document name"#;
+
+/// Answers as plain ```sql blocks the user runs themselves
+const RAW_SQL_FORMAT_PROMPT: &str = r#"RESPONSE FORMAT FOR DATA QUERIES:
+When answering data questions, provide a brief explanation followed by the query in a fenced SQL block:
+
+```sql
+SELECT region, SUM(amount) AS total_sales FROM orders GROUP BY region ORDER BY total_sales DESC
+```
+
+RESPONSE FORMAT FOR DOCUMENT QUESTIONS:
+When answering questions about documents, provide a clear answer based on the document excerpts in the context. Reference the source document name when citing information.
+
+IMPORTANT:
+- Always use valid DuckDB SQL syntax for data queries
+- Keep queries efficient with appropriate LIMIT clauses for large results
+- Explain briefly what each query returns
+- When referencing documents, cite the document name"#;
+
 // Timeout for embedding requests (model loading can take time)
 const EMBEDDING_TIMEOUT_SECS: u64 = 300; // 5 minutes
 
+/// Time allowed to establish a connection to Ollama, for any request
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Wait for the first chat token, which includes loading the model
+const CHAT_FIRST_TOKEN_TIMEOUT_SECS: u64 = 300;
+/// Longest gap allowed between streamed chat chunks once tokens are flowing
+const CHAT_IDLE_TIMEOUT_SECS: u64 = 60;
+
+/// Prompt for turning an image document into searchable text
+const IMAGE_DESCRIPTION_PROMPT: &str = "Describe this image in detail so it can be found by a text search. Transcribe any visible text exactly, and for charts or diagrams explain what they show, including labels and values.";
+
 #[derive(Debug, Serialize)]
 struct ChatRequest {
     model: String,
     messages: Vec<ChatMessageRequest>,
     stream: bool,
     options: ChatOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>, // "json" or a JSON schema the output must match
 }
 
 #[derive(Debug, Serialize)]
@@ -47,6 +140,21 @@ struct ChatMessageContent {
     content: String,
 }
 
+#[derive(Debug, Serialize)]
+struct GenerateRequest {
+    model: String,
+    prompt: String,
+    stream: bool,
+    options: GenerateOptions,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>, // Base64-encoded, for multimodal models
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
 #[derive(Debug, Serialize)]
 struct EmbeddingRequest {
     model: String,
@@ -68,7 +176,10 @@ pub struct OllamaService {
 impl OllamaService {
     pub fn new() -> Self {
         OllamaService {
-            client: Client::new(),
+            client: Client::builder()
+                .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
             base_url: OLLAMA_BASE_URL.to_string(),
         }
     }
@@ -117,75 +228,87 @@ impl OllamaService {
         Ok(tags
             .models
             .into_iter()
-            .map(|m| OllamaModel {
-                name: m.name,
-                size: m.size,
-                digest: m.digest,
-                modified_at: m.modified_at,
+            .map(|m| {
+                let details = m.details.unwrap_or_default();
+                let families: Vec<String> = details
+                    .family
+                    .into_iter()
+                    .chain(details.families.unwrap_or_default())
+                    .collect();
+                OllamaModel {
+                    is_embedding: Self::is_embedding_model(&m.name, &families),
+                    name: m.name,
+                    size: m.size,
+                    digest: m.digest,
+                    modified_at: m.modified_at,
+                }
             })
             .collect())
     }
 
-    pub async fn chat_stream(
-        &self,
-        window: &Window,
-        model: &str,
-        messages: Vec<(String, String)>, // (role, content) pairs
-        context: Option<String>,
-    ) -> Result<()> {
-        let url = format!("{}/api/chat", self.base_url);
-
-        // Build messages with optional context
-        let mut chat_messages: Vec<ChatMessageRequest> = Vec::new();
-
-        // Add system message with context if provided
-        let base_prompt = r#"You are a helpful data analyst assistant working with a DuckDB database and document library.
-
-You have access to:
-1. DATABASE TABLES - Structured data you can query with SQL
-2. DOCUMENT EXCERPTS - Relevant passages from uploaded documents (PDFs, Word docs, text files, etc.)
-
-When the user asks about documents, reference the document excerpts provided in the context.
-When the user asks about data/metrics, write SQL queries against the database tables.
-For questions that span both, combine insights from both sources.
-
-RESPONSE FORMAT FOR DATA QUERIES:
-When answering data questions, provide a brief explanation followed by a query block. Do NOT show raw SQL to the user - use this special format instead:
-
-```duckbake
-{"sql": "YOUR SQL QUERY HERE", "viz": "TYPE", "xKey": "column", "yKey": "column"}
-```
-
-Where:
-- sql: The DuckDB SQL query to execute
-- viz: Visualization type - one of: "table", "bar", "line", "pie"
-- xKey: Column for x-axis/labels (optional, auto-detected if omitted)
-- yKey: Column for y-axis/values (optional, auto-detected if omitted)
+    /// Whether a model only produces embeddings, going by its name and the architecture
+    /// families Ollama reports (BERT-style encoders can't generate text)
+    pub fn is_embedding_model(name: &str, families: &[String]) -> bool {
+        let name = name.to_lowercase();
+        EMBEDDING_MODEL_PATTERNS.iter().any(|p| name.contains(p))
+            || families.iter().any(|f| f.to_lowercase().contains("bert"))
+    }
 
-VISUALIZATION GUIDELINES:
-- Use "table" for detailed row-level data, text results, or many columns
-- Use "bar" for comparing categories (e.g., sales by region, counts by type)
-- Use "line" for trends over time (e.g., monthly sales, daily users)
-- Use "pie" for showing proportions of a whole (e.g., market share, percentages) - limit to 5-7 slices
+    /// Build the chat system prompt for the requested response format
+    fn system_prompt(mode: ResponseMode) -> String {
+        match mode {
+            ResponseMode::Duckbake => {
+                format!("{}\n\n{}", CHAT_PROMPT_INTRO, DUCKBAKE_FORMAT_PROMPT)
+            }
+            ResponseMode::RawSql => {
+                format!("{}\n\n{}", CHAT_PROMPT_INTRO, RAW_SQL_FORMAT_PROMPT)
+            }
+            ResponseMode::Plain => CHAT_PROMPT_INTRO.to_string(),
+        }
+    }
 
-RESPONSE FORMAT FOR DOCUMENT QUESTIONS:
-When answering questions about documents, provide a clear answer based on the document excerpts in the context. Reference the source document name when citing information.
+    /// Extract every ```duckbake fenced block from a response. Blocks whose JSON is
+    /// malformed (including an unknown viz type) or that fail `VizSpec::validate` are
+    /// skipped; an unclosed fence runs to the end.
+    pub fn parse_viz_specs(text: &str) -> Vec<VizSpec> {
+        const OPEN: &str = "```duckbake";
+        const CLOSE: &str = "```";
+
+        let mut blocks = Vec::new();
+        let mut search_from = 0;
+
+        while let Some(found) = text[search_from..].find(OPEN) {
+            let start = search_from + found;
+            let body_start = start + OPEN.len();
+            let (body_end, end) = match text[body_start..].find(CLOSE) {
+                Some(i) => (body_start + i, body_start + i + CLOSE.len()),
+                None => (text.len(), text.len()),
+            };
+            search_from = end;
+
+            let body = text[body_start..body_end].trim();
+            match serde_json::from_str::<VizSpec>(body) {
+                Ok(spec) => match spec.validate() {
+                    Ok(()) => blocks.push(VizSpec { start, end, ..spec }),
+                    Err(e) => warn!(target: "chat", "Skipping duckbake block at byte {}: {}", start, e),
+                },
+                Err(e) => warn!(target: "chat", "Skipping malformed duckbake block at byte {}: {}", start, e),
+            }
+        }
 
-EXAMPLE (Data Query):
-User: "Show me sales by region"
-Response: Here's the breakdown of sales by region:
+        blocks
+    }
 
-```duckbake
-{"sql": "SELECT region, SUM(amount) as total_sales FROM orders GROUP BY region ORDER BY total_sales DESC", "viz": "bar", "xKey": "region", "yKey": "total_sales"}
-```
+    /// System prompt (with database context when given) followed by the conversation
+    fn chat_messages(
+        messages: Vec<(String, String)>,
+        context: Option<String>,
+        response_mode: ResponseMode,
+    ) -> Vec<ChatMessageRequest> {
+        let mut chat_messages: Vec<ChatMessageRequest> = Vec::new();
 
-IMPORTANT:
-- Always use valid DuckDB SQL syntax for data queries
-- Keep queries efficient with appropriate LIMIT clauses for large results
-- Choose the most appropriate visualization for the data
-- Provide brief context before the query block
-- You can include multiple query blocks for complex analyses
-- When referencing documents, cite the document name"#;
+        // Add system message with context if provided
+        let base_prompt = Self::system_prompt(response_mode);
 
         if let Some(ctx) = context {
             chat_messages.push(ChatMessageRequest {
@@ -207,24 +330,39 @@ IMPORTANT:
             chat_messages.push(ChatMessageRequest { role, content });
         }
 
+        chat_messages
+    }
+
+    /// Stream a chat response, returning the duckbake blocks it contained
+    pub async fn chat_stream(
+        &self,
+        window: &Window,
+        model: &str,
+        messages: Vec<(String, String)>, // (role, content) pairs
+        context: Option<String>,
+        response_mode: ResponseMode,
+    ) -> Result<Vec<VizSpec>> {
+        let url = format!("{}/api/chat", self.base_url);
+
         let request = ChatRequest {
             model: model.to_string(),
-            messages: chat_messages,
+            messages: Self::chat_messages(messages, context, response_mode),
             stream: true,
             options: ChatOptions {
                 num_ctx: 8192, // Larger context window to fit document content
             },
+            format: None,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|_| AppError::OllamaNotAvailable)?;
+        let first_token_timeout = Duration::from_secs(CHAT_FIRST_TOKEN_TIMEOUT_SECS);
+        let send = self.client.post(&url).json(&request).send();
+        let response = match tokio::time::timeout(first_token_timeout, send).await {
+            Ok(response) => response.map_err(|_| AppError::OllamaNotAvailable)?,
+            Err(_) => return Err(Self::chat_timeout(window, first_token_timeout)),
+        };
 
         if !response.status().is_success() {
+            warn!(target: "ollama", "Chat request failed with status {}", response.status());
             return Err(AppError::Custom(format!(
                 "Ollama returned status: {}",
                 response.status()
@@ -232,8 +370,18 @@ IMPORTANT:
         }
 
         let mut stream = response.bytes_stream();
+        let mut full_response = String::new();
+
+        // The idle window resets on every chunk, so only a stalled stream times out
+        let mut idle_timeout = first_token_timeout;
+        loop {
+            let chunk = match tokio::time::timeout(idle_timeout, stream.next()).await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(_) => return Err(Self::chat_timeout(window, idle_timeout)),
+            };
+            idle_timeout = Duration::from_secs(CHAT_IDLE_TIMEOUT_SECS);
 
-        while let Some(chunk) = stream.next().await {
             match chunk {
                 Ok(bytes) => {
                     // Parse each line (NDJSON format)
@@ -246,24 +394,168 @@ IMPORTANT:
                             if let Some(msg) = response.message {
                                 if !msg.content.is_empty() {
                                     let _ = window.emit("chat-chunk", &msg.content);
+                                    full_response.push_str(&msg.content);
                                 }
                             }
                             if response.done {
+                                let blocks = Self::parse_viz_specs(&full_response);
+                                let _ = window.emit("chat-blocks", &blocks);
                                 let _ = window.emit("chat-done", ());
-                                return Ok(());
+                                return Ok(blocks);
                             }
                         }
                     }
                 }
                 Err(e) => {
+                    warn!(target: "ollama", "Chat stream failed: {}", e);
                     let _ = window.emit("chat-error", e.to_string());
                     return Err(AppError::Custom(e.to_string()));
                 }
             }
         }
 
+        let blocks = Self::parse_viz_specs(&full_response);
+        let _ = window.emit("chat-blocks", &blocks);
         let _ = window.emit("chat-done", ());
-        Ok(())
+        Ok(blocks)
+    }
+
+    /// Ask for a single non-streamed reply constrained by Ollama's `format` option
+    /// (`"json"` or a JSON schema), returning the parsed JSON value
+    pub async fn chat_json(
+        &self,
+        model: &str,
+        messages: Vec<(String, String)>,
+        context: Option<String>,
+        format: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}/api/chat", self.base_url);
+
+        // Format instructions for duckbake/sql blocks would fight the JSON constraint
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages: Self::chat_messages(messages, context, ResponseMode::Plain),
+            stream: false,
+            options: ChatOptions { num_ctx: 8192 },
+            format: Some(format),
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .timeout(Duration::from_secs(CHAT_FIRST_TOKEN_TIMEOUT_SECS))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| {
+                warn!(target: "ollama", "JSON chat request failed: {}", e);
+                if e.is_timeout() {
+                    AppError::Custom(format!(
+                        "Ollama stopped responding: no response for {} seconds",
+                        CHAT_FIRST_TOKEN_TIMEOUT_SECS
+                    ))
+                } else {
+                    AppError::OllamaNotAvailable
+                }
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            warn!(target: "ollama", "JSON chat request failed with status {}", status);
+            return Err(AppError::Custom(format!("Ollama returned status {}: {}", status, body)));
+        }
+
+        let reply: ChatStreamResponse = response.json().await?;
+        let content = reply.message.map(|m| m.content).unwrap_or_default();
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::Custom(format!("Model did not return valid JSON: {}", e)))
+    }
+
+    /// Run a single prompt through `/api/generate` and return the whole completion.
+    /// For one-shot tasks (titles, table descriptions) that don't need the chat
+    /// system prompt or streaming.
+    pub async fn generate(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<String> {
+        self.generate_with_images(model, prompt, Vec::new(), options).await
+    }
+
+    /// Describe an image with a multimodal model (e.g. llava), so image documents
+    /// can be chunked and embedded as text
+    pub async fn describe_image(&self, model: &str, image: &[u8]) -> Result<String> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(image);
+        let description = self
+            .generate_with_images(model, IMAGE_DESCRIPTION_PROMPT, vec![encoded], None)
+            .await?;
+        if description.is_empty() {
+            return Err(AppError::Custom(format!(
+                "Model '{}' returned no description for the image",
+                model
+            )));
+        }
+        Ok(description)
+    }
+
+    async fn generate_with_images(
+        &self,
+        model: &str,
+        prompt: &str,
+        images: Vec<String>,
+        options: Option<GenerateOptions>,
+    ) -> Result<String> {
+        let url = format!("{}/api/generate", self.base_url);
+
+        let request = GenerateRequest {
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            stream: false,
+            options: options.unwrap_or_default(),
+            images,
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .timeout(Duration::from_secs(CHAT_FIRST_TOKEN_TIMEOUT_SECS))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| {
+                warn!(target: "ollama", "Generate request failed: {}", e);
+                if e.is_timeout() {
+                    AppError::Custom(format!(
+                        "Ollama stopped responding: no response for {} seconds",
+                        CHAT_FIRST_TOKEN_TIMEOUT_SECS
+                    ))
+                } else {
+                    AppError::OllamaNotAvailable
+                }
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            warn!(target: "ollama", "Generate request failed with status {}", status);
+            return Err(AppError::Custom(format!("Ollama returned status {}: {}", status, body)));
+        }
+
+        let reply: GenerateResponse = response.json().await?;
+        Ok(reply.response.trim().to_string())
+    }
+
+    /// Report a chat that stopped producing tokens to the UI and as an error
+    fn chat_timeout(window: &Window, waited: Duration) -> AppError {
+        let message = format!(
+            "Ollama stopped responding: no response for {} seconds",
+            waited.as_secs()
+        );
+        warn!(target: "ollama", "{}", message);
+        let _ = window.emit("chat-error", &message);
+        AppError::Custom(message)
     }
 
     /// Warm up the embedding model by sending a test request
@@ -301,6 +593,7 @@ IMPORTANT:
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
+            warn!(target: "ollama", "Warmup of {} failed with status {}", model, status);
             return Err(AppError::Custom(format!(
                 "Model warmup failed ({}): {}. Make sure '{}' model is installed (ollama pull {})",
                 status, body, model, model
@@ -334,6 +627,7 @@ IMPORTANT:
             .send()
             .await
             .map_err(|e| {
+                warn!(target: "ollama", "Embedding request to {} failed: {}", model, e);
                 if e.is_timeout() {
                     AppError::Custom(format!(
                         "Embedding request timed out after {} seconds. The model may still be loading - try again.",
@@ -349,6 +643,7 @@ IMPORTANT:
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
+            warn!(target: "ollama", "Embedding with {} failed with status {}", model, status);
             return Err(AppError::Custom(format!(
                 "Embedding failed ({}): {}. Make sure '{}' model is installed (ollama pull {})",
                 status, body, model, model
@@ -359,6 +654,18 @@ IMPORTANT:
         Ok(embed_response.embeddings)
     }
 
+    /// Whether a `generate_embeddings` failure looks like the model was unloaded mid-job
+    /// (a cold-load timeout or a server-side error), so re-warming and retrying may help
+    pub fn is_model_unload_error(error: &AppError) -> bool {
+        match error {
+            AppError::Custom(msg) => {
+                msg.starts_with("Embedding request timed out")
+                    || msg.starts_with("Embedding failed (5")
+            }
+            _ => false,
+        }
+    }
+
     /// Pull/download a model from Ollama registry
     pub async fn pull_model(&self, window: &Window, model: &str) -> Result<()> {
         let url = format!("{}/api/pull", self.base_url);
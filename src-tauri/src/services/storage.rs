@@ -1,25 +1,59 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use directories::ProjectDirs;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::error::{AppError, Result};
-use crate::models::{Project, ProjectSummary, ProjectsFile};
+use crate::models::{
+    AppSettings, BootstrapConfig, DataDirInfo, DuckDbSettings, ImportDefaults, Project,
+    ProjectSummary, ProjectsFile, RecentImport,
+};
+use crate::services::{ImportMode, ImportOptions};
 
 pub struct StorageService {
+    data_dir: PathBuf,
     databases_dir: PathBuf,
+    trash_dir: PathBuf, // Database files of trashed projects
     projects_file: PathBuf,
+    settings_file: PathBuf,
 }
 
+/// Number of recent imports kept in settings.json
+const MAX_RECENT_IMPORTS: usize = 20;
+
+/// Days a trashed project is kept when settings.json doesn't say otherwise
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Environment variable that overrides the data directory, e.g. for portable installs
+pub const DATA_DIR_ENV: &str = "DUCKBAKE_DATA_DIR";
+
 impl StorageService {
     pub fn new() -> Result<Self> {
-        let project_dirs = ProjectDirs::from("com", "joedesigns", "duckbake")
-            .ok_or_else(|| AppError::Custom("Could not determine app data directory".into()))?;
+        let info = Self::resolve_data_dir()?;
+        let data_dir = PathBuf::from(&info.data_dir);
+
+        if info.source != "default" {
+            if let Err(e) = Self::check_writable(&data_dir) {
+                warn!(
+                    target: "storage",
+                    "Data directory from {} is unusable, falling back to default: {}",
+                    info.source, e
+                );
+                return Self::open(PathBuf::from(info.default_data_dir));
+            }
+        }
+
+        Self::open(data_dir)
+    }
 
-        let data_dir = project_dirs.data_dir().to_path_buf();
+    /// Open storage rooted at a specific data directory
+    pub fn open(data_dir: PathBuf) -> Result<Self> {
         let databases_dir = data_dir.join("databases");
+        let trash_dir = data_dir.join("trash");
         let projects_file = data_dir.join("projects.json");
+        let settings_file = data_dir.join("settings.json");
 
         // Ensure directories exist
         fs::create_dir_all(&data_dir)?;
@@ -33,8 +67,11 @@ impl StorageService {
         }
 
         Ok(StorageService {
+            data_dir,
             databases_dir,
+            trash_dir,
             projects_file,
+            settings_file,
         })
     }
 
@@ -50,9 +87,117 @@ impl StorageService {
         Ok(())
     }
 
+    fn project_dirs() -> Result<ProjectDirs> {
+        ProjectDirs::from("com", "joedesigns", "duckbake")
+            .ok_or_else(|| AppError::Custom("Could not determine app data directory".into()))
+    }
+
+    fn bootstrap_file() -> Result<PathBuf> {
+        Ok(Self::project_dirs()?.config_dir().join("bootstrap.json"))
+    }
+
+    fn read_bootstrap() -> BootstrapConfig {
+        Self::bootstrap_file()
+            .and_then(|path| Ok(fs::read_to_string(path)?))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Work out which data directory to use: env var, then bootstrap config, then the OS default
+    pub fn resolve_data_dir() -> Result<DataDirInfo> {
+        let default_data_dir = Self::project_dirs()?.data_dir().display().to_string();
+
+        let (data_dir, source) = match std::env::var(DATA_DIR_ENV) {
+            Ok(dir) if !dir.trim().is_empty() => (dir, "env"),
+            _ => match Self::read_bootstrap().data_dir {
+                Some(dir) => (dir, "config"),
+                None => (default_data_dir.clone(), "default"),
+            },
+        };
+
+        Ok(DataDirInfo {
+            data_dir,
+            default_data_dir,
+            source: source.to_string(),
+        })
+    }
+
+    /// Persist the data directory override; None goes back to the OS default
+    pub fn save_data_dir_override(data_dir: Option<&Path>) -> Result<()> {
+        let path = Self::bootstrap_file()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let config = BootstrapConfig {
+            data_dir: data_dir.map(|d| d.display().to_string()),
+        };
+        fs::write(path, serde_json::to_string_pretty(&config)?)?;
+        Ok(())
+    }
+
+    /// Create the directory if needed and make sure files can be written to it
+    pub fn check_writable(dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        let probe = dir.join(".duckbake_write_test");
+        fs::write(&probe, b"ok").map_err(|e| {
+            AppError::Custom(format!("{} is not writable: {}", dir.display(), e))
+        })?;
+        let _ = fs::remove_file(probe);
+        Ok(())
+    }
+
+    /// Copy projects, settings and databases into an empty data directory
+    /// Skipped when the target already has a projects.json, so nothing there is overwritten
+    pub fn copy_data_to(&self, target: &Path) -> Result<()> {
+        if target.join("projects.json").exists() {
+            return Ok(());
+        }
+
+        for (source, name) in [(&self.databases_dir, "databases"), (&self.trash_dir, "trash")] {
+            if !source.exists() {
+                continue;
+            }
+            let target_dir = target.join(name);
+            fs::create_dir_all(&target_dir)?;
+            for entry in fs::read_dir(source)? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() {
+                    fs::copy(entry.path(), target_dir.join(entry.file_name()))?;
+                }
+            }
+        }
+
+        if self.settings_file.exists() {
+            fs::copy(&self.settings_file, target.join("settings.json"))?;
+        }
+        // Written last so an interrupted copy isn't mistaken for a complete data dir
+        fs::copy(&self.projects_file, target.join("projects.json"))?;
+        Ok(())
+    }
+
+    fn read_settings(&self) -> Result<AppSettings> {
+        if !self.settings_file.exists() {
+            return Ok(AppSettings::default());
+        }
+        let content = fs::read_to_string(&self.settings_file)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn write_settings(&self, settings: &AppSettings) -> Result<()> {
+        let json = serde_json::to_string_pretty(settings)?;
+        fs::write(&self.settings_file, json)?;
+        Ok(())
+    }
+
     pub fn list_projects(&self) -> Result<Vec<ProjectSummary>> {
         let file = self.read_projects()?;
-        Ok(file.projects.iter().map(ProjectSummary::from).collect())
+        Ok(file
+            .projects
+            .iter()
+            .filter(|p| p.deleted_at.is_none())
+            .map(ProjectSummary::from)
+            .collect())
     }
 
     pub fn create_project(&self, name: String, description: String) -> Result<Project> {
@@ -67,6 +212,13 @@ impl StorageService {
             created_at: now.clone(),
             updated_at: now,
             database_file: database_file.clone(),
+            duckdb_settings: DuckDbSettings::default(),
+            embedding_model: None,
+            embedding_dimension: None,
+            vision_model: None,
+            chat_model: None,
+            import_defaults: ImportDefaults::default(),
+            deleted_at: None,
         };
 
         // Create the database file path (DuckDB will create it on first connection)
@@ -100,7 +252,10 @@ impl StorageService {
                 embedding_model VARCHAR NOT NULL,
                 vector_column_name VARCHAR NOT NULL,
                 last_updated TIMESTAMP,
-                PRIMARY KEY (table_name, column_name)
+                last_row_id BIGINT,
+                similarity_metric VARCHAR,
+                normalized BOOLEAN,
+                PRIMARY KEY (table_name, column_name, embedding_model)
             );
             "#,
         )?;
@@ -113,18 +268,75 @@ impl StorageService {
         Ok(project)
     }
 
+    /// A project that isn't in the trash
     pub fn get_project(&self, id: &str) -> Result<Project> {
         let file = self.read_projects()?;
         file.projects
             .into_iter()
-            .find(|p| p.id == id)
+            .find(|p| p.id == id && p.deleted_at.is_none())
             .ok_or_else(|| AppError::ProjectNotFound(id.to_string()))
     }
 
+    /// Move a project to the trash: its database file goes to `trash/` and the entry
+    /// is kept, marked with when it was deleted, until it's restored or purged
     pub fn delete_project(&self, id: &str) -> Result<()> {
         let mut file = self.read_projects()?;
 
-        // Find and remove the project
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id && p.deleted_at.is_none())
+            .ok_or_else(|| AppError::ProjectNotFound(id.to_string()))?;
+
+        fs::create_dir_all(&self.trash_dir)?;
+        Self::move_database_files(&self.databases_dir, &self.trash_dir, &project.database_file)?;
+        project.deleted_at = Some(chrono::Utc::now().to_rfc3339());
+        self.write_projects(&file)?;
+
+        Ok(())
+    }
+
+    /// Projects in the trash, most recently deleted first
+    pub fn list_trashed_projects(&self) -> Result<Vec<Project>> {
+        let mut trashed: Vec<Project> = self
+            .read_projects()?
+            .projects
+            .into_iter()
+            .filter(|p| p.deleted_at.is_some())
+            .collect();
+        trashed.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(trashed)
+    }
+
+    /// Take a project out of the trash, moving its database file back
+    pub fn restore_project(&self, id: &str) -> Result<Project> {
+        let mut file = self.read_projects()?;
+
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id && p.deleted_at.is_some())
+            .ok_or_else(|| AppError::Custom(format!("Project is not in the trash: {}", id)))?;
+
+        if self.databases_dir.join(&project.database_file).exists() {
+            return Err(AppError::Custom(format!(
+                "Can't restore '{}': its database file already exists",
+                project.name
+            )));
+        }
+        Self::move_database_files(&self.trash_dir, &self.databases_dir, &project.database_file)?;
+        project.deleted_at = None;
+
+        let restored = project.clone();
+        self.write_projects(&file)?;
+
+        Ok(restored)
+    }
+
+    /// Permanently delete a project, trashed or not, along with its database file
+    pub fn purge_project(&self, id: &str) -> Result<()> {
+        let mut file = self.read_projects()?;
+
         let project = file
             .projects
             .iter()
@@ -135,15 +347,77 @@ impl StorageService {
         file.projects.retain(|p| p.id != id);
         self.write_projects(&file)?;
 
-        // Delete the database file
-        let db_path = self.databases_dir.join(&project.database_file);
-        if db_path.exists() {
-            fs::remove_file(db_path)?;
+        let dir = if project.deleted_at.is_some() {
+            &self.trash_dir
+        } else {
+            &self.databases_dir
+        };
+        for path in Self::database_files(dir, &project.database_file) {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
         }
 
         Ok(())
     }
 
+    /// Purge projects that have been in the trash longer than the retention period,
+    /// returning how many were removed
+    pub fn purge_expired_trash(&self) -> Result<usize> {
+        let days = self.trash_retention_days()?;
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+
+        let expired: Vec<String> = self
+            .list_trashed_projects()?
+            .into_iter()
+            .filter(|p| {
+                p.deleted_at
+                    .as_deref()
+                    .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                    .is_some_and(|at| at < cutoff)
+            })
+            .map(|p| p.id)
+            .collect();
+
+        for id in &expired {
+            self.purge_project(id)?;
+        }
+        Ok(expired.len())
+    }
+
+    pub fn trash_retention_days(&self) -> Result<u32> {
+        Ok(self
+            .read_settings()?
+            .trash_retention_days
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS))
+    }
+
+    pub fn set_trash_retention_days(&self, days: u32) -> Result<()> {
+        let mut settings = self.read_settings()?;
+        settings.trash_retention_days = Some(days);
+        self.write_settings(&settings)
+    }
+
+    /// A database file and the write-ahead log DuckDB may keep next to it
+    fn database_files(dir: &Path, database_file: &str) -> [PathBuf; 2] {
+        [
+            dir.join(database_file),
+            dir.join(format!("{}.wal", database_file)),
+        ]
+    }
+
+    fn move_database_files(from: &Path, to: &Path, database_file: &str) -> Result<()> {
+        for (source, target) in Self::database_files(from, database_file)
+            .into_iter()
+            .zip(Self::database_files(to, database_file))
+        {
+            if source.exists() {
+                fs::rename(source, target)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn update_project(&self, id: &str, name: Option<String>, description: Option<String>) -> Result<Project> {
         let mut file = self.read_projects()?;
 
@@ -167,6 +441,170 @@ impl StorageService {
         Ok(updated)
     }
 
+    /// Every project that isn't in the trash
+    pub fn list_full_projects(&self) -> Result<Vec<Project>> {
+        Ok(self
+            .read_projects()?
+            .projects
+            .into_iter()
+            .filter(|p| p.deleted_at.is_none())
+            .collect())
+    }
+
+    pub fn update_duckdb_settings(&self, id: &str, settings: DuckDbSettings) -> Result<Project> {
+        let mut file = self.read_projects()?;
+
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AppError::ProjectNotFound(id.to_string()))?;
+
+        project.duckdb_settings = settings;
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let updated = project.clone();
+        self.write_projects(&file)?;
+
+        Ok(updated)
+    }
+
+    pub fn update_embedding_model(
+        &self,
+        id: &str,
+        model: String,
+        dimension: usize,
+    ) -> Result<Project> {
+        let mut file = self.read_projects()?;
+
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AppError::ProjectNotFound(id.to_string()))?;
+
+        project.embedding_model = Some(model);
+        project.embedding_dimension = Some(dimension);
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let updated = project.clone();
+        self.write_projects(&file)?;
+
+        Ok(updated)
+    }
+
+    /// Set (or clear) the multimodal model used to describe image documents
+    pub fn update_vision_model(&self, id: &str, model: Option<String>) -> Result<Project> {
+        let mut file = self.read_projects()?;
+
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AppError::ProjectNotFound(id.to_string()))?;
+
+        project.vision_model = model;
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let updated = project.clone();
+        self.write_projects(&file)?;
+
+        Ok(updated)
+    }
+
+    /// Set (or clear) the Ollama model the project's chat uses by default
+    pub fn update_chat_model(&self, id: &str, model: Option<String>) -> Result<Project> {
+        let mut file = self.read_projects()?;
+
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AppError::ProjectNotFound(id.to_string()))?;
+
+        project.chat_model = model;
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let updated = project.clone();
+        self.write_projects(&file)?;
+
+        Ok(updated)
+    }
+
+    pub fn update_import_defaults(&self, id: &str, defaults: ImportDefaults) -> Result<Project> {
+        let mut file = self.read_projects()?;
+
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AppError::ProjectNotFound(id.to_string()))?;
+
+        project.import_defaults = defaults;
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let updated = project.clone();
+        self.write_projects(&file)?;
+
+        Ok(updated)
+    }
+
+    /// Recent imports, most recent first
+    pub fn list_recent_imports(&self) -> Result<Vec<RecentImport>> {
+        Ok(self.read_settings()?.recent_imports)
+    }
+
+    pub fn get_recent_import(&self, id: &str) -> Result<RecentImport> {
+        self.read_settings()?
+            .recent_imports
+            .into_iter()
+            .find(|r| r.id == id)
+            .ok_or_else(|| AppError::Custom(format!("Recent import not found: {}", id)))
+    }
+
+    /// Record an import, replacing any earlier entry for the same file and table
+    pub fn record_recent_import(
+        &self,
+        project_id: &str,
+        file_path: &str,
+        table_name: &str,
+        mode: ImportMode,
+        options: ImportOptions,
+    ) -> Result<RecentImport> {
+        let mut settings = self.read_settings()?;
+
+        let existing = settings.recent_imports.iter().position(|r| {
+            r.project_id == project_id && r.file_path == file_path && r.table_name == table_name
+        });
+        let id = match existing {
+            Some(i) => settings.recent_imports.remove(i).id,
+            None => Uuid::new_v4().to_string(),
+        };
+
+        let entry = RecentImport {
+            id,
+            project_id: project_id.to_string(),
+            file_path: file_path.to_string(),
+            table_name: table_name.to_string(),
+            mode,
+            options,
+            imported_at: chrono::Utc::now().to_rfc3339(),
+        };
+        settings.recent_imports.insert(0, entry.clone());
+        settings.recent_imports.truncate(MAX_RECENT_IMPORTS);
+        self.write_settings(&settings)?;
+
+        Ok(entry)
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone()
+    }
+
+    pub fn databases_dir(&self) -> PathBuf {
+        self.databases_dir.clone()
+    }
+
     pub fn get_database_path(&self, project: &Project) -> PathBuf {
         self.databases_dir.join(&project.database_file)
     }
@@ -1,7 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use parking_lot::Mutex;
+use tracing::{info, warn};
 
+use crate::error::AppError;
+use crate::models::ActiveJob;
 use crate::services::{DuckDbService, OllamaService, StorageService};
 
 pub struct AppState {
@@ -10,18 +13,86 @@ pub struct AppState {
     pub ollama: OllamaService,
     /// Set of table names that should cancel their vectorization
     pub vectorization_cancellations: Mutex<HashSet<String>>,
+    /// Set of document ids that should cancel their vectorization
+    pub document_vectorization_cancellations: Mutex<HashSet<String>>,
+    /// Set of stream ids whose streamed query should stop
+    pub query_cancellations: Mutex<HashSet<String>>,
+    /// The destructive or long-running operation each busy project is running
+    pub project_jobs: Mutex<HashMap<String, ActiveJob>>,
+}
+
+/// Held for the length of a project job; the project is free again once it drops
+pub struct ProjectJob<'a> {
+    state: &'a AppState,
+    project_id: String,
+}
+
+impl Drop for ProjectJob<'_> {
+    fn drop(&mut self) {
+        self.state.project_jobs.lock().remove(&self.project_id);
+    }
 }
 
 impl AppState {
     pub fn new() -> Result<Self, crate::error::AppError> {
+        let storage = StorageService::new()?;
+        let duckdb = DuckDbService::new();
+
+        // A failed purge just leaves the projects in the trash until next time
+        match storage.purge_expired_trash() {
+            Ok(0) => {}
+            Ok(purged) => {
+                info!(target: "storage", "Purged {} expired project(s) from the trash", purged)
+            }
+            Err(e) => warn!(target: "storage", "Failed to purge expired trash: {}", e),
+        }
+
+        // Register persisted DuckDB settings so they apply when connections open
+        for project in storage.list_full_projects()? {
+            duckdb.register_settings(&project.id, project.duckdb_settings);
+        }
+
         Ok(AppState {
-            storage: Mutex::new(StorageService::new()?),
-            duckdb: DuckDbService::new(),
+            storage: Mutex::new(storage),
+            duckdb,
             ollama: OllamaService::new(),
             vectorization_cancellations: Mutex::new(HashSet::new()),
+            document_vectorization_cancellations: Mutex::new(HashSet::new()),
+            query_cancellations: Mutex::new(HashSet::new()),
+            project_jobs: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Claim a project for an operation that mustn't overlap with another one (embedding,
+    /// dropping or clearing data). Fails while another such operation is running;
+    /// plain reads never take this lock.
+    pub fn start_project_job(
+        &self,
+        project_id: &str,
+        operation: &str,
+    ) -> Result<ProjectJob<'_>, AppError> {
+        let mut jobs = self.project_jobs.lock();
+        if let Some(job) = jobs.get(project_id) {
+            return Err(AppError::Custom(format!("Project is busy: {}", job.operation)));
+        }
+        jobs.insert(
+            project_id.to_string(),
+            ActiveJob {
+                operation: operation.to_string(),
+                started_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+        Ok(ProjectJob {
+            state: self,
+            project_id: project_id.to_string(),
         })
     }
 
+    /// The operation holding a project's lock, if any
+    pub fn active_jobs(&self, project_id: &str) -> Vec<ActiveJob> {
+        self.project_jobs.lock().get(project_id).cloned().into_iter().collect()
+    }
+
     /// Request cancellation of vectorization for a table
     pub fn cancel_vectorization(&self, table_name: &str) {
         self.vectorization_cancellations.lock().insert(table_name.to_string());
@@ -36,4 +107,36 @@ impl AppState {
     pub fn clear_vectorization_cancellation(&self, table_name: &str) {
         self.vectorization_cancellations.lock().remove(table_name);
     }
+
+    /// Request cancellation of vectorization for a document
+    pub fn cancel_document_vectorization(&self, document_id: &str) {
+        self.document_vectorization_cancellations
+            .lock()
+            .insert(document_id.to_string());
+    }
+
+    /// Check if vectorization should be cancelled for a document
+    pub fn should_cancel_document_vectorization(&self, document_id: &str) -> bool {
+        self.document_vectorization_cancellations.lock().contains(document_id)
+    }
+
+    /// Clear cancellation flag for a document
+    pub fn clear_document_vectorization_cancellation(&self, document_id: &str) {
+        self.document_vectorization_cancellations.lock().remove(document_id);
+    }
+
+    /// Request cancellation of a streamed query
+    pub fn cancel_query_stream(&self, stream_id: &str) {
+        self.query_cancellations.lock().insert(stream_id.to_string());
+    }
+
+    /// Check if a streamed query should stop
+    pub fn should_cancel_query_stream(&self, stream_id: &str) -> bool {
+        self.query_cancellations.lock().contains(stream_id)
+    }
+
+    /// Clear cancellation flag for a streamed query
+    pub fn clear_query_stream_cancellation(&self, stream_id: &str) {
+        self.query_cancellations.lock().remove(stream_id);
+    }
 }
@@ -1,17 +1,48 @@
-import { useState, useCallback } from "react";
+import { useState, useCallback, useEffect, useRef } from "react";
 import { Send } from "lucide-react";
 import { Button } from "@/components/ui/button";
+import { getDraft, saveDraft } from "@/lib/tauri";
 
 interface ChatInputProps {
 	onSend: (message: string) => void;
 	disabled?: boolean;
+	projectId?: string;
+	conversationId?: string | null; // Drafts are kept per conversation when set
 }
 
 export function ChatInput({
 	onSend,
 	disabled,
+	projectId,
+	conversationId,
 }: ChatInputProps) {
 	const [input, setInput] = useState("");
+	const draftTarget = useRef({ projectId, conversationId });
+	draftTarget.current = { projectId, conversationId };
+
+	// Restore the conversation's unsent message
+	useEffect(() => {
+		if (!projectId || !conversationId) return;
+		let cancelled = false;
+		getDraft(projectId, conversationId)
+			.then((draft) => {
+				if (!cancelled) setInput(draft ?? "");
+			})
+			.catch(() => {});
+		return () => {
+			cancelled = true;
+		};
+	}, [projectId, conversationId]);
+
+	// Save while typing, against the conversation the text was typed in
+	useEffect(() => {
+		const { projectId, conversationId } = draftTarget.current;
+		if (!projectId || !conversationId) return;
+		const timer = setTimeout(() => {
+			saveDraft(projectId, conversationId, input).catch(() => {});
+		}, 500);
+		return () => clearTimeout(timer);
+	}, [input]);
 
 	const handleSubmit = useCallback(
 		(e: React.FormEvent) => {
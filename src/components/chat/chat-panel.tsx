@@ -1,4 +1,4 @@
-import { useEffect, useRef, useCallback, useState } from "react";
+import { useEffect, useRef, useCallback, useMemo, useState } from "react";
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { listen } from "@tauri-apps/api/event";
 import ReactMarkdown from "react-markdown";
@@ -29,7 +29,6 @@ import { ChatInput } from "./chat-input";
 import {
 	DataVisualization,
 	type VisualizationConfig,
-	type VizType,
 } from "./data-visualization";
 import { OllamaSetup, OllamaModelManager } from "@/components/ollama";
 import { useChatStore } from "@/stores";
@@ -37,8 +36,12 @@ import {
 	listOllamaModels,
 	checkOllamaStatus,
 	sendChatMessage,
+	parseVizSpec,
+	getProjectChatModel,
+	setProjectChatModel,
+	generateConversationTitle,
 	getProjectContext,
-	executeQuery,
+	executeChatQuery,
 	getTables,
 	semanticSearch,
 	semanticSearchDocuments,
@@ -46,6 +49,7 @@ import {
 	getDocuments,
 	listConversations,
 	createConversation,
+	updateConversation,
 	getConversation,
 	deleteConversation,
 	addMessage,
@@ -56,22 +60,13 @@ import type {
 	QueryResult,
 	SemanticSearchResult,
 	DocumentSearchResult,
+	VizSpec,
 } from "@/types";
 
 interface ChatPanelProps {
 	projectId: string;
 }
 
-// Common embedding model name patterns to filter out from chat
-const EMBEDDING_MODEL_PATTERNS = [
-	"embed",
-	"all-minilm",
-	"bge-",
-	"e5-",
-	"gte-",
-	"paraphrase",
-];
-
 // Fun loading messages
 const LOADING_MESSAGES = [
 	"Thinking...",
@@ -92,51 +87,53 @@ const LOADING_MESSAGES = [
 	"Simmering...",
 ];
 
-function isEmbeddingModel(modelName: string): boolean {
-	const name = modelName.toLowerCase();
-	return EMBEDDING_MODEL_PATTERNS.some((pattern) => name.includes(pattern));
-}
-
-// Parsed query block from AI response
-interface QueryBlock {
+// Run a query block, retrying once with the real name when the model misspelled
+// a table or column and the closest match is clear
+async function runQueryBlock(
+	projectId: string,
+	block: VizSpec,
+): Promise<{
+	config: VisualizationConfig;
+	result?: QueryResult;
+	error?: string;
 	sql: string;
-	viz: VizType;
-	xKey?: string;
-	yKey?: string;
-}
-
-// Extract duckbake query blocks from content
-function extractQueryBlocks(content: string): {
-	blocks: QueryBlock[];
-	cleanContent: string;
-} {
-	const regex = /```duckbake\n([\s\S]*?)```/gi;
-	const blocks: QueryBlock[] = [];
-	let cleanContent = content;
-
-	let match;
-	while ((match = regex.exec(content)) !== null) {
-		try {
-			const json = JSON.parse(match[1].trim());
-			if (json.sql) {
-				blocks.push({
-					sql: json.sql,
-					viz: json.viz || "table",
-					xKey: json.xKey,
-					yKey: json.yKey,
-				});
-			}
-		} catch {
-			// Invalid JSON, skip
+}> {
+	try {
+		const outcome = await executeChatQuery(projectId, block.sql, true);
+		if (outcome.result) {
+			return {
+				config: {
+					type: block.viz,
+					xKey: block.xKey ?? undefined,
+					yKey: block.yKey ?? undefined,
+				},
+				result: outcome.result,
+				sql: outcome.sql,
+			};
 		}
-		// Remove the block from display content
-		cleanContent = cleanContent.replace(match[0], "");
+		const hint = outcome.didYouMean
+			? ` Did you mean "${outcome.didYouMean.didYouMean}"?`
+			: "";
+		return {
+			config: { type: block.viz },
+			error: `${outcome.error}${hint}`,
+			sql: outcome.sql,
+		};
+	} catch (error) {
+		return {
+			config: { type: block.viz },
+			error: error instanceof Error ? error.message : String(error),
+			sql: block.sql,
+		};
 	}
+}
 
-	// Clean up extra whitespace
-	cleanContent = cleanContent.replace(/\n{3,}/g, "\n\n").trim();
-
-	return { blocks, cleanContent };
+// Remove duckbake query blocks from content for display; parseVizSpec reads them
+function stripQueryBlocks(content: string): string {
+	return content
+		.replace(/```duckbake\n[\s\S]*?```/gi, "")
+		.replace(/\n{3,}/g, "\n\n")
+		.trim();
 }
 
 // Build context string from project data
@@ -206,10 +203,25 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 		return localStorage.getItem("selected-ollama-model") || "";
 	});
 
+	// Picking a model makes it the project's default; the last pick anywhere is the
+	// fallback for projects that haven't chosen one
 	const setSelectedModel = (model: string) => {
 		setSelectedModelState(model);
 		localStorage.setItem("selected-ollama-model", model);
+		setProjectChatModel(projectId, model).catch((e) => {
+			console.error("Failed to save chat model:", e);
+		});
 	};
+
+	useEffect(() => {
+		getProjectChatModel(projectId)
+			.then((model) => {
+				if (model) setSelectedModelState(model);
+			})
+			.catch((e) => {
+				console.error("Failed to load chat model:", e);
+			});
+	}, [projectId]);
 	const [sidebarWidth, setSidebarWidth] = useState(224);
 	const isResizing = useRef(false);
 	const [showSetup, setShowSetup] = useState(false);
@@ -295,12 +307,16 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 		refetchInterval: 10000,
 	});
 
-	const { data: models = [] } = useQuery({
+	const { data: allModels = [] } = useQuery({
 		queryKey: ["ollama-models"],
 		queryFn: listOllamaModels,
 		enabled: ollamaStatus?.connected,
-		select: (data) => data.filter((model) => !isEmbeddingModel(model.name)),
 	});
+	// Embedding-only models can't chat, so they stay out of the picker
+	const models = useMemo(
+		() => allModels.filter((model) => !model.isEmbedding),
+		[allModels],
+	);
 
 	const { data: projectContext } = useQuery({
 		queryKey: ["project-context", projectId],
@@ -352,7 +368,7 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 		if (models.length > 0) {
 			const savedModelExists = models.some((m) => m.name === selectedModel);
 			if (!selectedModel || !savedModelExists) {
-				setSelectedModel(models[0].name);
+				setSelectedModelState(models[0].name);
 			}
 		}
 	}, [models, selectedModel]);
@@ -429,8 +445,11 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 		async (content: string) => {
 			const messageId = crypto.randomUUID();
 
-			// Extract query blocks
-			const { blocks } = extractQueryBlocks(content);
+			// Extract query blocks, dropping any the backend finds malformed
+			const blocks = await parseVizSpec(content).catch((e) => {
+				console.error("Failed to parse query blocks:", e);
+				return [];
+			});
 
 			// Update the message content to the cleaned version (without duckbake blocks)
 			// We need to finalize with the full content first, then we'll render cleanContent
@@ -460,24 +479,7 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 				}[] = [];
 
 				for (const block of blocks) {
-					try {
-						const result = await executeQuery(projectId, block.sql);
-						results.push({
-							config: {
-								type: block.viz,
-								xKey: block.xKey,
-								yKey: block.yKey,
-							},
-							result,
-							sql: block.sql,
-						});
-					} catch (error) {
-						results.push({
-							config: { type: block.viz },
-							error: error instanceof Error ? error.message : String(error),
-							sql: block.sql,
-						});
-					}
+					results.push(await runQueryBlock(projectId, block));
 				}
 
 				// Store results keyed by message ID
@@ -562,7 +564,7 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 			for (const message of messages) {
 				if (message.role !== "assistant") continue;
 
-				const { blocks } = extractQueryBlocks(message.content);
+				const blocks = await parseVizSpec(message.content).catch(() => []);
 				if (blocks.length === 0) continue;
 
 				const results: {
@@ -573,24 +575,7 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 				}[] = [];
 
 				for (const block of blocks) {
-					try {
-						const result = await executeQuery(projectId, block.sql);
-						results.push({
-							config: {
-								type: block.viz,
-								xKey: block.xKey,
-								yKey: block.yKey,
-							},
-							result,
-							sql: block.sql,
-						});
-					} catch (error) {
-						results.push({
-							config: { type: block.viz },
-							error: error instanceof Error ? error.message : String(error),
-							sql: block.sql,
-						});
-					}
+					results.push(await runQueryBlock(projectId, block));
 				}
 
 				if (results.length > 0) {
@@ -639,6 +624,13 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 					addConversation(convo);
 					refetchConversations();
 					conversationId = convo.id;
+
+					// Swap in a model-written title when it arrives, keeping the quick one on failure
+					const newId = convo.id;
+					generateConversationTitle(selectedModel, content)
+						.then((generated) => updateConversation(projectId, newId, generated))
+						.then(() => refetchConversations())
+						.catch((e) => console.warn("Failed to generate conversation title:", e));
 				} catch (e) {
 					console.error("Failed to create conversation:", e);
 					return;
@@ -715,7 +707,7 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 			let documentResults: DocumentSearchResult[] | undefined;
 			if (searchDocs) {
 				try {
-					const docs = await getDocuments(projectId);
+					const { documents: docs } = await getDocuments(projectId);
 					const vectorizedDocs = docs.filter((d) => d.isVectorized);
 
 					if (vectorizedDocs.length > 0) {
@@ -809,12 +801,8 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 	}
 
 	// Check if models are available - if not, show setup (unless dismissed)
-	const hasEmbeddingModel = models.some(
-		(m) =>
-			m.name.toLowerCase().includes("nomic") ||
-			m.name.toLowerCase().includes("embed"),
-	);
-	const hasChatModel = models.some((m) => !isEmbeddingModel(m.name));
+	const hasEmbeddingModel = allModels.some((m) => m.isEmbedding);
+	const hasChatModel = models.length > 0;
 
 	if (!setupDismissed && (!hasEmbeddingModel || !hasChatModel)) {
 		return (
@@ -1031,6 +1019,8 @@ export function ChatPanel({ projectId }: ChatPanelProps) {
 				<ChatInput
 					onSend={handleSend}
 					disabled={isStreaming || searchStatus?.searching}
+					projectId={projectId}
+					conversationId={currentConversationId}
 				/>
 			</div>
 		</div>
@@ -1051,7 +1041,7 @@ function MessageBubble({ message, visualizations }: MessageBubbleProps) {
 	const isUser = message.role === "user";
 
 	// Get cleaned content (without duckbake blocks) for display
-	const { cleanContent } = extractQueryBlocks(message.content);
+	const cleanContent = stripQueryBlocks(message.content);
 	const displayContent = cleanContent || message.content;
 
 	return (
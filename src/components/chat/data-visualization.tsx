@@ -17,9 +17,9 @@ import {
 import { Code } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { useAppStore } from "@/stores";
-import type { QueryResult } from "@/types";
+import type { QueryResult, VizType } from "@/types";
 
-export type VizType = "table" | "bar" | "line" | "pie";
+export type { VizType };
 
 export interface VisualizationConfig {
 	type: VizType;
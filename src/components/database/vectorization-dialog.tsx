@@ -37,6 +37,7 @@ export function VectorizationDialog({
   const queryClient = useQueryClient();
   const [selectedColumns, setSelectedColumns] = useState<string[]>([]);
   const [progress, setProgress] = useState<VectorizationProgress | null>(null);
+  const [whereClause, setWhereClause] = useState("");
 
   const { data: status, isLoading: statusLoading } = useQuery({
     queryKey: ["vectorization-status", projectId, tableName],
@@ -91,7 +92,17 @@ export function VectorizationDialog({
   const [mutationError, setMutationError] = useState<string | null>(null);
 
   const vectorizeMutation = useMutation({
-    mutationFn: () => vectorizeTable(projectId, tableName, selectedColumns),
+    mutationFn: () =>
+      vectorizeTable(
+        projectId,
+        tableName,
+        selectedColumns,
+        undefined,
+        undefined,
+        undefined,
+        undefined,
+        whereClause.trim() || undefined
+      ),
     onSuccess: () => {
       setMutationError(null);
       queryClient.invalidateQueries({
@@ -129,7 +140,10 @@ export function VectorizationDialog({
 
   const isProcessing =
     vectorizeMutation.isPending ||
-    ((progress?.status === "processing" || progress?.status === "loading_model") &&
+    ((progress?.status === "processing" ||
+      progress?.status === "embedding_batch" ||
+      progress?.status === "reloading_model" ||
+      progress?.status === "loading_model") &&
       progress.tableName === tableName);
 
   const handleClose = () => {
@@ -170,6 +184,12 @@ export function VectorizationDialog({
                   </p>
                   <p>Model: {status.embeddingModel || "unknown"}</p>
                   <p>Columns: {status.vectorizedColumns.join(", ")}</p>
+                  {status.isPartial && (
+                    <p>
+                      Partial: only rows where{" "}
+                      <code className="bg-muted px-1 py-0.5 rounded">{status.rowFilter}</code>
+                    </p>
+                  )}
                 </div>
               </div>
             ) : !isProcessing ? (
@@ -199,6 +219,16 @@ export function VectorizationDialog({
                         </label>
                       ))}
                     </div>
+                    <div className="space-y-1">
+                      <p className="text-sm font-medium">Only rows where (optional):</p>
+                      <input
+                        type="text"
+                        value={whereClause}
+                        onChange={(e) => setWhereClause(e.target.value)}
+                        placeholder="e.g. status = 'active'"
+                        className="w-full h-8 px-2 text-sm font-mono border rounded bg-background"
+                      />
+                    </div>
                     <p className="text-xs text-muted-foreground">
                       Requires the <code className="bg-muted px-1 py-0.5 rounded">nomic-embed-text</code> Ollama model
                     </p>
@@ -213,7 +243,11 @@ export function VectorizationDialog({
                   <span>
                     {progress.status === "loading_model"
                       ? "Loading embedding model..."
-                      : "Vectorizing..."}
+                      : progress.status === "reloading_model"
+                        ? "Reloading embedding model..."
+                        : progress.status === "embedding_batch"
+                          ? `Embedding batch ${progress.batchIndex}...`
+                          : "Vectorizing..."}
                   </span>
                 </div>
                 {progress.status === "loading_model" ? (
@@ -41,7 +41,7 @@ export function DocumentList({ projectId, onUploadClick, onSelectDocument }: Doc
 
   const { data: documents = [], isLoading } = useQuery({
     queryKey: ["documents", projectId],
-    queryFn: () => getDocuments(projectId),
+    queryFn: async () => (await getDocuments(projectId)).documents,
   });
 
   const deleteDocumentMutation = useMutation({
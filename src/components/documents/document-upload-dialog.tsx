@@ -57,7 +57,7 @@ export function DocumentUploadDialog({
         filters: [
           {
             name: "Documents",
-            extensions: ["txt", "md", "markdown", "docx", "pdf"],
+            extensions: ["txt", "md", "markdown", "docx", "pdf", "png", "jpg", "jpeg"],
           },
         ],
       });
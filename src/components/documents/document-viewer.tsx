@@ -10,6 +10,7 @@ import {
   Hash,
   User,
   Calendar,
+  X,
 } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { ScrollArea } from "@/components/ui/scroll-area";
@@ -21,7 +22,12 @@ import {
   DialogHeader,
   DialogTitle,
 } from "@/components/ui/dialog";
-import { getDocument, deleteDocument, vectorizeDocument } from "@/lib/tauri";
+import {
+  getDocument,
+  deleteDocument,
+  vectorizeDocument,
+  cancelDocumentVectorization,
+} from "@/lib/tauri";
 import { useDocumentStore } from "@/stores";
 import type { DocumentVectorizationProgress } from "@/types";
 
@@ -80,6 +86,10 @@ export function DocumentViewer({ projectId, documentId }: DocumentViewerProps) {
     mutationFn: () => vectorizeDocument(projectId, documentId),
   });
 
+  const cancelMutation = useMutation({
+    mutationFn: () => cancelDocumentVectorization(documentId),
+  });
+
   if (isLoading) {
     return (
       <div className="flex items-center justify-center h-full">
@@ -155,6 +165,16 @@ export function DocumentViewer({ projectId, documentId }: DocumentViewerProps) {
               <span>
                 {progress.processedChunks}/{progress.totalChunks} chunks
               </span>
+              <Button
+                size="icon"
+                variant="ghost"
+                className="h-6 w-6"
+                title="Stop vectorizing"
+                onClick={() => cancelMutation.mutate()}
+                disabled={cancelMutation.isPending}
+              >
+                <X className="h-3 w-3" />
+              </Button>
             </div>
           )}
           {document.isVectorized && !vectorizing && (
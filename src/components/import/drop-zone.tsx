@@ -49,14 +49,9 @@ export function DropZone({ projectId }: DropZoneProps) {
 	const { setActiveTab } = useAppStore();
 
 	const importFileMutation = useMutation({
-		mutationFn: async ({
-			filePath,
-			tableName,
-		}: {
-			filePath: string;
-			tableName: string;
-		}) => {
-			return importFile(projectId, filePath, tableName, "create");
+		// Table name and mode come from the project's import defaults
+		mutationFn: async ({ filePath }: { filePath: string }) => {
+			return importFile(projectId, filePath);
 		},
 		onSuccess: () => {
 			queryClient.invalidateQueries({ queryKey: ["tables", projectId] });
@@ -106,25 +101,20 @@ export function DropZone({ projectId }: DropZoneProps) {
 		}
 
 		if (isDataFile) {
-			// Generate table name from file name
-			const tableName = fileName
-				.replace(/\.[^/.]+$/, "") // Remove extension
-				.replace(/[^a-zA-Z0-9_]/g, "_") // Replace invalid chars
-				.replace(/^_+|_+$/g, "") // Trim underscores
-				.replace(/_+/g, "_") // Collapse multiple underscores
-				.toLowerCase();
-
 			setImports((prev) => [
 				...prev,
-				{ fileName, status: "importing", type: "data", tableName },
+				{ fileName, status: "importing", type: "data" },
 			]);
 
 			try {
-				await importFileMutation.mutateAsync({ filePath, tableName });
+				const result = await importFileMutation.mutateAsync({ filePath });
+				const tableName = result.tableName;
 
 				setImports((prev) =>
 					prev.map((i) =>
-						i.fileName === fileName ? { ...i, status: "success" } : i,
+						i.fileName === fileName
+							? { ...i, status: "success", tableName }
+							: i,
 					),
 				);
 
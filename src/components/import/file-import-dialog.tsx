@@ -1,4 +1,4 @@
-import { useState } from "react";
+import { useRef, useState } from "react";
 import { useMutation, useQueryClient } from "@tanstack/react-query";
 import { open } from "@tauri-apps/plugin-dialog";
 import { FileSpreadsheet, Upload, Table, AlertCircle } from "lucide-react";
@@ -20,7 +20,12 @@ import {
   SelectValue,
 } from "@/components/ui/select";
 import { ScrollArea } from "@/components/ui/scroll-area";
-import { previewImport, importFile } from "@/lib/tauri";
+import {
+  previewImport,
+  estimateRowCount,
+  importFile,
+  getImportTarget,
+} from "@/lib/tauri";
 import type { ImportPreview, ImportMode } from "@/types";
 
 interface FileImportDialogProps {
@@ -41,10 +46,14 @@ export function FileImportDialog({
   const [importMode, setImportMode] = useState<ImportMode>("create");
   const [isLoadingPreview, setIsLoadingPreview] = useState(false);
   const [previewError, setPreviewError] = useState<string | null>(null);
+  // Only the latest selected file may update the row count
+  const latestFileRef = useRef<string | null>(null);
 
   const importMutation = useMutation({
     mutationFn: () =>
-      importFile(projectId, selectedFile!, tableName, importMode),
+      importFile(projectId, selectedFile!, tableName, importMode, {
+        dedupeColumns: (preview?.duplicateColumns.length ?? 0) > 0,
+      }),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["tables", projectId] });
       handleClose();
@@ -75,6 +84,7 @@ export function FileImportDialog({
 
       if (file) {
         setSelectedFile(file);
+        latestFileRef.current = file;
         setPreviewError(null);
         setIsLoadingPreview(true);
 
@@ -82,12 +92,20 @@ export function FileImportDialog({
           const previewData = await previewImport(projectId, file);
           setPreview(previewData);
 
-          // Generate default table name from file name
-          const fileName = previewData.fileName
-            .replace(/\.[^/.]+$/, "") // Remove extension
-            .replace(/[^a-zA-Z0-9_]/g, "_") // Replace invalid chars
-            .toLowerCase();
-          setTableName(fileName);
+          // Counting rows needs a full scan, so fill it in when it arrives
+          estimateRowCount(projectId, file)
+            .then((count) => {
+              if (latestFileRef.current !== file) return;
+              setPreview((current) =>
+                current ? { ...current, totalRowsEstimate: count } : current
+              );
+            })
+            .catch((err) => console.error("Failed to count rows:", err));
+
+          // Start from the project's import defaults
+          const target = await getImportTarget(projectId, file);
+          setTableName(target.tableName);
+          setImportMode(target.mode);
         } catch (err) {
           setPreviewError(
             err instanceof Error ? err.message : "Failed to preview file"
@@ -104,6 +122,7 @@ export function FileImportDialog({
 
   const handleClose = () => {
     setSelectedFile(null);
+    latestFileRef.current = null;
     setPreview(null);
     setTableName("");
     setImportMode("create");
@@ -180,6 +199,16 @@ export function FileImportDialog({
                 )}
               </div>
 
+              {preview.duplicateColumns.length > 0 && (
+                <div className="flex items-center gap-2 p-3 bg-yellow-500/10 text-yellow-700 dark:text-yellow-400 rounded-md">
+                  <AlertCircle className="h-4 w-4" />
+                  <span className="text-sm">
+                    Duplicate column names will be renamed:{" "}
+                    {preview.duplicateColumns.join(", ")}
+                  </span>
+                </div>
+              )}
+
               {/* Table Name & Mode */}
               <div className="grid grid-cols-2 gap-4">
                 <div className="space-y-2">
@@ -320,8 +349,22 @@ export function FileImportDialog({
             <span className="text-sm">
               {importMutation.error instanceof Error
                 ? importMutation.error.message
-                : "Import failed"}
+                : String(importMutation.error || "Import failed")}
             </span>
+            {importMode === "append" &&
+              String(importMutation.error).includes("Table not found") && (
+                <Button
+                  size="sm"
+                  variant="outline"
+                  className="ml-auto"
+                  onClick={() => {
+                    setImportMode("create");
+                    importMutation.reset();
+                  }}
+                >
+                  Create table instead
+                </Button>
+              )}
           </div>
         )}
       </DialogContent>
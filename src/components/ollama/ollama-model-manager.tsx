@@ -141,18 +141,6 @@ export function OllamaModelManager({ onBack }: OllamaModelManagerProps) {
   const hasModel = (name: string) =>
     models.some((m) => m.name === name || m.name.startsWith(name.split(":")[0] + ":"));
 
-  const isEmbeddingModel = (name: string) => {
-    const lower = name.toLowerCase();
-    return (
-      lower.includes("embed") ||
-      lower.includes("nomic") ||
-      lower.includes("minilm") ||
-      lower.includes("bge") ||
-      lower.includes("e5-") ||
-      lower.includes("gte-")
-    );
-  };
-
   const filteredPopularModels = POPULAR_MODELS.filter(
     (m) =>
       m.name.toLowerCase().includes(searchQuery.toLowerCase()) ||
@@ -260,7 +248,7 @@ export function OllamaModelManager({ onBack }: OllamaModelManagerProps) {
                     className="flex items-center justify-between p-3 border rounded-lg"
                   >
                     <div className="flex items-center gap-3">
-                      {isEmbeddingModel(model.name) ? (
+                      {model.isEmbedding ? (
                         <Sparkles className="h-5 w-5 text-primary" />
                       ) : (
                         <MessageSquare className="h-5 w-5 text-muted-foreground" />
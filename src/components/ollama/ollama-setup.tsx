@@ -119,12 +119,7 @@ export function OllamaSetup({ onComplete, onManageModels }: OllamaSetupProps) {
       const hasEmbedding = models.some((m) =>
         m.name.startsWith(RECOMMENDED_MODELS.embedding.name)
       );
-      const hasChatModel = models.some(
-        (m) =>
-          !m.name.includes("embed") &&
-          !m.name.includes("nomic") &&
-          !m.name.includes("bge")
-      );
+      const hasChatModel = models.some((m) => !m.isEmbedding);
 
       if (!hasEmbedding || !hasChatModel) {
         setStep("models");
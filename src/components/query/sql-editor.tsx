@@ -1,10 +1,21 @@
 import { useState, useCallback, useEffect, useMemo, useRef } from "react";
-import { useMutation, useQuery } from "@tanstack/react-query";
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
 import CodeMirror from "@uiw/react-codemirror";
 import { sql } from "@codemirror/lang-sql";
 import { EditorView } from "@codemirror/view";
 import { catppuccinMocha, catppuccinLatte } from "@catppuccin/codemirror";
-import { Play, Clock, AlertCircle, Save, FileCode, Trash2, Pencil, Check, X } from "lucide-react";
+import {
+  Play,
+  Clock,
+  AlertCircle,
+  Save,
+  FileCode,
+  Trash2,
+  Pencil,
+  Check,
+  X,
+  TableProperties,
+} from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { ScrollArea } from "@/components/ui/scroll-area";
@@ -12,6 +23,8 @@ import { DataGrid } from "@/components/database";
 import { useAppStore, useProjectStore } from "@/stores";
 import {
   executeQuery,
+  validateQuery,
+  materializeQuery,
   listSavedQueries,
   saveQuery,
   updateSavedQuery,
@@ -52,6 +65,9 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
   const [editingName, setEditingName] = useState("");
   const [showSaveInput, setShowSaveInput] = useState(false);
   const [newQueryName, setNewQueryName] = useState("");
+  const [showTableInput, setShowTableInput] = useState(false);
+  const [newTableName, setNewTableName] = useState("");
+  const queryClient = useQueryClient();
   const pendingSql = useAppStore((s) => s.pendingSql);
   const setPendingSql = useAppStore((s) => s.setPendingSql);
   const pendingExecuted = useRef(false);
@@ -100,6 +116,21 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
     staleTime: 30000,
   });
 
+  // Validate as the user types, once they pause
+  const [debouncedQuery, setDebouncedQuery] = useState(query);
+  useEffect(() => {
+    const timer = setTimeout(() => setDebouncedQuery(query), 400);
+    return () => clearTimeout(timer);
+  }, [query]);
+
+  const { data: validation } = useQuery({
+    queryKey: ["validate-query", projectId, debouncedQuery],
+    queryFn: () => validateQuery(projectId, debouncedQuery),
+    enabled: !!debouncedQuery.trim(),
+    staleTime: 30000,
+  });
+  const liveError = debouncedQuery === query && validation?.error ? validation.error : null;
+
   // Build schema for CodeMirror SQL autocomplete
   const sqlSchema = useMemo(() => {
     if (!projectContext?.tables) return {};
@@ -139,6 +170,17 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
     },
   });
 
+  const materializeMutation = useMutation({
+    mutationFn: ({ sql, tableName }: { sql: string; tableName: string }) =>
+      materializeQuery(projectId, sql, tableName, "create"),
+    onSuccess: () => {
+      setShowTableInput(false);
+      setNewTableName("");
+      queryClient.invalidateQueries({ queryKey: ["tables", projectId] });
+      queryClient.invalidateQueries({ queryKey: ["project-context", projectId] });
+    },
+  });
+
   const saveMutation = useMutation({
     mutationFn: ({ name, sql }: { name: string; sql: string }) =>
       saveQuery(projectId, name, sql),
@@ -216,6 +258,13 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
     }
   };
 
+  const handleMaterialize = () => {
+    const tableName = newTableName.trim();
+    if (tableName && query.trim()) {
+      materializeMutation.mutate({ tableName, sql: query.trim() });
+    }
+  };
+
   const handleLoadQuery = (saved: SavedQuery) => {
     setQuery(saved.sql);
     setCurrentQueryId(saved.id);
@@ -346,7 +395,62 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
               foldGutter: false,
             }}
           />
+          {liveError && (
+            <div
+              className="absolute bottom-2 left-12 max-w-[50%] truncate text-xs text-destructive"
+              title={liveError.message}
+            >
+              {liveError.line !== null &&
+                `Line ${liveError.line}${liveError.column !== null ? `:${liveError.column}` : ""}: `}
+              {liveError.message}
+            </div>
+          )}
           <div className="absolute bottom-2 right-2 flex items-center gap-2">
+            {showTableInput ? (
+              <div className="flex items-center gap-1 bg-background border rounded-md px-2 py-1 shadow-md">
+                <Input
+                  value={newTableName}
+                  onChange={(e) => setNewTableName(e.target.value)}
+                  placeholder="Table name..."
+                  className="h-6 w-32 text-xs border-0 p-0 focus-visible:ring-0"
+                  autoFocus
+                  onKeyDown={(e) => {
+                    if (e.key === "Enter") handleMaterialize();
+                    if (e.key === "Escape") {
+                      setShowTableInput(false);
+                      setNewTableName("");
+                    }
+                  }}
+                />
+                <button
+                  className="p-1 hover:text-primary"
+                  onClick={handleMaterialize}
+                  disabled={!newTableName.trim() || materializeMutation.isPending}
+                >
+                  <Check className="h-3.5 w-3.5" />
+                </button>
+                <button
+                  className="p-1 hover:text-muted-foreground"
+                  onClick={() => {
+                    setShowTableInput(false);
+                    setNewTableName("");
+                  }}
+                >
+                  <X className="h-3.5 w-3.5" />
+                </button>
+              </div>
+            ) : (
+              <Button
+                size="icon"
+                variant="outline"
+                className="rounded-full h-8 w-8 shadow-md"
+                onClick={() => setShowTableInput(true)}
+                disabled={!query.trim()}
+                title="Save results as table"
+              >
+                <TableProperties className="h-4 w-4" />
+              </Button>
+            )}
             {showSaveInput ? (
               <div className="flex items-center gap-1 bg-background border rounded-md px-2 py-1 shadow-md">
                 <Input
@@ -415,6 +519,17 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
 
         {/* Results */}
         <div className="flex-1 overflow-hidden flex flex-col">
+          {materializeMutation.isError && (
+            <div className="p-3 bg-destructive/10 text-destructive flex items-start gap-2">
+              <AlertCircle className="h-4 w-4 mt-0.5 shrink-0" />
+              <span className="text-sm">
+                {materializeMutation.error instanceof Error
+                  ? materializeMutation.error.message
+                  : String(materializeMutation.error)}
+              </span>
+            </div>
+          )}
+
           {executeMutation.isError && (
             <div className="p-3 bg-destructive/10 text-destructive flex items-start gap-2">
               <AlertCircle className="h-4 w-4 mt-0.5 shrink-0" />
@@ -1,27 +1,72 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 import type {
   Project,
   ProjectSummary,
   ProjectStats,
+  ActiveJob,
+  GlobalSearchResult,
+  DataDirInfo,
+  DuckDbSettings,
   CreateProjectInput,
   TableInfo,
+  TableSummary,
+  StorageBreakdown,
+  ClearTarget,
+  ClearedData,
   TableSchema,
+  GeneratedColumn,
+  ColumnTypeChange,
+  DedupeResult,
+  TableDiff,
   QueryResult,
+  ChatQueryResult,
+  AggregateFn,
+  AggregateResult,
+  TimeBucket,
+  QueryValidation,
+  QueryStreamEvent,
+  SeekPage,
+  TableNote,
+  RowCount,
+  DistinctValues,
   ProjectContext,
+  ConnectionCapabilities,
+  AttachedDatabase,
+  OpenConnection,
   OllamaStatus,
   OllamaModel,
+  ResponseMode,
+  VizSpec,
+  VizSuggestion,
   ImportPreview,
+  FilePeek,
   ImportResult,
   ImportMode,
+  ImportOptions,
+  ImportDefaults,
+  ImportTarget,
+  ImportValidation,
+  RecentImport,
   VectorizationStatus,
+  ExtractionResult,
   SemanticSearchResult,
+  SimilarityMetric,
+  ExportFormat,
+  ExportResult,
   Conversation,
   ConversationWithMessages,
   ChatMessage,
   SavedQuery,
   Document,
   DocumentInfo,
+  DocumentPage,
+  DocumentChunkPage,
+  ChunkConfig,
+  BulkDocumentResult,
+  DocumentRevectorization,
   DocumentSearchResult,
+  HealthCheck,
+  LogLevel,
 } from "@/types";
 
 // Project commands
@@ -46,6 +91,37 @@ export async function deleteProject(id: string): Promise<void> {
   return invoke("delete_project", { id });
 }
 
+export async function listTrashedProjects(): Promise<Project[]> {
+  return invoke("list_trashed_projects");
+}
+
+export async function restoreProject(id: string): Promise<Project> {
+  return invoke("restore_project", { id });
+}
+
+export async function purgeProject(id: string): Promise<void> {
+  return invoke("purge_project", { id });
+}
+
+export async function getTrashRetentionDays(): Promise<number> {
+  return invoke("get_trash_retention_days");
+}
+
+export async function setTrashRetentionDays(days: number): Promise<void> {
+  return invoke("set_trash_retention_days", { days });
+}
+
+export async function clearProjectData(
+  projectId: string,
+  what: ClearTarget
+): Promise<ClearedData> {
+  return invoke("clear_project_data", { projectId, what });
+}
+
+export async function getActiveJobs(projectId: string): Promise<ActiveJob[]> {
+  return invoke("get_active_jobs", { projectId });
+}
+
 export async function updateProject(
   id: string,
   name?: string,
@@ -58,6 +134,21 @@ export async function getAllProjectStats(): Promise<ProjectStats[]> {
   return invoke("get_all_project_stats");
 }
 
+export async function globalSearch(query: string): Promise<GlobalSearchResult> {
+  return invoke("global_search", { query });
+}
+
+export async function getDataDir(): Promise<DataDirInfo> {
+  return invoke("get_data_dir");
+}
+
+export async function setDataDir(
+  path: string | null,
+  copyExisting: boolean
+): Promise<DataDirInfo> {
+  return invoke("set_data_dir", { path, copyExisting });
+}
+
 export async function exportProject(
   projectId: string,
   destinationPath: string
@@ -73,8 +164,15 @@ export async function importProject(
 }
 
 // Database commands
-export async function getTables(projectId: string): Promise<TableInfo[]> {
-  return invoke("get_tables", { projectId });
+export async function getTables(
+  projectId: string,
+  includeAttached?: boolean
+): Promise<TableInfo[]> {
+  return invoke("get_tables", { projectId, includeAttached });
+}
+
+export async function listTablesFast(projectId: string): Promise<TableSummary[]> {
+  return invoke("list_tables_fast", { projectId });
 }
 
 export async function getTableSchema(
@@ -84,6 +182,28 @@ export async function getTableSchema(
   return invoke("get_table_schema", { projectId, tableName });
 }
 
+export async function getTableDdl(
+  projectId: string,
+  tableName: string
+): Promise<string> {
+  return invoke("get_table_ddl", { projectId, tableName });
+}
+
+// CREATE TABLE statements for every user table, separated by blank lines
+export async function getProjectSchemaSql(projectId: string): Promise<string> {
+  return invoke("get_project_schema_sql", { projectId });
+}
+
+export async function diffTables(
+  projectId: string,
+  tableA: string,
+  tableB: string,
+  compareRows?: boolean,
+  rowLimit?: number
+): Promise<TableDiff> {
+  return invoke("diff_tables", { projectId, tableA, tableB, compareRows, rowLimit });
+}
+
 export async function executeQuery(
   projectId: string,
   sql: string
@@ -91,15 +211,131 @@ export async function executeQuery(
   return invoke("execute_query", { projectId, sql });
 }
 
+export async function executeChatQuery(
+  projectId: string,
+  sql: string,
+  autoCorrect?: boolean
+): Promise<ChatQueryResult> {
+  return invoke("execute_chat_query", { projectId, sql, autoCorrect });
+}
+
+// Resolves once the query has finished streaming; stop early with cancelQueryStream
+export async function executeQueryStreamed(
+  projectId: string,
+  sql: string,
+  streamId: string,
+  onEvent: (event: QueryStreamEvent) => void
+): Promise<void> {
+  const channel = new Channel<QueryStreamEvent>();
+  channel.onmessage = onEvent;
+  return invoke("execute_query_streamed", { projectId, sql, streamId, channel });
+}
+
+export async function cancelQueryStream(streamId: string): Promise<void> {
+  return invoke("cancel_query_stream", { streamId });
+}
+
+export async function validateQuery(
+  projectId: string,
+  sql: string
+): Promise<QueryValidation> {
+  return invoke("validate_query", { projectId, sql });
+}
+
+export async function materializeQuery(
+  projectId: string,
+  sql: string,
+  tableName: string,
+  mode: ImportMode
+): Promise<ImportResult> {
+  return invoke("materialize_query", { projectId, sql, tableName, mode });
+}
+
 export async function queryTable(
   projectId: string,
   tableName: string,
   page: number,
   pageSize: number,
   orderBy?: string,
-  orderDesc?: boolean
+  orderDesc?: boolean,
+  includeRowid?: boolean
 ): Promise<QueryResult> {
-  return invoke("query_table", { projectId, tableName, page, pageSize, orderBy, orderDesc });
+  return invoke("query_table", {
+    projectId,
+    tableName,
+    page,
+    pageSize,
+    orderBy,
+    orderDesc,
+    includeRowid,
+  });
+}
+
+export async function queryTableSeek(
+  projectId: string,
+  tableName: string,
+  orderBy: string,
+  pageSize: number,
+  afterKey?: unknown
+): Promise<SeekPage> {
+  return invoke("query_table_seek", { projectId, tableName, afterKey, orderBy, pageSize });
+}
+
+export async function getRandomSample(
+  projectId: string,
+  tableName: string,
+  n: number
+): Promise<QueryResult> {
+  return invoke("get_random_sample", { projectId, tableName, n });
+}
+
+export async function countRows(
+  projectId: string,
+  tableName: string,
+  whereClause?: string,
+  exact?: boolean
+): Promise<RowCount> {
+  return invoke("count_rows", { projectId, tableName, whereClause, exact });
+}
+
+export async function getDistinctValues(
+  projectId: string,
+  tableName: string,
+  column: string,
+  limit?: number
+): Promise<DistinctValues> {
+  return invoke("get_distinct_values", { projectId, tableName, column, limit });
+}
+
+export async function copyResultAsTsv(
+  projectId: string,
+  sql: string
+): Promise<number> {
+  return invoke("copy_result_as_tsv", { projectId, sql });
+}
+
+export async function getCellValue(
+  projectId: string,
+  tableName: string,
+  column: string,
+  primaryKeyValue?: unknown,
+  rowId?: number
+): Promise<unknown> {
+  return invoke("get_cell_value", { projectId, tableName, column, primaryKeyValue, rowId });
+}
+
+export async function setDuckDbSettings(
+  projectId: string,
+  threads?: number,
+  memoryLimit?: string
+): Promise<DuckDbSettings> {
+  return invoke("set_duckdb_settings", { projectId, threads, memoryLimit });
+}
+
+export async function getDuckDbSettings(
+  projectId: string
+): Promise<DuckDbSettings> {
+  return invoke("get_duckdb_settings", { projectId });
 }
 
 export async function getProjectContext(
@@ -108,6 +344,77 @@ export async function getProjectContext(
   return invoke("get_project_context", { projectId });
 }
 
+export async function renameColumn(
+  projectId: string,
+  tableName: string,
+  oldName: string,
+  newName: string
+): Promise<TableSchema> {
+  return invoke("rename_column", { projectId, tableName, oldName, newName });
+}
+
+export async function addColumn(
+  projectId: string,
+  tableName: string,
+  name: string,
+  dataType: string,
+  defaultValue?: string
+): Promise<TableSchema> {
+  return invoke("add_column", {
+    projectId,
+    tableName,
+    name,
+    dataType,
+    default: defaultValue,
+  });
+}
+
+export async function addGeneratedColumn(
+  projectId: string,
+  tableName: string,
+  name: string,
+  expression: string
+): Promise<GeneratedColumn> {
+  return invoke("add_generated_column", {
+    projectId,
+    tableName,
+    name,
+    expression,
+  });
+}
+
+export async function dropColumn(
+  projectId: string,
+  tableName: string,
+  name: string
+): Promise<TableSchema> {
+  return invoke("drop_column", { projectId, tableName, name });
+}
+
+// Without keyColumns, only rows identical in every column count as duplicates
+export async function deduplicateTable(
+  projectId: string,
+  tableName: string,
+  keyColumns?: string[]
+): Promise<DedupeResult> {
+  return invoke("deduplicate_table", { projectId, tableName, keyColumns });
+}
+
+// Values that don't convert to newType become null and are counted in failedCasts
+export async function changeColumnType(
+  projectId: string,
+  tableName: string,
+  column: string,
+  newType: string
+): Promise<ColumnTypeChange> {
+  return invoke("change_column_type", {
+    projectId,
+    tableName,
+    column,
+    newType,
+  });
+}
+
 export async function deleteTable(
   projectId: string,
   tableName: string
@@ -115,6 +422,55 @@ export async function deleteTable(
   return invoke("delete_table", { projectId, tableName });
 }
 
+export async function getStorageBreakdown(
+  projectId: string
+): Promise<StorageBreakdown> {
+  return invoke("get_storage_breakdown", { projectId });
+}
+
+export async function checkpoint(projectId: string): Promise<void> {
+  return invoke("checkpoint", { projectId });
+}
+
+export async function listOpenConnections(): Promise<OpenConnection[]> {
+  return invoke("list_open_connections");
+}
+
+export async function closeConnection(projectId: string): Promise<void> {
+  return invoke("close_connection", { projectId });
+}
+
+export async function shutdown(): Promise<void> {
+  return invoke("shutdown");
+}
+
+export async function getCapabilities(
+  projectId: string
+): Promise<ConnectionCapabilities> {
+  return invoke("get_capabilities", { projectId });
+}
+
+export async function attachDatabase(
+  projectId: string,
+  filePath: string,
+  alias: string
+): Promise<AttachedDatabase> {
+  return invoke("attach_database", { projectId, filePath, alias });
+}
+
+export async function detachDatabase(
+  projectId: string,
+  alias: string
+): Promise<void> {
+  return invoke("detach_database", { projectId, alias });
+}
+
+export async function listAttachedDatabases(
+  projectId: string
+): Promise<AttachedDatabase[]> {
+  return invoke("list_attached_databases", { projectId });
+}
+
 // Import commands
 export async function previewImport(
   projectId: string,
@@ -123,13 +479,80 @@ export async function previewImport(
   return invoke("preview_import", { projectId, filePath });
 }
 
+export async function describeFile(filePath: string): Promise<ImportPreview> {
+  return invoke("describe_file", { filePath });
+}
+
+export async function peekFile(
+  filePath: string,
+  maxLines?: number
+): Promise<FilePeek> {
+  return invoke("peek_file", { filePath, maxLines });
+}
+
+export async function estimateRowCount(
+  projectId: string,
+  filePath: string
+): Promise<number | null> {
+  return invoke("estimate_row_count", { projectId, filePath });
+}
+
+// Without a table name or mode, the project's import defaults are used
 export async function importFile(
   projectId: string,
   filePath: string,
-  tableName: string,
-  mode: ImportMode
+  tableName?: string,
+  mode?: ImportMode,
+  options?: ImportOptions
+): Promise<ImportResult> {
+  return invoke("import_file", {
+    projectId,
+    filePath,
+    tableName,
+    mode,
+    options,
+  });
+}
+
+export async function getImportTarget(
+  projectId: string,
+  filePath: string
+): Promise<ImportTarget> {
+  return invoke("get_import_target", { projectId, filePath });
+}
+
+export async function setProjectImportDefaults(
+  projectId: string,
+  defaults: ImportDefaults
+): Promise<Project> {
+  return invoke("set_project_import_defaults", { projectId, defaults });
+}
+
+export async function getRecentImports(): Promise<RecentImport[]> {
+  return invoke("get_recent_imports");
+}
+
+export async function reimport(
+  projectId: string,
+  recentId: string
 ): Promise<ImportResult> {
-  return invoke("import_file", { projectId, filePath, tableName, mode });
+  return invoke("reimport", { projectId, recentId });
+}
+
+export async function validateImport(
+  projectId: string,
+  filePath: string,
+  tableName?: string,
+  mode?: ImportMode,
+  options?: ImportOptions
+): Promise<ImportValidation> {
+  return invoke("validate_import", {
+    projectId,
+    filePath,
+    tableName,
+    mode,
+    options,
+  });
 }
 
 export async function getSupportedExtensions(): Promise<string[]> {
@@ -145,12 +568,127 @@ export async function listOllamaModels(): Promise<OllamaModel[]> {
   return invoke("list_ollama_models");
 }
 
+export async function setProjectChatModel(
+  projectId: string,
+  model: string | null
+): Promise<Project> {
+  return invoke("set_project_chat_model", { projectId, model });
+}
+
+export async function getProjectChatModel(
+  projectId: string
+): Promise<string | null> {
+  return invoke("get_project_chat_model", { projectId });
+}
+
 export async function sendChatMessage(
   model: string,
   messages: [string, string][],
+  context?: string,
+  responseMode?: ResponseMode
+): Promise<void> {
+  await invoke("send_chat_message", { model, messages, context, responseMode });
+}
+
+// Non-streamed reply constrained to "json" or a JSON schema, returned parsed
+export async function sendChatMessageJson<T = unknown>(
+  model: string,
+  messages: [string, string][],
+  jsonSchema: "json" | Record<string, unknown>,
   context?: string
+): Promise<T> {
+  return invoke("send_chat_message", { model, messages, context, jsonSchema });
+}
+
+export async function sendChatMessageAndRun(
+  projectId: string,
+  model: string,
+  messages: [string, string][],
+  context?: string,
+  responseMode?: ResponseMode,
+  maxRows?: number
 ): Promise<void> {
-  return invoke("send_chat_message", { model, messages, context });
+  return invoke("send_chat_message_and_run", {
+    projectId,
+    model,
+    messages,
+    context,
+    responseMode,
+    maxRows,
+  });
+}
+
+export async function suggestVisualization(
+  queryResult: QueryResult
+): Promise<VizSuggestion> {
+  return invoke("suggest_visualization", { queryResult });
+}
+
+// measure may be omitted for "count" to count rows per group. bucket groups a
+// date/timestamp column by period; fillGaps adds the periods with no rows.
+export async function aggregate(
+  projectId: string,
+  tableName: string,
+  groupBy: string,
+  measure: string | null,
+  agg: AggregateFn,
+  bucket?: TimeBucket,
+  fillGaps?: boolean
+): Promise<AggregateResult> {
+  return invoke("aggregate", {
+    projectId,
+    tableName,
+    groupBy,
+    measure,
+    agg,
+    bucket,
+    fillGaps,
+  });
+}
+
+export async function parseVizSpec(text: string): Promise<VizSpec[]> {
+  return invoke("parse_viz_spec", { text });
+}
+
+export async function generateConversationTitle(
+  model: string,
+  message: string
+): Promise<string> {
+  return invoke("generate_conversation_title", { model, message });
+}
+
+export async function explainTable(
+  projectId: string,
+  tableName: string,
+  model: string
+): Promise<string> {
+  return invoke("explain_table", { projectId, tableName, model });
+}
+
+export async function summarizeTable(
+  projectId: string,
+  tableName: string,
+  model: string,
+  refresh?: boolean
+): Promise<TableNote> {
+  return invoke("summarize_table", { projectId, tableName, model, refresh });
+}
+
+// Progress arrives as "extraction-progress" events shaped like VectorizationProgress
+export async function extractStructured(
+  projectId: string,
+  tableName: string,
+  columns: string[],
+  schema: Record<string, unknown>,
+  model?: string
+): Promise<ExtractionResult> {
+  return invoke("extract_structured", {
+    projectId,
+    tableName,
+    columns,
+    schema,
+    model,
+  });
 }
 
 export async function pullOllamaModel(model: string): Promise<void> {
@@ -179,16 +717,38 @@ export async function getTextColumns(
 export async function vectorizeTable(
   projectId: string,
   tableName: string,
-  columns: string[]
+  columns: string[],
+  resume?: boolean,
+  metric?: SimilarityMetric,
+  normalize?: boolean,
+  model?: string,
+  whereClause?: string
 ): Promise<void> {
-  return invoke("vectorize_table", { projectId, tableName, columns });
+  return invoke("vectorize_table", {
+    projectId,
+    tableName,
+    columns,
+    resume,
+    metric,
+    normalize,
+    model,
+    whereClause,
+  });
 }
 
 export async function removeVectorization(
   projectId: string,
-  tableName: string
+  tableName: string,
+  model?: string
 ): Promise<void> {
-  return invoke("remove_vectorization", { projectId, tableName });
+  return invoke("remove_vectorization", { projectId, tableName, model });
+}
+
+export async function setProjectEmbeddingModel(
+  projectId: string,
+  model: string
+): Promise<Project> {
+  return invoke("set_project_embedding_model", { projectId, model });
 }
 
 export async function cancelVectorization(
@@ -201,9 +761,41 @@ export async function semanticSearch(
   projectId: string,
   tableName: string,
   query: string,
-  limit?: number
+  limit?: number,
+  metric?: SimilarityMetric,
+  model?: string
 ): Promise<SemanticSearchResult[]> {
-  return invoke("semantic_search", { projectId, tableName, query, limit });
+  return invoke("semantic_search", {
+    projectId,
+    tableName,
+    query,
+    limit,
+    metric,
+    model,
+  });
+}
+
+// Writes the full matching rows, plus a _similarity column, to filePath
+export async function exportSemanticSearch(
+  projectId: string,
+  tableName: string,
+  query: string,
+  filePath: string,
+  format: ExportFormat,
+  limit?: number,
+  metric?: SimilarityMetric,
+  model?: string
+): Promise<ExportResult> {
+  return invoke("export_semantic_search", {
+    projectId,
+    tableName,
+    query,
+    limit,
+    filePath,
+    format,
+    metric,
+    model,
+  });
 }
 
 // Conversation commands
@@ -251,6 +843,21 @@ export async function addMessage(
   return invoke("add_message", { projectId, conversationId, role, content });
 }
 
+export async function saveDraft(
+  projectId: string,
+  conversationId: string,
+  content: string
+): Promise<void> {
+  return invoke("save_draft", { projectId, conversationId, content });
+}
+
+export async function getDraft(
+  projectId: string,
+  conversationId: string
+): Promise<string | null> {
+  return invoke("get_draft", { projectId, conversationId });
+}
+
 // Saved query commands
 export async function listSavedQueries(
   projectId: string
@@ -285,13 +892,34 @@ export async function deleteSavedQuery(
 // Document commands
 export async function uploadDocument(
   projectId: string,
-  filePath: string
+  filePath: string,
+  chunkConfig?: ChunkConfig
 ): Promise<DocumentInfo> {
-  return invoke("upload_document", { projectId, filePath });
+  return invoke("upload_document", { projectId, filePath, chunkConfig });
 }
 
-export async function getDocuments(projectId: string): Promise<DocumentInfo[]> {
-  return invoke("get_documents", { projectId });
+export async function uploadDocumentText(
+  projectId: string,
+  title: string,
+  content: string,
+  fileType?: "txt" | "md",
+  chunkConfig?: ChunkConfig
+): Promise<DocumentInfo> {
+  return invoke("upload_document_text", {
+    projectId,
+    title,
+    content,
+    fileType,
+    chunkConfig,
+  });
+}
+
+export async function getDocuments(
+  projectId: string,
+  page?: number,
+  pageSize?: number
+): Promise<DocumentPage> {
+  return invoke("get_documents", { projectId, page, pageSize });
 }
 
 export async function getDocument(
@@ -308,6 +936,13 @@ export async function deleteDocument(
   return invoke("delete_document", { projectId, documentId });
 }
 
+export async function deleteDocuments(
+  projectId: string,
+  documentIds: string[]
+): Promise<BulkDocumentResult[]> {
+  return invoke("delete_documents", { projectId, documentIds });
+}
+
 export async function vectorizeDocument(
   projectId: string,
   documentId: string
@@ -315,6 +950,36 @@ export async function vectorizeDocument(
   return invoke("vectorize_document", { projectId, documentId });
 }
 
+// Takes effect after the batch of chunks currently being embedded
+export async function cancelDocumentVectorization(
+  documentId: string
+): Promise<void> {
+  return invoke("cancel_document_vectorization", { documentId });
+}
+
+// Embeds only new or changed chunks, leaving the rest of the document's vectors alone
+export async function revectorizeDocument(
+  projectId: string,
+  documentId: string
+): Promise<DocumentRevectorization> {
+  return invoke("revectorize_document", { projectId, documentId });
+}
+
+export async function vectorizeDocuments(
+  projectId: string,
+  documentIds: string[]
+): Promise<BulkDocumentResult[]> {
+  return invoke("vectorize_documents", { projectId, documentIds });
+}
+
+// Multimodal model (e.g. llava) that describes uploaded images; null clears it
+export async function setProjectVisionModel(
+  projectId: string,
+  model: string | null
+): Promise<Project> {
+  return invoke("set_project_vision_model", { projectId, model });
+}
+
 export async function getSupportedDocumentExtensions(): Promise<string[]> {
   return invoke("get_supported_document_extensions");
 }
@@ -322,9 +987,31 @@ export async function getSupportedDocumentExtensions(): Promise<string[]> {
 export async function semanticSearchDocuments(
   projectId: string,
   query: string,
-  limit?: number
+  limit?: number,
+  offset?: number,
+  metric?: SimilarityMetric
 ): Promise<DocumentSearchResult[]> {
-  return invoke("semantic_search_documents", { projectId, query, limit });
+  return invoke("semantic_search_documents", {
+    projectId,
+    query,
+    limit,
+    offset,
+    metric,
+  });
+}
+
+export async function getDocumentChunksPaginated(
+  projectId: string,
+  documentId: string,
+  page: number,
+  pageSize: number
+): Promise<DocumentChunkPage> {
+  return invoke("get_document_chunks_paginated", {
+    projectId,
+    documentId,
+    page,
+    pageSize,
+  });
 }
 
 export async function getDocumentChunksById(
@@ -334,3 +1021,16 @@ export async function getDocumentChunksById(
 ): Promise<DocumentSearchResult[]> {
   return invoke("get_document_chunks_by_id", { projectId, documentId, limit });
 }
+
+// Health commands
+export async function selfTest(): Promise<HealthCheck[]> {
+  return invoke("self_test");
+}
+
+export async function getLogPath(): Promise<string> {
+  return invoke("get_log_path");
+}
+
+export async function setLogLevel(level: LogLevel): Promise<void> {
+  return invoke("set_log_level", { level });
+}
@@ -532,9 +532,9 @@ export function HomePage() {
 						</DialogTitle>
 						<DialogDescription>
 							{deletingProjectIds.length === 1 ? (
-								<>Are you sure you want to delete "{projects.find(p => p.id === deletingProjectIds[0])?.name}"? This action cannot be undone.</>
+								<>Are you sure you want to delete "{projects.find(p => p.id === deletingProjectIds[0])?.name}"? It will be moved to the trash and can be restored later.</>
 							) : (
-								<>Are you sure you want to delete {deletingProjectIds.length} projects? This action cannot be undone.</>
+								<>Are you sure you want to delete {deletingProjectIds.length} projects? They will be moved to the trash and can be restored later.</>
 							)}
 						</DialogDescription>
 					</DialogHeader>
@@ -58,7 +58,8 @@ import {
 } from "@/components/documents";
 import {
 	openProject,
-	getTables,
+	listTablesFast,
+	countRows,
 	getTableSchema,
 	listProjects,
 	updateProject,
@@ -177,12 +178,19 @@ export function ProjectPage() {
 		queryFn: listProjects,
 	});
 
+	// Estimated counts keep the first render fast; the selected table gets an exact count
 	const { data: tables = [], isLoading: tablesLoading } = useQuery({
-		queryKey: ["tables", id],
-		queryFn: () => getTables(id!),
+		queryKey: ["tables", id, "fast"],
+		queryFn: () => listTablesFast(id!),
 		enabled: !!id,
 	});
 
+	const { data: selectedRowCount } = useQuery({
+		queryKey: ["tables", id, "count", selectedTable],
+		queryFn: () => countRows(id!, selectedTable!, undefined, true),
+		enabled: !!id && !!selectedTable,
+	});
+
 	const { data: tableSchema } = useQuery({
 		queryKey: ["table-schema", id, selectedTable],
 		queryFn: () => getTableSchema(id!, selectedTable!),
@@ -379,7 +387,10 @@ export function ProjectPage() {
 																				<Sparkles className="h-3.5 w-3.5 text-primary" />
 																			) : (
 																				<span className="opacity-60">
-																					{table.rowCount.toLocaleString()}
+																					{selectedTable === table.name &&
+																					selectedRowCount
+																						? selectedRowCount.count.toLocaleString()
+																						: `~${table.estimatedRowCount.toLocaleString()}`}
 																				</span>
 																			)
 																		}
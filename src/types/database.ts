@@ -1,3 +1,5 @@
+import type { VizSpec } from "./ollama";
+
 export interface TableInfo {
   name: string;
   rowCount: number;
@@ -6,20 +8,80 @@ export interface TableInfo {
   vectorizedColumns: string[];
 }
 
+// Catalog estimates from list_tables_fast, no table scans
+export interface TableSummary {
+  name: string;
+  estimatedRowCount: number;
+  columnCount: number;
+  estimatedSizeBytes: number | null;
+  isVectorized: boolean;
+}
+
+export interface StorageBreakdown {
+  fileSize: number;
+  categories: StorageCategory[]; // Sums to fileSize
+}
+
+export interface StorageCategory {
+  name:
+    | "user_tables"
+    | "embeddings"
+    | "documents"
+    | "document_chunks"
+    | "conversations"
+    | "other";
+  bytes: number;
+}
+
 export interface VectorizationStatus {
   tableName: string;
   isVectorized: boolean;
   vectorizedColumns: string[];
   embeddingCount: number;
   embeddingModel: string | null;
+  embeddingModels: string[];
+  lastVectorizedAt: string | null;
+  isPartial: boolean;
+  rowFilter: string | null;
+  needsReindex: boolean; // Rows were rebuilt since, so the embeddings are stale
 }
 
 export interface VectorizationProgress {
   tableName: string;
   totalRows: number;
   processedRows: number;
-  status: "pending" | "loading_model" | "processing" | "completed" | "cancelled" | "error";
+  status:
+    | "pending"
+    | "counting"
+    | "loading_model"
+    | "processing"
+    | "embedding_batch"
+    | "reloading_model"
+    | "completed"
+    | "cancelled"
+    | "error";
   error: string | null;
+  cacheHitRate: number | null;
+  batchIndex: number | null;
+  progressPercent: number;
+  etaSeconds: number | null;
+}
+
+export interface ExtractionResult {
+  tableName: string;
+  columns: string[];
+  rowsProcessed: number;
+  rowsFailed: number;
+  cancelled: boolean;
+}
+
+export type SimilarityMetric = "cosine" | "dot" | "euclidean";
+
+export type ExportFormat = "csv" | "parquet" | "json";
+
+export interface ExportResult {
+  filePath: string;
+  rowsExported: number;
 }
 
 export interface SemanticSearchResult {
@@ -40,11 +102,152 @@ export interface TableSchema {
   columns: ColumnInfo[];
 }
 
+export interface DedupeResult {
+  tableName: string;
+  keyColumns: string[]; // Every column when none were given
+  rowsBefore: number;
+  rowsRemoved: number;
+  embeddingsStale: boolean; // The table was vectorized and needs re-indexing
+}
+
+export interface ColumnTypeChange {
+  schema: TableSchema; // The table after the change
+  oldType: string;
+  failedCasts: number; // Non-null values that didn't convert and are now null
+}
+
+export interface GeneratedColumn {
+  tableName: string;
+  columnName: string;
+  expression: string;
+  viewName: string | null;
+}
+
+export interface SchemaChange {
+  column: string;
+  change: "added" | "removed" | "retyped"; // "added" means only in table B
+  typeA: string | null;
+  typeB: string | null;
+}
+
+export interface RowDiff {
+  count: number;
+  rows: QueryResult;
+  truncated: boolean;
+}
+
+export interface TableDiff {
+  schemaChanges: SchemaChange[];
+  comparedColumns: string[];
+  rowsOnlyInA: RowDiff | null;
+  rowsOnlyInB: RowDiff | null;
+}
+
 export interface QueryResult {
   columns: string[];
   rows: Record<string, unknown>[];
   rowCount: number;
   executionTimeMs: number;
+  affectedRows: number | null;
+  hasRowid: boolean;
+  estimatedCardinality: number | null;
+}
+
+export type ClearTarget =
+  | "tables"
+  | "embeddings"
+  | "documents"
+  | "conversations"
+  | "saved_queries"
+  | "all_user_data";
+
+export interface ClearedData {
+  tablesDropped: string[];
+  embeddingsRemoved: number;
+  documentsRemoved: number;
+  conversationsRemoved: number;
+  messagesRemoved: number;
+  savedQueriesRemoved: number;
+}
+
+export interface TableNote {
+  tableName: string;
+  summary: string;
+  model: string;
+  updatedAt: string;
+}
+
+// Messages from executeQueryStreamed: columns, then row batches, then complete
+export type QueryStreamEvent =
+  | { event: "columns"; data: { columns: string[] } }
+  | { event: "rowBatch"; data: { rows: Record<string, unknown>[] } }
+  | {
+      event: "complete";
+      data: {
+        rowCount: number;
+        executionTimeMs: number;
+        affectedRows: number | null;
+        cancelled: boolean;
+      };
+    };
+
+export interface SeekPage {
+  result: QueryResult;
+  lastKey: unknown;
+  hasMore: boolean;
+}
+
+export interface NameSuggestion {
+  kind: "table" | "column";
+  missing: string;
+  didYouMean: string;
+  confidence: number;
+  unambiguous: boolean;
+}
+
+export type AggregateFn = "count" | "sum" | "avg" | "min" | "max";
+
+export type TimeBucket = "day" | "week" | "month" | "year";
+
+export interface AggregateResult {
+  result: QueryResult;
+  viz: VizSpec; // sql is the generated query
+}
+
+export interface ChatQueryResult {
+  sql: string; // What ran, after any correction
+  result: QueryResult | null;
+  error: string | null;
+  didYouMean: NameSuggestion | null;
+  corrected: boolean;
+}
+
+export interface QueryValidation {
+  valid: boolean;
+  columns: ColumnInfo[];
+  error: QueryError | null;
+}
+
+export interface QueryError {
+  message: string;
+  line: number | null;
+  column: number | null;
+}
+
+export interface RowCount {
+  count: number;
+  exact: boolean;
+}
+
+export interface DistinctValue {
+  value: unknown;
+  count: number;
+}
+
+export interface DistinctValues {
+  column: string;
+  values: DistinctValue[];
+  truncated: boolean;
 }
 
 export interface SortConfig {
@@ -68,3 +271,22 @@ export interface TableContext {
 export interface ProjectContext {
   tables: TableContext[];
 }
+
+export interface ConnectionCapabilities {
+  listCosineSimilarity: boolean;
+  arrayCosineSimilarity: boolean;
+  vssExtension: boolean;
+  excelReader: boolean;
+}
+
+export interface OpenConnection {
+  projectId: string;
+  lastUsedAt: string;
+  locked: boolean;
+}
+
+export interface AttachedDatabase {
+  alias: string;
+  filePath: string;
+  dbType: "duckdb" | "sqlite";
+}
@@ -13,6 +13,7 @@ export interface Document {
   content: string;
   uploadedAt: string;
   isVectorized: boolean;
+  sourcePath: string | null; // Original file for image documents
 }
 
 export interface DocumentInfo {
@@ -26,6 +27,43 @@ export interface DocumentInfo {
   uploadedAt: string;
 }
 
+export interface BulkDocumentResult {
+  documentId: string;
+  success: boolean;
+  error: string | null;
+}
+
+export interface DocumentRevectorization {
+  documentId: string;
+  embeddedChunks: number;
+  unchangedChunks: number;
+}
+
+export interface DocumentPage {
+  documents: DocumentInfo[];
+  total: number;
+  page: number;
+  pageSize: number;
+}
+
+export interface DocumentChunk {
+  id: string;
+  documentId: string;
+  chunkIndex: number;
+  chunkType: "paragraph" | "section" | "heading";
+  content: string;
+  startOffset: number; // Character index into Document.content
+  endOffset: number;
+  headingPath: string | null;
+}
+
+export interface DocumentChunkPage {
+  chunks: DocumentChunk[];
+  total: number;
+  page: number;
+  pageSize: number;
+}
+
 export interface DocumentVectorizationProgress {
   documentId: string;
   documentName: string;
@@ -39,6 +77,12 @@ export interface DocumentVectorizationProgress {
     | "cancelled"
     | "error";
   error: string | null;
+  progressPercent: number;
+  etaSeconds: number | null;
+}
+
+export interface ChunkConfig {
+  includeHeadingChunks?: boolean;
 }
 
 export interface HeadingInfo {
@@ -51,5 +95,6 @@ export interface DocumentSearchResult {
   documentId: string;
   documentName: string;
   content: string;
+  headingPath: string | null;
   similarity: number;
 }
@@ -0,0 +1,7 @@
+export interface HealthCheck {
+  name: string;
+  ok: boolean;
+  detail: string;
+}
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
@@ -4,17 +4,67 @@ export interface ImportPreview {
   columns: PreviewColumn[];
   sampleRows: unknown[][];
   totalRowsEstimate: number | null;
+  detectedDelimiter: string | null;
+  detectedHasHeader: boolean | null;
+  detectedDateFormat: string | null;
+  duplicateColumns: string[];
 }
 
 export interface PreviewColumn {
   name: string;
   inferredType: string;
+  nullRate: number;
+  distinctEstimate: number;
+}
+
+export interface FilePeek {
+  fileName: string;
+  fileSize: number;
+  lines: string[];
+  encoding: "utf-8" | "utf-8-bom" | "utf-16le" | "utf-16be" | "ascii" | "unknown";
+  lineEnding: "lf" | "crlf" | "cr" | "mixed" | null;
+  isBinary: boolean;
+  truncated: boolean;
 }
 
 export interface ImportResult {
   tableName: string;
   rowsImported: number;
   columnsCount: number;
+  tablesCreated: string[];
+}
+
+export interface ImportValidation {
+  valid: boolean;
+  error: string | null;
+  warnings: string[];
+  rowsToImport: number | null;
 }
 
 export type ImportMode = "create" | "replace" | "append";
+
+export interface ImportDefaults {
+  mode: ImportMode | null;
+  tableNameTemplate: string | null; // May use {filename} and {date}
+}
+
+export interface ImportTarget {
+  tableName: string;
+  mode: ImportMode;
+}
+
+export interface ImportOptions {
+  dedupeColumns?: boolean;
+  addRowId?: boolean;
+  addImportTimestamp?: boolean;
+}
+
+export interface RecentImport {
+  id: string;
+  projectId: string;
+  filePath: string;
+  tableName: string;
+  mode: ImportMode;
+  options: ImportOptions;
+  importedAt: string;
+}
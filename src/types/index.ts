@@ -3,3 +3,4 @@ export * from "./database";
 export * from "./ollama";
 export * from "./import";
 export * from "./document";
+export * from "./health";
@@ -1,3 +1,5 @@
+import type { QueryResult } from "./database";
+
 export interface OllamaStatus {
   connected: boolean;
   version?: string;
@@ -8,6 +10,7 @@ export interface OllamaModel {
   size: number;
   digest: string;
   modifiedAt: string;
+  isEmbedding: boolean;
 }
 
 export interface OllamaPullProgress {
@@ -17,6 +20,32 @@ export interface OllamaPullProgress {
   completed?: number;
 }
 
+export type ResponseMode = "duckbake" | "raw_sql" | "plain";
+
+export type VizType = "table" | "bar" | "line" | "pie";
+
+export interface VizSpec {
+  sql: string;
+  viz: VizType;
+  xKey: string | null;
+  yKey: string | null;
+  start: number;
+  end: number;
+}
+
+export interface VizSuggestion {
+  viz: VizType;
+  xKey: string | null;
+  yKey: string | null;
+  confidence: number;
+}
+
+export interface DuckbakeBlockResult {
+  block: VizSpec;
+  result: QueryResult | null;
+  error: string | null;
+}
+
 export interface ChatMessage {
   id: string;
   role: "user" | "assistant";
@@ -31,6 +60,7 @@ export interface Conversation {
   title: string;
   createdAt: string;
   updatedAt: string;
+  model: string | null;
 }
 
 export interface ConversationWithMessages extends Conversation {
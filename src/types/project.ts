@@ -1,3 +1,5 @@
+import type { ImportDefaults } from "./import";
+
 export interface Project {
   id: string;
   name: string;
@@ -5,6 +7,23 @@ export interface Project {
   createdAt: string;
   updatedAt: string;
   databaseFile: string;
+  duckdbSettings: DuckDbSettings;
+  embeddingModel: string | null;
+  embeddingDimension: number | null;
+  visionModel: string | null;
+  chatModel: string | null;
+  importDefaults: ImportDefaults;
+  deletedAt: string | null;
+}
+
+export interface ActiveJob {
+  operation: string;
+  startedAt: string;
+}
+
+export interface DuckDbSettings {
+  threads: number | null;
+  memoryLimit: string | null;
 }
 
 export interface ProjectSummary {
@@ -20,6 +39,30 @@ export interface CreateProjectInput {
   description: string;
 }
 
+export interface DataDirInfo {
+  dataDir: string;
+  defaultDataDir: string;
+  source: "env" | "config" | "default";
+}
+
+export interface GlobalHit {
+  projectId: string;
+  projectName: string;
+  kind: "table" | "document" | "saved_query" | "conversation";
+  label: string;
+}
+
+export interface GlobalSearchError {
+  projectId: string;
+  projectName: string;
+  error: string;
+}
+
+export interface GlobalSearchResult {
+  hits: GlobalHit[];
+  errors: GlobalSearchError[];
+}
+
 export interface ProjectStats {
   projectId: string;
   tableCount: number;
@@ -28,4 +71,5 @@ export interface ProjectStats {
   savedQueryCount: number;
   documentCount: number;
   storageSize: number;
+  error: string | null;
 }